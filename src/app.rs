@@ -1,480 +1,3193 @@
 //! Implements the `paper` application logic for converting an [`Input`] into [`Output`]s.
+mod command;
+mod command_history;
+mod completion;
+mod encoding;
+mod history;
+mod layout;
+mod line_editor;
+mod picker;
+mod registers;
+mod results;
+mod search;
+mod settings;
+mod signs;
+mod substitution;
 pub(crate) mod translate;
 
+pub(crate) use {
+    history::{History, Replacement},
+    signs::VcsChanges,
+    substitution::FileReplacement,
+};
+
 use {
     crate::{
-        io::{Dimensions, DocEdit, File, Input, Output, RowText, Style, StyledText, Unit},
+        io::{
+            Dimensions, DocEdit, File, Input, Output, Region, RowText, SavedFile, Style,
+            StyledText, Unit,
+        },
         orient,
     },
+    command::{Command, CommandSpec, SubstituteScope, ViewAlignment, COMMANDS},
+    command_history::CommandHistory,
+    completion::Target,
     core::{
+        cmp,
         convert::{TryFrom, TryInto},
+        iter, mem,
         num::TryFromIntError,
-        slice::Iter,
+        ops,
+        slice::{Iter, IterMut},
     },
-    fehler::throws,
+    fehler::{throw, throws},
+    history::Change,
+    layout::Layout,
+    line_editor::LineEditor,
     log::trace,
     lsp_types::{
-        DocumentSymbol, Position, Range, ShowMessageRequestParams, TextDocumentIdentifier,
-        TextDocumentItem,
+        DocumentSymbol, MessageType, Position, PublishDiagnosticsParams, Range, ShowMessageParams,
+        ShowMessageRequestParams, TextDocumentContentChangeEvent, TextDocumentIdentifier,
+        TextDocumentItem, TextDocumentSyncKind,
     },
-    translate::{Interpreter, Operation, SelectionMovement},
+    picker::Picker,
+    regex::Regex,
+    registers::{Register, Registers, CLIPBOARD_REGISTER},
+    results::ResultList,
+    ropey::{Rope, RopeSlice},
+    search::Search,
+    settings::{LineNumbers, Settings, OPTIONS},
+    signs::{Bookmarks, Diagnostics, SignSource},
+    std::{rc::Rc, sync::Arc},
+    substitution::{Location, Substitution},
+    translate::{
+        CommandRecall, ConfirmAction, InsertPlacement, Interpreter, ListMovement, Operation,
+        ScrollAmount, SearchDirection, SelectionMovement,
+    },
+    unicode_segmentation::UnicodeSegmentation,
+    unicode_width::{UnicodeWidthChar, UnicodeWidthStr},
     url::Url,
 };
 
 /// The processor of the application.
 #[derive(Debug, Default)]
 pub(crate) struct Processor {
-    /// The currently visible pane.
+    /// The focused pane.
     pane: Pane,
-    /// The current command.
-    command: String,
+    /// The panes that are not focused.
+    panes: Vec<Pane>,
+    /// The arrangement of the panes within the page.
+    layout: Layout,
+    /// The [`Dimensions`] of the page.
+    page: Dimensions,
+    /// The tabs that are not shown, in the order of the tab strip.
+    tabs: Vec<Tab>,
+    /// The index within the tab strip of the tab that is shown.
+    tab: usize,
+    /// The current command, excluding its `:` prompt.
+    command: LineEditor,
+    /// The commands that have been executed.
+    command_history: CommandHistory,
     /// Translates input into operations.
     interpreter: Interpreter,
+    /// The current values of all options.
+    settings: Settings,
+    /// The registers that hold yanked text.
+    registers: Registers,
+    /// The name of the register used by the next yank, delete or put.
+    register: Option<char>,
+    /// Where the text read from the system clipboard is to be put.
+    paste_placement: Option<InsertPlacement>,
+    /// The file finder, if it is open.
+    picker: Option<Picker>,
+    /// The result list of the most recent search, if it is open.
+    results: Option<ResultList>,
+    /// The search of the document that is being edited.
+    search: Option<Search>,
+    /// The action that is being confirmed.
+    confirming: Option<ConfirmAction>,
+    /// The substitution whose replacements are being confirmed.
+    substitution: Option<Substitution>,
+    /// The [`Range`] that is selected once the document of the URL is created.
+    jump: Option<(Url, Range)>,
 }
 
 impl Processor {
-    /// Creates a new [`Processor`].
-    pub(crate) fn new() -> Self {
-        Self::default()
-    }
-
-    /// Processes `input` and generates [`Output`].
-    #[throws(ScopeFromRangeError)]
-    pub(crate) fn process(&mut self, input: Input) -> Vec<Output> {
-        self.interpreter
-            .translate(input)
-            .map_or_else(|| Ok(Vec::new()), |operation| self.operate(operation))?
+    /// Creates a new [`Processor`] that recalls the previously executed `commands`.
+    pub(crate) fn new(commands: Vec<String>) -> Self {
+        Self {
+            command_history: CommandHistory::new(commands),
+            ..Self::default()
+        }
     }
 
-    /// Performs `operation` and returns the appropriate [`Output`]s.
+    /// Runs `command` and returns the appropriate [`Output`]s.
     #[throws(ScopeFromRangeError)]
-    pub(crate) fn operate(&mut self, operation: Operation) -> Vec<Output> {
+    fn run(&mut self, command: Command) -> Vec<Output> {
         let mut outputs = Vec::new();
 
-        match operation {
-            Operation::Resize { dimensions } => {
-                self.pane.update_size(dimensions, &mut outputs);
+        match command {
+            Command::Open { path } => {
+                outputs.push(Output::OpenFile { path });
             }
-            Operation::Confirm(action) => {
-                outputs.push(Output::Question {
-                    request: ShowMessageRequestParams::from(action),
-                });
+            Command::Write => {
+                outputs.push(self.pane.save_output().unwrap_or_else(|| {
+                    notify(MessageType::Error, "no document to write".to_string())
+                }));
             }
-            Operation::Reset => {
-                self.command.clear();
-                self.pane.update(&mut outputs);
+            Command::Quit => {
+                outputs.append(&mut self.operate(Operation::Quit)?);
             }
-            Operation::StartCommand => {
-                self.command = ":".to_string();
-                outputs.push(Output::Command {
-                    command: self.command.clone(),
-                });
+            Command::Set(setting) => {
+                self.settings.apply(setting);
+                self.apply_settings_to_panes();
+                self.interpreter.set_scroll_keys(self.settings.scroll_keys);
+
+                // Arranging the panes lays out their documents again.
+                outputs.append(&mut self.arrange());
             }
-            Operation::Collect(ch) => {
-                self.command.push(ch);
-                outputs.push(Output::Command {
-                    command: self.command.clone(),
+            Command::Help { command: spec } => {
+                outputs.push(Output::UpdateView {
+                    region: self.pane.region,
+                    rows: help_rows(spec),
                 });
             }
-            Operation::Execute => {
-                if let Some(path) = self.command.strip_prefix(":open ") {
-                    outputs.push(Output::OpenFile {
-                        path: path.to_string(),
-                    });
-                }
+            Command::Undo => {
+                outputs.append(&mut self.operate(Operation::Undo)?);
             }
-            Operation::Quit => {
-                if let Some(output) = self.pane.close_doc() {
-                    outputs.push(output);
-                }
+            Command::Redo => {
+                outputs.append(&mut self.operate(Operation::Redo)?);
+            }
+            Command::ToggleWrap => {
+                outputs.extend(self.pane.toggle_wrap()?);
+            }
+            Command::ToggleBookmark => {
+                outputs.extend(self.pane.toggle_bookmark()?);
+            }
+            Command::AlignView(alignment) => {
+                outputs.extend(self.pane.align_view(alignment)?);
+            }
+            Command::Grep { pattern } => {
+                let results = ResultList::new(pattern.clone());
 
-                outputs.push(Output::Quit);
+                outputs.push(Output::Grep { pattern });
+                outputs.append(&mut self.results_outputs(&results));
+                self.results = Some(results);
+                self.interpreter.start_list();
             }
-            Operation::CreateDoc(file) => {
-                outputs.append(&mut self.pane.create_doc(file)?);
+            Command::ListDocs => {
+                outputs.push(Output::UpdateView {
+                    region: self.pane.region,
+                    rows: self.pane.list_rows(),
+                });
             }
-            Operation::Scroll(direction) => {
-                if let Some(output) = self.pane.scroll(direction)? {
-                    outputs.push(output);
+            Command::SwitchDoc { number } => {
+                outputs.push(
+                    number
+                        .checked_sub(1)
+                        .and_then(|index| self.pane.switch_doc(index))
+                        .unwrap_or_else(|| {
+                            notify(MessageType::Error, format!("no document {}", number))
+                        }),
+                );
+            }
+            Command::CloseDoc { number } => {
+                let index = number.map_or(Some(self.pane.current), |number| number.checked_sub(1));
+
+                // Closing the last view of a modified document would discard its changes.
+                if let Some(doc) = index
+                    .and_then(|index| self.pane.docs.get(index))
+                    .filter(|doc| doc.is_modified() && !self.is_shown_elsewhere(doc.url()))
+                {
+                    outputs.push(notify(
+                        MessageType::Error,
+                        format!("`{}` has unsaved changes", doc.name()),
+                    ));
+                } else {
+                    match index.and_then(|index| self.pane.close_doc(index)) {
+                        Some((doc, mut close_outputs)) => {
+                            outputs.extend(self.release(doc));
+                            outputs.append(&mut close_outputs);
+                        }
+                        None => outputs.push(notify(
+                            MessageType::Error,
+                            "no document to close".to_string(),
+                        )),
+                    }
                 }
             }
-            Operation::ChangeSelection(movement) => {
-                if let Some(output) = self.pane.change_selection(&movement)? {
-                    outputs.push(output);
+            Command::Substitute {
+                pattern,
+                replacement,
+                scope,
+            } => match Regex::new(&pattern) {
+                Ok(regex) => {
+                    outputs.append(&mut self.start_substitution(regex, replacement, scope)?);
                 }
+                Err(error) => outputs.push(notify(MessageType::Error, error.to_string())),
+            },
+        }
+
+        outputs
+    }
+
+    /// Starts confirming the replacements of the matches of `regex` within `scope` by `replacement`, returning the appropriate [`Output`]s.
+    #[throws(ScopeFromRangeError)]
+    fn start_substitution(
+        &mut self,
+        regex: Regex,
+        replacement: String,
+        scope: SubstituteScope,
+    ) -> Vec<Output> {
+        let mut outputs = Vec::new();
+        let bounds = match scope {
+            SubstituteScope::Selection => {
+                self.pane.doc().map(Document::selection_range).transpose()?
             }
+            SubstituteScope::Document | SubstituteScope::Project => None,
         };
+        let mut substitution = Substitution::new(regex, replacement, bounds);
+
+        match (self.pane.doc(), scope) {
+            (Some(doc), _) => doc.add_candidates(&mut substitution),
+            (None, SubstituteScope::Project) => {}
+            (None, SubstituteScope::Document | SubstituteScope::Selection) => {
+                outputs.push(notify(
+                    MessageType::Error,
+                    "no document to substitute".to_string(),
+                ));
+                return outputs;
+            }
+        }
 
-        outputs.push(Output::UpdateHeader);
-        trace!("outputs: {:?}", outputs);
+        if scope == SubstituteScope::Project {
+            // The matches within the files are added as they are found.
+            outputs.push(Output::Grep {
+                pattern: substitution.pattern().to_string(),
+            });
+            substitution.start_search(self.open_urls());
+        }
 
+        self.confirming = Some(ConfirmAction::Replace);
+        self.interpreter.start_replace();
+        self.substitution = Some(substitution);
+        outputs.append(&mut self.substitution_outputs()?);
         outputs
     }
-}
 
-/// A view of the document.
-#[derive(Debug, Default)]
-struct Pane {
-    /// The document in the pane.
-    doc: Option<Document>,
-    /// The [`Dimensions`] of the pane.
-    size: Dimensions,
-}
+    /// Returns the [`Output`]s that ask to confirm the next replacement of the substitution, or that apply the accepted replacements once every replacement has been confirmed.
+    #[throws(ScopeFromRangeError)]
+    fn substitution_outputs(&mut self) -> Vec<Output> {
+        let mut outputs = Vec::new();
 
-impl Pane {
-    /// Updates `self`.
-    fn update(&mut self, outputs: &mut Vec<Output>) {
-        if let Some(doc) = self.doc.as_mut() {
-            outputs.push(doc.change_output());
+        if let Some(substitution) = self.substitution.take() {
+            if substitution.is_done() {
+                outputs.append(&mut self.substitute(substitution)?);
+            } else {
+                outputs.push(match substitution.preview() {
+                    Some(preview) => Output::Question {
+                        region: self.pane.region,
+                        request: ShowMessageRequestParams {
+                            typ: MessageType::Info,
+                            message: format!("{}\n\n{}", preview, ConfirmAction::Replace),
+                            actions: None,
+                        },
+                    },
+                    None => Output::Command {
+                        command: format!("searching for `{}`...", substitution.pattern()),
+                        cursor: None,
+                    },
+                });
+                self.substitution = Some(substitution);
+            }
         }
+
+        outputs
     }
 
-    /// Updates the size of `self` to match `dimensions`;
-    fn update_size(&mut self, dimensions: Dimensions, outputs: &mut Vec<Output>) {
-        self.size = dimensions;
+    /// Applies the accepted replacements of `substitution`, returning the appropriate [`Output`]s.
+    ///
+    /// The replacements within the document are undone as a single step.
+    #[throws(ScopeFromRangeError)]
+    fn substitute(&mut self, substitution: Substitution) -> Vec<Output> {
+        let mut outputs = Vec::new();
+        let summary = substitution.summary();
+        let (document, files) = substitution.into_replacements();
+
+        self.confirming = None;
+        self.interpreter.end_replace();
 
-        if let Some(doc) = self.doc.as_mut() {
-            doc.dimensions = dimensions;
-            outputs.push(doc.change_output());
+        if let Some(output) = self.pane.substitute(document)? {
+            outputs.push(output);
+            outputs.extend(self.pane.history_output());
+        } else {
+            self.pane.update(&mut outputs);
         }
+
+        for (url, replacements) in files {
+            outputs.push(Output::ReplaceInFile { url, replacements });
+        }
+
+        outputs.push(notify(MessageType::Info, summary));
+        outputs
     }
 
-    /// Opens a document at `path`.
-    #[throws(OverflowError)]
-    fn create_doc(&mut self, file: File) -> Vec<Output> {
+    /// Arranges the panes within the page, returning the [`Output`]s that show the panes and their borders.
+    fn arrange(&mut self) -> Vec<Output> {
         let mut outputs = Vec::new();
-        let doc = Document::new(file, self.size)?;
-        let output = doc.open_output();
+        let arrangement = self.layout.arrange(self.page);
+
+        for (id, region) in arrangement.panes {
+            if let Some(pane) = iter::once(&mut self.pane)
+                .chain(self.panes.iter_mut())
+                .find(|pane| pane.id == id)
+            {
+                pane.set_region(region, &mut outputs);
+            }
+        }
 
-        if let Some(old_doc) = self.doc.replace(doc) {
-            outputs.push(old_doc.close());
+        for (region, direction) in arrangement.borders {
+            outputs.push(Output::UpdateView {
+                region,
+                rows: layout::border_rows(region, direction),
+            });
         }
 
-        outputs.push(output);
         outputs
     }
 
-    /// Change selection of `self` as described by `movement`.
+    /// Updates the documents of the other panes that show the file of the document of the focused pane, returning the [`Output`]s that show them.
     #[throws(ScopeFromRangeError)]
-    fn change_selection(&mut self, movement: &SelectionMovement) -> Option<Output> {
-        self.doc
-            .as_mut()
-            .map(|doc| {
-                doc.change_selection(movement)?;
-                doc.rows().map(|rows| Output::UpdateView { rows })
-            })
-            .transpose()?
+    fn sync_panes(&mut self) -> Vec<Output> {
+        let mut outputs = Vec::new();
+
+        if let Some(doc) = self.pane.doc() {
+            for pane in &mut self.panes {
+                outputs.extend(pane.sync(doc)?);
+            }
+
+            // The panes of the tabs that are not shown are shown once their tab is shown.
+            for pane in self.tabs.iter_mut().flat_map(Tab::panes_mut) {
+                let _ = pane.sync(doc)?;
+            }
+        }
+
+        outputs
     }
 
-    /// Returns the [`Output`] to close the [`Document`] of `self`.
-    fn close_doc(&mut self) -> Option<Output> {
-        self.doc.take().map(Document::close)
+    /// Returns the [`Output`]s that send the whole text of each document that changed since it was opened, once per file.
+    ///
+    /// The changes sent before the position encoding and sync were negotiated counted UTF-16 code units and were incremental, which the negotiated ones may misread.
+    fn resend_changed_docs(&mut self) -> Vec<Output> {
+        let mut outputs = Vec::new();
+        let mut resent: Vec<Url> = Vec::new();
+
+        if self.settings.sync == TextDocumentSyncKind::None {
+            return outputs;
+        }
+
+        for doc in iter::once(&mut self.pane)
+            .chain(&mut self.panes)
+            .chain(self.tabs.iter_mut().flat_map(Tab::panes_mut))
+            .flat_map(|pane| pane.docs.iter_mut())
+            .filter(|doc| doc.version() != 0)
+        {
+            // Every document of a file shares its version, so each moves to the version that is resent.
+            doc.version = doc.version.saturating_add(1);
+
+            if !resent.contains(doc.url()) {
+                resent.push(doc.url().clone());
+                outputs.push(doc.resend_output());
+            }
+        }
+
+        outputs
     }
 
-    /// Scrolls `self` towards `direction`.
-    #[throws(ScopeFromRangeError)]
-    fn scroll(&mut self, direction: orient::ScreenDirection) -> Option<Output> {
-        self.doc
-            .as_mut()
-            .map(|doc| {
-                doc.scroll(direction);
-                doc.rows().map(|rows| Output::UpdateView { rows })
-            })
-            .transpose()?
+    /// Applies the settings to the documents of every pane of every tab.
+    fn apply_settings_to_panes(&mut self) {
+        for pane in iter::once(&mut self.pane)
+            .chain(&mut self.panes)
+            .chain(self.tabs.iter_mut().flat_map(Tab::panes_mut))
+        {
+            pane.apply_settings(self.settings);
+        }
     }
-}
 
-/// A [`Range`] of text that can be selected.
-#[derive(Clone, Debug)]
-struct Symbol {
-    /// The [`Range`] of `Self`.
-    range: Range,
-    /// The children [`Symbol`]s of `Self`.
-    children: Vec<Symbol>,
-}
+    /// Returns the URL of each file shown by a document of any pane of any tab.
+    fn open_urls(&self) -> Vec<Url> {
+        let mut urls: Vec<Url> = Vec::new();
+
+        for doc in iter::once(&self.pane)
+            .chain(&self.panes)
+            .chain(self.tabs.iter().flat_map(Tab::panes))
+            .flat_map(|pane| pane.docs.iter())
+        {
+            if !urls.contains(doc.url()) {
+                urls.push(doc.url().clone());
+            }
+        }
 
-impl Symbol {
-    /// Creates the default root [`Symbol`].
-    #[throws(OverflowError)]
-    fn create_root(text: &str, last_line_length: u32) -> Self {
-        u32::try_from(text.lines().count()).and_then(|line_count| {
-            Ok(Self {
-                range: Range::new(
-                    Position::new(0, 0),
-                    Position::new(line_count.saturating_sub(1), last_line_length),
-                ),
-                children: (0_u32..)
-                    .zip(text.lines())
-                    .map(|(line, line_text)| {
-                        line_text.len().try_into().map(|line_len| Self {
-                            range: Range::new(
-                                Position::new(line, 0),
-                                Position::new(line, line_len),
-                            ),
-                            children: (0_u32..0)
-                                .zip(line_text.chars())
-                                .map(|(character, _)| Self {
-                                    range: Range::new(
-                                        Position::new(line, character),
-                                        Position::new(line, character.saturating_add(1)),
-                                    ),
-                                    children: Vec::new(),
-                                })
-                                .collect(),
-                        })
-                    })
-                    .collect::<Result<Vec<Self>, _>>()?,
-            })
-        })?
+        urls
     }
-}
 
-impl From<DocumentSymbol> for Symbol {
-    fn from(symbol: DocumentSymbol) -> Self {
-        let mut children = Vec::new();
+    /// Returns if a pane other than the focused pane, of any tab, shows the file at `url`.
+    fn is_shown_elsewhere(&self, url: &Url) -> bool {
+        self.panes
+            .iter()
+            .chain(self.tabs.iter().flat_map(Tab::panes))
+            .any(|pane| pane.find_doc(url).is_some())
+    }
 
-        if let Some(symbol_children) = symbol.children {
-            for child in symbol_children.into_iter().rev() {
-                children.push(child.into());
+    /// Records that the documents of `file` at the version that was written match their file.
+    ///
+    /// Returns the [`Output`] that stores their history, keyed to the written text. Documents edited since the write remain modified.
+    fn mark_saved(&mut self, file: &SavedFile) -> Option<Output> {
+        let mut saved: Option<Document> = None;
+
+        for doc in iter::once(&mut self.pane)
+            .chain(&mut self.panes)
+            .chain(self.tabs.iter_mut().flat_map(Tab::panes_mut))
+            .flat_map(|pane| pane.docs.iter_mut())
+            .filter(|doc| *doc.url() == file.url && doc.version() == file.version)
+        {
+            doc.changes.clone_from(&file.changes);
+
+            // Documents at the same version share their history.
+            if let Some(saved) = saved.as_ref() {
+                doc.saved_version = saved.saved_version;
+                doc.saved_text = saved.saved_text.clone();
+                doc.history = Arc::clone(&saved.history);
+            } else {
+                doc.mark_saved();
+                saved = Some(doc.clone());
             }
         }
 
-        Self {
-            range: symbol.range,
-            children,
+        saved.as_ref().map(Document::history_output)
+    }
+
+    /// Shows the diagnostics of `params` in the documents of its file.
+    ///
+    /// Diagnostics published for another version of a document are ignored.
+    fn diagnose(&mut self, params: &PublishDiagnosticsParams) {
+        let diagnostics = Diagnostics::new(&params.diagnostics);
+
+        for doc in iter::once(&mut self.pane)
+            .chain(&mut self.panes)
+            .chain(self.tabs.iter_mut().flat_map(Tab::panes_mut))
+            .flat_map(|pane| pane.docs.iter_mut())
+            .filter(|doc| {
+                *doc.url() == params.uri
+                    && params
+                        .version
+                        .is_none_or(|version| version == doc.version())
+            })
+        {
+            doc.diagnostics.clone_from(&diagnostics);
         }
     }
-}
 
-/// A line in a [`Document`].
-#[derive(Clone, Debug)]
-struct Line {
-    /// The index within the [`Document`] of the first row of `Self`.
-    first_row: Row,
-    /// The start and end indexes of each row in `Self`.
-    rows: Vec<(usize, usize)>,
-}
+    /// Returns the [`Output`] to close `doc` unless a pane of any tab still shows its file.
+    fn release(&self, doc: Document) -> Option<Output> {
+        (!iter::once(&self.pane)
+            .chain(&self.panes)
+            .chain(self.tabs.iter().flat_map(Tab::panes))
+            .any(|pane| pane.find_doc(doc.url()).is_some()))
+        .then(|| doc.close())
+    }
 
-impl Line {
-    /// Creates a new [`Line`].
-    fn new(first_row: Row, rows: Vec<(usize, usize)>) -> Self {
-        Self { first_row, rows }
+    /// Removes the panes of the shown tab, returning them as a [`Tab`].
+    fn take_tab(&mut self) -> Tab {
+        Tab {
+            pane: mem::take(&mut self.pane),
+            panes: mem::take(&mut self.panes),
+            layout: mem::take(&mut self.layout),
+        }
     }
-}
 
-/// A file and the user's current interactions with it.
-#[derive(Clone, Debug)]
-pub(crate) struct Document {
-    /// The file of the document.
-    file: File,
-    /// The lines in `Self`.
-    lines: U32Vec<Line>,
-    /// The [`Dimensions`] of the screen showing the document.
-    dimensions: Dimensions,
-    /// The first row that is visible.
-    first_visible_row: Row,
-    /// The last row that is visible.
-    max_visible_row: Row,
-    /// The version of the document.
-    version: i32,
-    /// The root of all [`Symbol`]s in `Self`.
-    root_symbol: Symbol,
-    /// Describes the [`Symbol`] that is selected.
-    selection: Vec<usize>,
-}
+    /// Shows the panes of `tab`.
+    fn show_tab(&mut self, tab: Tab) {
+        self.pane = tab.pane;
+        self.panes = tab.panes;
+        self.layout = tab.layout;
+    }
 
-impl Document {
-    /// Creates a new [`Document`].
-    #[throws(OverflowError)]
-    fn new(file: File, dimensions: Dimensions) -> Self {
-        let max_length = usize::from(dimensions.width);
-        let mut prev_index = 0;
-        let mut row_end_index = max_length;
-        let mut lines = U32Vec::new();
-        let mut row_count: u64 = 0;
-        let text = file.text();
+    /// Returns the labels of the tab strip, which is only shown when there are multiple tabs.
+    fn tab_strip(&self) -> Vec<StyledText> {
+        let mut names: Vec<String> = self.tabs.iter().map(|tab| tab.pane.name()).collect();
+
+        names.insert(cmp::min(self.tab, names.len()), self.pane.name());
+
+        if names.len() > 1 {
+            names
+                .into_iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    StyledText::new(
+                        format!(" {} {} ", index.saturating_add(1), name),
+                        if index == self.tab {
+                            Style::Selection
+                        } else {
+                            Style::Default
+                        },
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns the [`Output`] that displays the command being edited.
+    fn command_output(&self) -> Output {
+        Output::Command {
+            command: format!(":{}", self.command.text()),
+            // The cursor follows the `:` prompt.
+            cursor: Some(self.command.cursor().saturating_add(1)),
+        }
+    }
+
+    /// Completes the `prefix` before the cursor of the command to `candidates`, returning the appropriate [`Output`]s.
+    ///
+    /// A single candidate is completed in full, followed by `separator`. Otherwise the text shared by all candidates is completed and the candidates are listed in the page.
+    fn complete(&mut self, prefix: &str, candidates: &[String], separator: &str) -> Vec<Output> {
+        let mut outputs = Vec::new();
+
+        match candidates {
+            [] => {}
+            [candidate] => {
+                self.command.replace_before_cursor(
+                    prefix.chars().count(),
+                    &format!("{}{}", candidate, separator),
+                );
+                outputs.push(self.command_output());
+                self.pane.update(&mut outputs);
+            }
+            [..] => {
+                self.command.replace_before_cursor(
+                    prefix.chars().count(),
+                    &completion::common_prefix(candidates),
+                );
+                outputs.push(self.command_output());
+                outputs.push(Output::UpdateView {
+                    region: self.pane.region,
+                    rows: candidates
+                        .iter()
+                        .map(|candidate| {
+                            RowText::new(vec![StyledText::new(candidate.clone(), Style::Default)])
+                        })
+                        .collect(),
+                });
+            }
+        }
+
+        outputs
+    }
+
+    /// Returns the [`Output`]s that display `picker`.
+    fn picker_outputs(&self, picker: &Picker) -> Vec<Output> {
+        vec![
+            Output::Command {
+                command: format!("{}{}", PICK_PROMPT, picker.query().text()),
+                cursor: Some(
+                    picker
+                        .query()
+                        .cursor()
+                        .saturating_add(PICK_PROMPT.chars().count()),
+                ),
+            },
+            Output::UpdateView {
+                region: self.pane.region,
+                rows: picker.rows(self.pane.region.size.height.into()),
+            },
+        ]
+    }
+
+    /// Returns the [`Output`]s that display `results`.
+    fn results_outputs(&self, results: &ResultList) -> Vec<Output> {
+        vec![
+            Output::Command {
+                command: results.summary(),
+                cursor: None,
+            },
+            Output::UpdateView {
+                region: self.pane.region,
+                rows: results.rows(
+                    self.pane.region.size.height.into(),
+                    self.pane.region.size.width.into(),
+                ),
+            },
+        ]
+    }
+
+    /// Searches the document for the pattern of `search`, returning the [`Output`]s that display the search.
+    #[throws(ScopeFromRangeError)]
+    fn search_outputs(&mut self, search: &Search) -> Vec<Output> {
+        let mut outputs = vec![Output::Command {
+            command: format!("{}{}", search.prompt(), search.query().text()),
+            cursor: Some(
+                search
+                    .query()
+                    .cursor()
+                    .saturating_add(search.prompt().chars().count()),
+            ),
+        }];
+
+        outputs.extend(self.pane.search(
+            search::matcher(search.query().text()),
+            search.origin(),
+            search.direction(),
+            search.range_selection(),
+        )?);
+        outputs
+    }
+
+    /// Stores `register` in the register called `name`, returning the [`Output`] that copies it to the system clipboard if `name` is the clipboard register.
+    fn set_register(&mut self, name: Option<char>, register: Register) -> Option<Output> {
+        let output = (name == Some(CLIPBOARD_REGISTER)).then(|| Output::Copy {
+            text: register.clipboard_text(),
+        });
+
+        self.registers.set(name, register);
+        output
+    }
+
+    /// Puts the text of `register` at `placement` relative to the selection, returning the appropriate [`Output`]s.
+    #[throws(ScopeFromRangeError)]
+    fn put(&mut self, register: &Register, placement: InsertPlacement) -> Vec<Output> {
+        let mut outputs = Vec::new();
+
+        if let Some(output) = self.pane.put(register, placement)? {
+            outputs.push(output);
+            outputs.extend(self.pane.history_output());
+        }
+
+        outputs
+    }
+
+    /// Processes `input` and generates [`Output`].
+    #[throws(ScopeFromRangeError)]
+    pub(crate) fn process(&mut self, input: Input) -> Vec<Output> {
+        self.interpreter
+            .translate(input)
+            .map_or_else(|| Ok(Vec::new()), |operation| self.operate(operation))?
+    }
+
+    /// Performs `operation` and returns the appropriate [`Output`]s.
+    #[allow(clippy::too_many_lines)] // Splitting the match over operations would only obscure it.
+    #[throws(ScopeFromRangeError)]
+    pub(crate) fn operate(&mut self, operation: Operation) -> Vec<Output> {
+        let mut outputs = Vec::new();
+
+        match operation {
+            Operation::Resize { dimensions } => {
+                self.page = dimensions;
+                outputs.append(&mut self.arrange());
+            }
+            Operation::Confirm(action) => {
+                self.confirming = Some(action);
+                outputs.push(Output::Question {
+                    region: self.pane.region,
+                    request: ShowMessageRequestParams::from(action),
+                });
+            }
+            Operation::Accept => match self.confirming {
+                Some(ConfirmAction::Quit) => {
+                    outputs.append(&mut self.operate(Operation::Quit)?);
+                }
+                Some(ConfirmAction::Replace) => {
+                    if let Some(substitution) = self.substitution.as_mut() {
+                        substitution.accept();
+                    }
+
+                    outputs.append(&mut self.substitution_outputs()?);
+                }
+                None => {}
+            },
+            Operation::Skip => match self.confirming {
+                Some(ConfirmAction::Replace) => {
+                    if let Some(substitution) = self.substitution.as_mut() {
+                        substitution.skip();
+                    }
+
+                    outputs.append(&mut self.substitution_outputs()?);
+                }
+                // Only replacements can be skipped.
+                Some(ConfirmAction::Quit) | None => {}
+            },
+            Operation::Reset => {
+                self.picker = None;
+                self.results = None;
+                self.confirming = None;
 
-        for (index, _) in text.match_indices('\n') {
-            let first_row = row_count;
-            let mut row_indices = Vec::new();
-            let mut end_index = index;
-            let before_end_index = end_index.saturating_sub(1);
+                // Stopping a substitution applies the replacements accepted so far.
+                if let Some(substitution) = self.substitution.take() {
+                    outputs.append(&mut self.substitute(substitution)?);
+                }
+
+                // Cancelling a search restores the selection from before the search.
+                if let Some(search) = self.search.take() {
+                    self.pane.restore_range_selection(search.range_selection());
+                }
+
+                self.pane.clear_search();
+                self.command.clear();
+                self.command_history.reset();
+                outputs.push(Output::Command {
+                    command: String::new(),
+                    cursor: None,
+                });
+                self.pane.update(&mut outputs);
+            }
+            Operation::StartCommand => {
+                self.command.clear();
+                outputs.push(self.command_output());
+            }
+            Operation::Collect(ch) => {
+                self.command.insert(ch.encode_utf8(&mut [0; 4]));
+                outputs.push(self.command_output());
+            }
+            Operation::CollectText(text) => {
+                self.command.insert(&text);
+                outputs.push(self.command_output());
+            }
+            Operation::EditCommand(edit) => {
+                self.command.edit(edit);
+                outputs.push(self.command_output());
+            }
+            Operation::RecallCommand(recall) => {
+                let current = self.command.text().to_string();
+                let recalled = match recall {
+                    CommandRecall::Previous => self
+                        .command_history
+                        .previous(&current)
+                        .map(|entry| (entry.to_string(), usize::MAX)),
+                    CommandRecall::Next => {
+                        self.command_history.next().map(|entry| (entry, usize::MAX))
+                    }
+                    CommandRecall::SearchBackward => {
+                        // The cursor stays after the prefix so that searching can be repeated.
+                        let cursor = self.command.cursor();
+
+                        self.command_history
+                            .search_backward(&current, self.command.text_before_cursor())
+                            .map(|entry| (entry.to_string(), cursor))
+                    }
+                };
+
+                if let Some((text, cursor)) = recalled {
+                    self.command.set(text, cursor);
+                    outputs.push(self.command_output());
+                }
+            }
+            Operation::Complete => match Target::of(self.command.text_before_cursor()) {
+                Some(Target::Command(prefix)) => {
+                    let prefix = prefix.to_string();
+
+                    outputs.append(&mut self.complete(
+                        &prefix,
+                        &completion::command_names(&prefix),
+                        " ",
+                    ));
+                }
+                // The paths are completed once they have been listed.
+                Some(Target::Path(prefix)) => outputs.push(Output::ListPaths {
+                    prefix: prefix.to_string(),
+                }),
+                None => {}
+            },
+            Operation::CompletePaths(listed) => {
+                // Paths listed for a command that has since been edited are ignored.
+                if Target::of(self.command.text_before_cursor())
+                    == Some(Target::Path(&listed.prefix))
+                {
+                    outputs.append(&mut self.complete(&listed.prefix, &listed.paths, ""));
+                }
+            }
+            Operation::Execute => {
+                let text = self.command.text().to_string();
+
+                outputs.push(Output::Command {
+                    command: format!(":{}", text),
+                    cursor: None,
+                });
+
+                if !text.trim().is_empty() {
+                    match command::parse(&text) {
+                        Ok(command) => outputs.append(&mut self.run(command)?),
+                        Err(error) => outputs.push(notify(MessageType::Error, error.to_string())),
+                    }
+                }
+
+                if self.command_history.push(text.clone()) {
+                    outputs.push(Output::RecordCommand { command: text });
+                }
+            }
+            Operation::Quit => {
+                // Quitting would discard the changes of every modified document, as closing it does.
+                let unsaved = iter::once(&self.pane)
+                    .chain(&self.panes)
+                    .chain(self.tabs.iter().flat_map(Tab::panes))
+                    .flat_map(|pane| pane.docs.iter())
+                    .find(|doc| doc.is_modified())
+                    .map(Document::name);
+
+                if let Some(name) = unsaved {
+                    outputs.push(notify(
+                        MessageType::Error,
+                        format!("`{}` has unsaved changes", name),
+                    ));
+                    // The confirmation may be shown over the focused pane.
+                    self.pane.update(&mut outputs);
+                } else {
+                    let mut docs = self.pane.take_docs();
+                    let mut closed: Vec<Url> = Vec::new();
+
+                    for pane in self
+                        .panes
+                        .iter_mut()
+                        .chain(self.tabs.iter_mut().flat_map(Tab::panes_mut))
+                    {
+                        docs.append(&mut pane.take_docs());
+                    }
+
+                    // A file shown by multiple panes is closed once.
+                    for doc in docs {
+                        if !closed.contains(doc.url()) {
+                            closed.push(doc.url().clone());
+                            outputs.push(doc.close());
+                        }
+                    }
+
+                    outputs.push(Output::Quit);
+                }
+            }
+            Operation::CreateDoc(file) => {
+                let shown = self
+                    .panes
+                    .iter()
+                    .chain(self.tabs.iter().flat_map(Tab::panes))
+                    .find_map(|pane| pane.find_doc(file.url()))
+                    .cloned();
+                // A jump whose file failed to open is dropped rather than applied to another file.
+                let jump = self.jump.take().filter(|(url, _)| url == file.url());
+
+                outputs.append(&mut self.pane.create_doc(file, shown, self.settings)?);
+
+                if let Some((_, range)) = jump {
+                    outputs.extend(self.pane.select_range(range)?);
+                }
+            }
+            Operation::MarkSaved(file) => {
+                outputs.extend(self.mark_saved(&file));
+            }
+            Operation::Diagnose(params) => {
+                self.diagnose(&params);
+            }
+            Operation::SetPositionEncoding(encoding) => {
+                if encoding != self.settings.encoding {
+                    self.settings.encoding = encoding;
+                    outputs.append(&mut self.resend_changed_docs());
+                    self.apply_settings_to_panes();
+                }
+            }
+            Operation::SetTextDocumentSync(sync) => {
+                if sync != self.settings.sync {
+                    self.settings.sync = sync;
+                    outputs.append(&mut self.resend_changed_docs());
+                    self.apply_settings_to_panes();
+                }
+            }
+            Operation::Scroll(direction, amount) => {
+                if let Some(output) = self.pane.scroll(direction, amount)? {
+                    outputs.push(output);
+                }
+            }
+            Operation::ChangeSelection(movement) => {
+                if let Some(output) = self.pane.change_selection(&movement)? {
+                    outputs.push(output);
+                }
+            }
+            Operation::Click { row, column } => {
+                if !self.pane.region.contains(row, column) {
+                    if let Some(pane) = self
+                        .panes
+                        .iter_mut()
+                        .find(|pane| pane.region.contains(row, column))
+                    {
+                        mem::swap(&mut self.pane, pane);
+                    }
+                }
+
+                // Cells outside every pane, such as borders, are ignored.
+                if self.pane.region.contains(row, column) {
+                    outputs.extend(self.pane.click(row, column)?);
+                }
+            }
+            Operation::Drag { row, column } => {
+                // A drag only selects the text of the focused pane.
+                if self.pane.region.contains(row, column) {
+                    outputs.extend(self.pane.drag(row, column)?);
+                }
+            }
+            Operation::Wheel {
+                direction,
+                row,
+                column,
+            } => {
+                if let Some(pane) = iter::once(&mut self.pane)
+                    .chain(&mut self.panes)
+                    .find(|pane| pane.region.contains(row, column))
+                {
+                    outputs.extend(pane.scroll(direction, ScrollAmount::Step)?);
+                }
+            }
+            Operation::StartInsert(placement) => {
+                if let Some(output) = self.pane.start_insert(placement)? {
+                    outputs.push(output);
+                }
+            }
+            Operation::InsertText(text) => {
+                if let Some(output) = self.pane.insert(&text)? {
+                    outputs.push(output);
+                }
+            }
+            Operation::DeleteBackward => {
+                if let Some(output) = self.pane.delete_backward()? {
+                    outputs.push(output);
+                }
+            }
+            Operation::EndInsert => {
+                if let Some(output) = self.pane.end_insert()? {
+                    outputs.push(output);
+                    outputs.extend(self.pane.history_output());
+                }
+            }
+            Operation::Undo => {
+                if let Some(output) = self.pane.undo()? {
+                    outputs.push(output);
+                    outputs.extend(self.pane.history_output());
+                }
+            }
+            Operation::Redo => {
+                if let Some(output) = self.pane.redo()? {
+                    outputs.push(output);
+                    outputs.extend(self.pane.history_output());
+                }
+            }
+            Operation::SelectRegister(name) => {
+                self.register = Some(name);
+            }
+            Operation::Yank => {
+                let name = self.register.take();
+
+                if let Some(register) = self.pane.yank()? {
+                    outputs.extend(self.set_register(name, register));
+                }
+            }
+            Operation::Delete => {
+                let name = self.register.take();
+
+                if let Some((register, output)) = self.pane.delete()? {
+                    outputs.push(output);
+                    outputs.extend(self.pane.history_output());
+                    outputs.extend(self.set_register(name, register));
+                }
+            }
+            Operation::Put(placement) => {
+                let name = self.register.take();
+
+                match name {
+                    Some(CLIPBOARD_REGISTER) => {
+                        // The text is put once it has been read from the clipboard.
+                        self.paste_placement = Some(placement);
+                        outputs.push(Output::Paste);
+                    }
+                    Some(_) | None => {
+                        if let Some(register) = self.registers.get(name).cloned() {
+                            outputs.extend(self.put(&register, placement)?);
+                        }
+                    }
+                }
+            }
+            Operation::PutClipboard(text) => {
+                if let Some(placement) = self.paste_placement.take() {
+                    let register = Register::from_clipboard(text);
+
+                    outputs.extend(self.put(&register, placement)?);
+                    self.registers.set(Some(CLIPBOARD_REGISTER), register);
+                }
+            }
+            Operation::StartPick => {
+                let picker = Picker::default();
+
+                outputs.push(Output::IndexPaths {
+                    hidden: self.settings.hidden,
+                });
+                outputs.append(&mut self.picker_outputs(&picker));
+                self.picker = Some(picker);
+            }
+            Operation::InputQuery(text) => {
+                if let Some(mut picker) = self.picker.take() {
+                    picker.insert(&text);
+                    outputs.append(&mut self.picker_outputs(&picker));
+                    self.picker = Some(picker);
+                }
+            }
+            Operation::EditQuery(edit) => {
+                if let Some(mut picker) = self.picker.take() {
+                    picker.edit(edit);
+                    outputs.append(&mut self.picker_outputs(&picker));
+                    self.picker = Some(picker);
+                }
+            }
+            Operation::MovePick(movement) => {
+                if let Some(mut picker) = self.picker.take() {
+                    picker.move_selection(movement);
+                    outputs.append(&mut self.picker_outputs(&picker));
+                    self.picker = Some(picker);
+                }
+            }
+            Operation::AddPickPaths(paths) => {
+                if let Some(mut picker) = self.picker.take() {
+                    picker.add_paths(paths);
+                    outputs.append(&mut self.picker_outputs(&picker));
+                    self.picker = Some(picker);
+                }
+            }
+            Operation::Pick => {
+                outputs.push(Output::Command {
+                    command: String::new(),
+                    cursor: None,
+                });
+
+                if let Some(path) = self
+                    .picker
+                    .take()
+                    .and_then(|picker| picker.selected_path().map(str::to_string))
+                {
+                    outputs.push(Output::OpenFile { path });
+                } else {
+                    self.pane.update(&mut outputs);
+                }
+            }
+            Operation::AddResults(matches) => {
+                match (self.results.take(), self.substitution.as_mut()) {
+                    (Some(mut results), _) => {
+                        results.add(matches);
+                        outputs.append(&mut self.results_outputs(&results));
+                        self.results = Some(results);
+                    }
+                    (None, Some(substitution)) => {
+                        substitution.add_matches(matches);
+                        outputs.append(&mut self.substitution_outputs()?);
+                    }
+                    (None, None) => {}
+                }
+            }
+            Operation::MoveResult(movement) => {
+                if let Some(mut results) = self.results.take() {
+                    results.move_selection(movement);
+                    outputs.append(&mut self.results_outputs(&results));
+                    self.results = Some(results);
+                }
+            }
+            Operation::Jump => {
+                outputs.push(Output::Command {
+                    command: String::new(),
+                    cursor: None,
+                });
+
+                if let Some((path, url, range)) =
+                    self.results.take().and_then(|results| results.selected())
+                {
+                    // The match is selected once the file has been read.
+                    self.jump = url.map(|url| (url, range));
+                    outputs.push(Output::OpenFile { path });
+                } else {
+                    self.pane.update(&mut outputs);
+                }
+            }
+            Operation::StartSearch(direction) => {
+                if let Some((origin, range_selection)) = self.pane.search_origin()? {
+                    let search = Search::new(direction, origin, range_selection);
+
+                    outputs.append(&mut self.search_outputs(&search)?);
+                    self.search = Some(search);
+                }
+            }
+            Operation::InputSearch(text) => {
+                if let Some(mut search) = self.search.take() {
+                    search.insert(&text);
+                    outputs.append(&mut self.search_outputs(&search)?);
+                    self.search = Some(search);
+                }
+            }
+            Operation::EditSearch(edit) => {
+                if let Some(mut search) = self.search.take() {
+                    search.edit(edit);
+                    outputs.append(&mut self.search_outputs(&search)?);
+                    self.search = Some(search);
+                }
+            }
+            Operation::EndSearch => {
+                self.search = None;
+                outputs.push(Output::Command {
+                    command: String::new(),
+                    cursor: None,
+                });
+            }
+            Operation::FindMatch(direction) => {
+                if let Some(output) = self.pane.find_match(direction)? {
+                    outputs.push(output);
+                }
+            }
+            Operation::SplitPane(direction) => {
+                if let Some(id) = self.layout.split(self.pane.id, direction) {
+                    let pane = self.pane.split(id);
+
+                    self.panes.push(mem::replace(&mut self.pane, pane));
+                    outputs.append(&mut self.arrange());
+                }
+            }
+            Operation::FocusPane(direction) => {
+                let neighbor = self.layout.neighbor(self.pane.id, direction, self.page);
+
+                if let Some(pane) = self.panes.iter_mut().find(|pane| Some(pane.id) == neighbor) {
+                    mem::swap(&mut self.pane, pane);
+                }
+            }
+            Operation::ResizePane(resize) => {
+                if self.layout.resize(self.pane.id, resize) {
+                    outputs.append(&mut self.arrange());
+                }
+            }
+            Operation::ClosePane => {
+                let focus = self.layout.remove(self.pane.id);
+
+                match self.panes.iter().position(|pane| Some(pane.id) == focus) {
+                    Some(index) => {
+                        let closed = mem::replace(&mut self.pane, self.panes.remove(index));
+
+                        for doc in closed.docs {
+                            outputs.extend(self.release(doc));
+                        }
+
+                        outputs.append(&mut self.arrange());
+                    }
+                    // Closing the last pane of a tab closes the tab.
+                    None => outputs.append(&mut self.operate(Operation::CloseTab)?),
+                }
+            }
+            Operation::NewTab => {
+                let tab = Tab {
+                    pane: self.pane.split(0),
+                    ..Tab::default()
+                };
+                let shown = self.take_tab();
+
+                self.tabs.insert(self.tab, shown);
+                self.tab = self.tab.saturating_add(1);
+                self.show_tab(tab);
+                outputs.append(&mut self.arrange());
+            }
+            Operation::SwitchTab(movement) => {
+                let index = match movement {
+                    ListMovement::Previous => self.tab.checked_sub(1).unwrap_or(self.tabs.len()),
+                    ListMovement::Next => {
+                        if self.tab < self.tabs.len() {
+                            self.tab.saturating_add(1)
+                        } else {
+                            0
+                        }
+                    }
+                };
+
+                if index != self.tab {
+                    let shown = self.take_tab();
+
+                    self.tabs.insert(self.tab, shown);
+                    self.tab = index;
+
+                    if index < self.tabs.len() {
+                        let tab = self.tabs.remove(index);
+
+                        self.show_tab(tab);
+                    }
+
+                    outputs.append(&mut self.arrange());
+                }
+            }
+            Operation::CloseTab => {
+                if self.tabs.is_empty() {
+                    outputs.push(notify(
+                        MessageType::Error,
+                        "cannot close the last tab".to_string(),
+                    ));
+                } else {
+                    let closed = self.take_tab();
+
+                    // The next tab takes the place of the closed tab unless it was the last tab.
+                    self.tab = cmp::min(self.tab, self.tabs.len().saturating_sub(1));
+
+                    if self.tab < self.tabs.len() {
+                        let tab = self.tabs.remove(self.tab);
+
+                        self.show_tab(tab);
+                    }
+
+                    for pane in iter::once(closed.pane).chain(closed.panes) {
+                        for doc in pane.docs {
+                            outputs.extend(self.release(doc));
+                        }
+                    }
+
+                    outputs.append(&mut self.arrange());
+                }
+            }
+        };
+
+        outputs.append(&mut self.sync_panes()?);
+        outputs.push(Output::UpdateHeader {
+            tabs: self.tab_strip(),
+        });
+        trace!("outputs: {:?}", outputs);
+
+        outputs
+    }
+}
+
+/// The prompt of the query of the file finder.
+const PICK_PROMPT: &str = "find: ";
+
+/// Returns the [`Output`] that shows `message` of `typ` to the user.
+const fn notify(typ: MessageType, message: String) -> Output {
+    Output::Notify {
+        message: ShowMessageParams { typ, message },
+    }
+}
+
+/// Returns the rows that describe `command`, or all commands if `command` is `None`.
+fn help_rows(command: Option<&CommandSpec>) -> Vec<RowText> {
+    let specs: Vec<&CommandSpec> =
+        command.map_or_else(|| COMMANDS.iter().collect(), |spec| vec![spec]);
+    let mut lines = Vec::new();
+
+    for spec in specs {
+        lines.push(if spec.aliases.is_empty() {
+            format!("{:<24}{}", spec.usage, spec.help)
+        } else {
+            format!(
+                "{:<24}{} (aliases: {})",
+                spec.usage,
+                spec.help,
+                spec.aliases.join(", ")
+            )
+        });
+
+        if command.is_some() && spec.name == "set" {
+            for option in OPTIONS {
+                lines.push(format!("  {:<22}{}", option.name, option.help));
+            }
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|line| RowText::new(vec![StyledText::new(line, Style::Default)]))
+        .collect()
+}
+
+/// The panes of a tab that is not shown.
+#[derive(Debug, Default)]
+struct Tab {
+    /// The focused pane.
+    pane: Pane,
+    /// The panes that are not focused.
+    panes: Vec<Pane>,
+    /// The arrangement of the panes within the page.
+    layout: Layout,
+}
+
+impl Tab {
+    /// Returns the panes of `self`.
+    fn panes(&self) -> impl Iterator<Item = &Pane> {
+        iter::once(&self.pane).chain(&self.panes)
+    }
+
+    /// Returns mutable references to the panes of `self`.
+    fn panes_mut(&mut self) -> impl Iterator<Item = &mut Pane> {
+        iter::once(&mut self.pane).chain(&mut self.panes)
+    }
+}
+
+/// A view of the open documents, one of which is shown.
+#[derive(Debug, Default)]
+struct Pane {
+    /// The id of the pane within the [`Layout`].
+    id: usize,
+    /// The open documents, in the order they were opened.
+    docs: Vec<Document>,
+    /// The index of the document that is shown.
+    current: usize,
+    /// The [`Region`] of the page in which the pane is shown.
+    region: Region,
+}
+
+impl Pane {
+    /// Returns a new [`Pane`] with `id` that shows the document shown by `self`.
+    ///
+    /// The document of the new pane keeps its own selection and scroll position.
+    fn split(&self, id: usize) -> Self {
+        Self {
+            id,
+            docs: self.doc().cloned().into_iter().collect(),
+            current: 0,
+            region: self.region,
+        }
+    }
+
+    /// Returns the name of the file of the shown document.
+    fn name(&self) -> String {
+        self.doc().map_or_else(|| "-".to_string(), Document::name)
+    }
+
+    /// Returns the open [`Document`] of the file at `url`.
+    fn find_doc(&self, url: &Url) -> Option<&Document> {
+        self.docs.iter().find(|doc| doc.url() == url)
+    }
+
+    /// Returns the [`Document`] that is shown.
+    fn doc(&self) -> Option<&Document> {
+        self.docs.get(self.current)
+    }
+
+    /// Returns a mutable reference to the [`Document`] that is shown.
+    fn doc_mut(&mut self) -> Option<&mut Document> {
+        self.docs.get_mut(self.current)
+    }
+
+    /// Returns the rows that list the open documents, marking the one that is shown.
+    fn list_rows(&self) -> Vec<RowText> {
+        self.docs
+            .iter()
+            .enumerate()
+            .map(|(index, doc)| {
+                picker::row_text(
+                    &format!("{} {}", index.saturating_add(1), doc.url().path()),
+                    &[],
+                    index == self.current,
+                )
+            })
+            .collect()
+    }
+
+    /// Shows the open document at `index`, returning `None` if no such document is open.
+    fn switch_doc(&mut self, index: usize) -> Option<Output> {
+        let output = self.docs.get_mut(index).map(Document::change_output);
+
+        if output.is_some() {
+            self.current = index;
+        }
+
+        output
+    }
+
+    /// Updates `self`.
+    fn update(&mut self, outputs: &mut Vec<Output>) {
+        if let Some(doc) = self.doc_mut() {
+            outputs.push(doc.change_output());
+        }
+    }
+
+    /// Updates `self` to be shown within `region`.
+    fn set_region(&mut self, region: Region, outputs: &mut Vec<Output>) {
+        self.region = region;
+
+        for doc in &mut self.docs {
+            doc.resize(region);
+        }
+
+        if self.docs.is_empty() {
+            outputs.push(self.clear_output());
+        } else {
+            self.update(outputs);
+        }
+    }
+
+    /// Sets the [`Settings`] of the documents of `self` to `settings`.
+    ///
+    /// The documents are not laid out again until they are resized.
+    fn apply_settings(&mut self, settings: Settings) {
+        for doc in &mut self.docs {
+            doc.settings = settings;
+        }
+    }
+
+    /// Returns the [`Output`] that clears the region of `self`.
+    fn clear_output(&self) -> Output {
+        Output::UpdateView {
+            region: self.region,
+            rows: vec![RowText::new(Vec::new()); self.region.size.height.into()],
+        }
+    }
+
+    /// Shows the document of `file`, opening it unless a document of the same file is already open.
+    ///
+    /// A document that is already open keeps its text, selection and scroll position. If another pane shows the file, `shown` is its [`Document`], which shares its text with the new document. Otherwise the new document follows `settings`.
+    #[throws(OverflowError)]
+    fn create_doc(
+        &mut self,
+        file: File,
+        shown: Option<Document>,
+        settings: Settings,
+    ) -> Vec<Output> {
+        let mut outputs = Vec::new();
+
+        if let Some(index) = self.docs.iter().position(|doc| doc.url() == file.url()) {
+            outputs.extend(self.switch_doc(index));
+        } else {
+            let doc = if let Some(mut doc) = shown {
+                doc.resize(self.region);
+                outputs.push(doc.change_output());
+                doc
+            } else {
+                let doc = Document::new(file, self.region, settings)?;
+
+                outputs.push(doc.open_output());
+                doc
+            };
+
+            self.current = self.docs.len();
+            self.docs.push(doc);
+        }
+
+        outputs
+    }
+
+    /// Replaces the text of the open document of the file of `source` with the text of `source`, returning the [`Output`] that shows it if it is shown.
+    #[throws(ScopeFromRangeError)]
+    fn sync(&mut self, source: &Document) -> Option<Output> {
+        let current = self.current;
+
+        self.docs
+            .iter_mut()
+            .enumerate()
+            .find(|(_, doc)| doc.url() == source.url() && doc.version() != source.version())
+            .and_then(|(index, doc)| {
+                doc.sync(source);
+                (index == current).then(|| doc.view_output())
+            })
+            .transpose()?
+    }
+
+    /// Change selection of `self` as described by `movement`.
+    #[throws(ScopeFromRangeError)]
+    fn change_selection(&mut self, movement: &SelectionMovement) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.change_selection(movement)?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Selects the innermost [`Symbol`] of the document shown at `row` and `column` of the page.
+    #[throws(ScopeFromRangeError)]
+    fn click(&mut self, row: Unit, column: Unit) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.click(row, column)?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Selects the text of the document between the cell that was last clicked and `row` and `column` of the page.
+    #[throws(ScopeFromRangeError)]
+    fn drag(&mut self, row: Unit, column: Unit) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.drag(row, column)?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Starts inserting text into the [`Document`] of `self` at `placement`.
+    #[throws(ScopeFromRangeError)]
+    fn start_insert(&mut self, placement: InsertPlacement) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.start_insert(placement)?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Inserts `text` into the [`Document`] of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn insert(&mut self, text: &str) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| doc.insert(text))
+            .transpose()?
+            .flatten()
+    }
+
+    /// Deletes the character before the insertion point of the [`Document`] of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn delete_backward(&mut self) -> Option<Output> {
+        self.doc_mut()
+            .map(Document::delete_backward)
+            .transpose()?
+            .flatten()
+    }
+
+    /// Stops inserting text into the [`Document`] of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn end_insert(&mut self) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.end_insert();
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Reverts the most recent change to the [`Document`] of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn undo(&mut self) -> Option<Output> {
+        self.doc_mut().map(Document::undo).transpose()?.flatten()
+    }
+
+    /// Reapplies the most recently reverted change to the [`Document`] of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn redo(&mut self) -> Option<Output> {
+        self.doc_mut().map(Document::redo).transpose()?.flatten()
+    }
+
+    /// Returns the selected text of the [`Document`] of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn yank(&self) -> Option<Register> {
+        self.doc().map(Document::yank).transpose()?
+    }
+
+    /// Deletes the selected text of the [`Document`] of `self`, returning the deleted text.
+    #[throws(ScopeFromRangeError)]
+    fn delete(&mut self) -> Option<(Register, Output)> {
+        self.doc_mut().map(Document::delete).transpose()?
+    }
+
+    /// Puts the text of `register` at `placement` relative to the selection of the [`Document`] of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn put(&mut self, register: &Register, placement: InsertPlacement) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| doc.put(register, placement))
+            .transpose()?
+    }
+
+    /// Returns the [`Output`] to store the history of the [`Document`] of `self`.
+    fn history_output(&self) -> Option<Output> {
+        self.doc().map(Document::history_output)
+    }
+
+    /// Removes the open document at `index`, returning it along with the [`Output`]s that update `self`, or `None` if no such document is open.
+    ///
+    /// If the document was shown, the document after it is shown instead.
+    fn close_doc(&mut self, index: usize) -> Option<(Document, Vec<Output>)> {
+        (index < self.docs.len()).then(|| {
+            let doc = self.docs.remove(index);
+            let mut outputs = Vec::new();
+
+            if index < self.current {
+                self.current = self.current.saturating_sub(1);
+            }
+
+            self.current = cmp::min(self.current, self.docs.len().saturating_sub(1));
+
+            if self.docs.is_empty() {
+                outputs.push(self.clear_output());
+            } else {
+                self.update(&mut outputs);
+            }
+
+            (doc, outputs)
+        })
+    }
+
+    /// Removes every open document, returning them.
+    fn take_docs(&mut self) -> Vec<Document> {
+        self.current = 0;
+        self.docs.drain(..).collect()
+    }
+
+    /// Returns the [`Output`] to write the [`Document`] of `self` to its file.
+    fn save_output(&self) -> Option<Output> {
+        self.doc().map(Document::save_output)
+    }
+
+    /// Scrolls the document by `amount` towards `direction`.
+    #[throws(ScopeFromRangeError)]
+    fn scroll(
+        &mut self,
+        direction: orient::ScreenDirection,
+        amount: ScrollAmount,
+    ) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.scroll(direction, amount);
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Switches between wrapping the lines of the document and scrolling them horizontally.
+    #[throws(ScopeFromRangeError)]
+    fn toggle_wrap(&mut self) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.toggle_wrap()?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Adds or removes a bookmark on the first line of the selection of the document.
+    #[throws(ScopeFromRangeError)]
+    fn toggle_bookmark(&mut self) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.toggle_bookmark()?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Scrolls the document so that its selection is shown at `alignment`.
+    #[throws(ScopeFromRangeError)]
+    fn align_view(&mut self, alignment: ViewAlignment) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.align_view(alignment)?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Selects `range` of the document.
+    #[throws(ScopeFromRangeError)]
+    fn select_range(&mut self, range: Range) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.select_range(range)?;
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Returns the start of the selection and the selected [`Range`] of the document, from which a search starts.
+    #[throws(OutOfBoundsError)]
+    fn search_origin(&self) -> Option<(Position, Option<Range>)> {
+        self.doc()
+            .map(|doc| {
+                doc.selection_range()
+                    .map(|range| (range.start, doc.range_selection))
+            })
+            .transpose()?
+    }
+
+    /// Highlights the matches of `pattern` in the document and selects the first match from `origin` towards `direction`.
+    ///
+    /// If nothing matches, `range_selection` is selected.
+    #[throws(ScopeFromRangeError)]
+    fn search(
+        &mut self,
+        pattern: Option<Regex>,
+        origin: Position,
+        direction: SearchDirection,
+        range_selection: Option<Range>,
+    ) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                doc.pattern = pattern;
+
+                match doc.find(origin, direction) {
+                    Some(range) => doc.select_range(range)?,
+                    None => doc.range_selection = range_selection,
+                }
+
+                doc.view_output()
+            })
+            .transpose()?
+    }
+
+    /// Selects the next match of the last search towards `direction`.
+    #[throws(ScopeFromRangeError)]
+    fn find_match(&mut self, direction: SearchDirection) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| {
+                let start = doc.selection_range()?.start;
+                let origin = match direction {
+                    // Skips the match that is selected.
+                    SearchDirection::Forward => {
+                        Position::new(start.line, start.character.saturating_add(1))
+                    }
+                    SearchDirection::Backward => start,
+                };
+
+                doc.find(origin, direction)
+                    .map(|range| {
+                        doc.select_range(range)?;
+                        doc.view_output()
+                    })
+                    .transpose()
+            })
+            .transpose()?
+            .flatten()
+    }
+
+    /// Selects `range_selection` of the document instead of the match of the cancelled search.
+    fn restore_range_selection(&mut self, range_selection: Option<Range>) {
+        if let Some(doc) = self.doc_mut() {
+            doc.range_selection = range_selection;
+        }
+    }
+
+    /// Applies `replacements` to the [`Document`] of `self` as a single change.
+    #[throws(ScopeFromRangeError)]
+    fn substitute(&mut self, replacements: Vec<Replacement>) -> Option<Output> {
+        self.doc_mut()
+            .map(|doc| doc.substitute(replacements))
+            .transpose()?
+            .flatten()
+    }
+
+    /// Stops highlighting the matches of the last search.
+    fn clear_search(&mut self) {
+        if let Some(doc) = self.doc_mut() {
+            doc.pattern = None;
+        }
+    }
+}
+
+/// A [`Range`] of text that can be selected.
+#[derive(Clone, Debug)]
+struct Symbol {
+    /// The [`Range`] of `Self`.
+    range: Range,
+    /// The children [`Symbol`]s of `Self`.
+    children: Vec<Symbol>,
+}
+
+impl Symbol {
+    /// Creates the default root [`Symbol`] of `text`, which has a child for each line.
+    #[throws(OverflowError)]
+    fn create_root(text: &Rope) -> Self {
+        let mut root = Self {
+            range: Range::default(),
+            children: Vec::new(),
+        };
+
+        root.replace_lines(text, 0, 0, text.len_lines())?;
+        root
+    }
+
+    /// Creates the [`Symbol`] of the line of `text` at `index`.
+    #[throws(OverflowError)]
+    fn line(text: &Rope, index: usize) -> Self {
+        let line = u32::try_from(index)?;
+
+        Self {
+            range: Range::new(
+                Position::new(line, 0),
+                Position::new(line, u32::try_from(line_length(text.line(index)))?),
+            ),
+            children: Vec::new(),
+        }
+    }
+
+    /// Replaces the `old_count` line children starting at `first_line` with the `new_count` lines of `text` starting at `first_line`.
+    ///
+    /// Only the replaced lines are rebuilt; the lines that follow are renumbered.
+    #[throws(OverflowError)]
+    fn replace_lines(
+        &mut self,
+        text: &Rope,
+        first_line: usize,
+        old_count: usize,
+        new_count: usize,
+    ) {
+        let end = cmp::min(first_line.saturating_add(old_count), self.children.len());
+        let start = cmp::min(first_line, end);
+        let new_end = first_line.saturating_add(new_count);
+        let new_lines = (first_line..new_end)
+            .map(|index| Self::line(text, index))
+            .collect::<Result<Vec<Self>, _>>()?;
+        self.children.splice(start..end, new_lines).for_each(drop);
+
+        for (line, child) in (0_u32..).zip(self.children.iter_mut()).skip(new_end) {
+            child.range.start.line = line;
+            child.range.end.line = line;
+        }
+
+        self.range = Range::new(
+            Position::new(0, 0),
+            self.children
+                .last()
+                .map_or_else(Position::default, |last| last.range.end),
+        );
+    }
+
+    /// Returns if `position` is within `self`; an empty [`Symbol`] contains its start.
+    fn contains(&self, position: Position) -> bool {
+        self.range.start <= position && (position < self.range.end || position == self.range.start)
+    }
+
+    /// Returns the indexes of the children that lead from `self` to the innermost [`Symbol`] that contains `position`.
+    fn path_to(&self, position: Position) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut symbol = self;
+
+        while let Some((index, child)) = symbol
+            .children
+            .iter()
+            .enumerate()
+            .find(|(_, child)| child.contains(position))
+        {
+            path.push(index);
+            symbol = child;
+        }
+
+        path
+    }
+}
+
+impl From<DocumentSymbol> for Symbol {
+    fn from(symbol: DocumentSymbol) -> Self {
+        let mut children = Vec::new();
+
+        if let Some(symbol_children) = symbol.children {
+            for child in symbol_children.into_iter().rev() {
+                children.push(child.into());
+            }
+        }
+
+        Self {
+            range: symbol.range,
+            children,
+        }
+    }
+}
+
+/// A line in a [`Document`].
+#[derive(Clone, Debug)]
+struct Line {
+    /// The index within the [`Document`] of the first row of `Self`.
+    first_row: Row,
+    /// The start and end character indexes, relative to the start of `Self`, of each row in `Self`.
+    rows: Vec<(usize, usize)>,
+}
+
+impl Line {
+    /// Creates a new [`Line`] by splitting `text` into rows that are each at most `width` columns wide.
+    ///
+    /// Rows only break between grapheme clusters, each of which takes up its display width. A tab extends to the next multiple of `tab_width` columns within its row. A grapheme cluster that is wider than `width` is shown on a row of its own.
+    fn new(first_row: Row, text: RopeSlice<'_>, width: usize, tab_width: u8) -> Self {
+        let line = text.slice(..line_length(text)).to_string();
+        let mut rows = Vec::new();
+        let mut start = 0_usize;
+        let mut end = 0_usize;
+        let mut row_width = 0_usize;
+
+        for grapheme in line.graphemes(true) {
+            let mut grapheme_width = display_width(grapheme, row_width, tab_width);
+
+            if row_width > 0 && row_width.saturating_add(grapheme_width) > width {
+                rows.push((start, end));
+                start = end;
+                row_width = 0;
+                grapheme_width = display_width(grapheme, row_width, tab_width);
+            }
+
+            end = end.saturating_add(grapheme.chars().count());
+            row_width = row_width.saturating_add(grapheme_width);
+        }
+
+        rows.push((start, end));
+        Self { first_row, rows }
+    }
+
+    /// Returns the [`Address`] of the character at index `character` of `self`.
+    ///
+    /// A character index at the boundary between 2 rows is at the start of the latter row.
+    fn address(&self, character: usize) -> Address {
+        let index = self
+            .rows
+            .iter()
+            .rposition(|&(start, _)| start <= character)
+            .unwrap_or(0);
+
+        Address {
+            row: self
+                .first_row
+                .saturating_add(u64::try_from(index).unwrap_or(u64::MAX)),
+            column: character.saturating_sub(self.rows.get(index).map_or(0, |&(start, _)| start)),
+        }
+    }
+
+    /// Returns the row that follows the last row of `self`.
+    fn end_row(&self) -> Row {
+        self.first_row
+            .saturating_add(u64::try_from(self.rows.len()).unwrap_or(u64::MAX))
+    }
+}
+
+/// Returns the first visible row closest to `first_visible_row` that shows `rows` within `height` rows, keeping `margin` rows between `rows` and the edges where possible.
+///
+/// If `rows` do not fit, the first of `rows` is shown. The margin is limited so that the first of `rows` can always be shown.
+fn revealing_row(
+    first_visible_row: Row,
+    rows: ops::RangeInclusive<Row>,
+    height: u64,
+    margin: u64,
+) -> Row {
+    let margin = cmp::min(margin, half(height.saturating_sub(1)));
+
+    cmp::min(
+        cmp::max(
+            first_visible_row,
+            rows.end()
+                .saturating_add(margin.saturating_add(1))
+                .saturating_sub(height),
+        ),
+        rows.start().saturating_sub(margin),
+    )
+}
+
+/// Returns the number of rows or columns scrolled by `amount` when `step` is set by the `scroll` option and `extent` rows or columns show the document.
+///
+/// Scrolling by part of a page always moves by at least 1 row or column.
+fn scroll_distance(amount: ScrollAmount, step: u64, extent: u64) -> u64 {
+    match amount {
+        ScrollAmount::Step => step,
+        ScrollAmount::Line => 1,
+        ScrollAmount::HalfPage => cmp::max(half(extent), 1),
+        ScrollAmount::Page => cmp::max(extent, 1),
+        ScrollAmount::Edge => u64::MAX,
+    }
+}
+
+/// Returns half of `value`, rounded down.
+fn half(value: u64) -> u64 {
+    value.checked_div(2).unwrap_or(0)
+}
+
+/// Returns the number of columns taken up by `grapheme` when it is shown at `column` of a row.
+///
+/// A tab extends to the next multiple of `tab_width` columns.
+fn display_width(grapheme: &str, column: usize, tab_width: u8) -> usize {
+    if grapheme == "\t" {
+        tab_columns(column, tab_width)
+    } else {
+        grapheme.width()
+    }
+}
+
+/// Returns the number of columns taken up by `chars` when they are shown at the start of a row.
+fn text_width(chars: impl Iterator<Item = char>, tab_width: u8) -> usize {
+    chars.fold(0, |width, ch| {
+        width.saturating_add(if ch == '\t' {
+            tab_columns(width, tab_width)
+        } else {
+            ch.width().unwrap_or(0)
+        })
+    })
+}
+
+/// Returns the number of columns taken up by a tab shown at `column` of a row.
+fn tab_columns(column: usize, tab_width: u8) -> usize {
+    let tab_width = cmp::max(usize::from(tab_width), 1);
+
+    tab_width.saturating_sub(column.checked_rem(tab_width).unwrap_or(0))
+}
+
+/// Returns the number of characters in `line`, excluding the line ending.
+fn line_length(line: RopeSlice<'_>) -> usize {
+    let mut length = line.len_chars();
+
+    // A line ending is one of "\n", "\r\n" or "\r".
+    for ending in ['\n', '\r'] {
+        if let Some(last) = length.checked_sub(1) {
+            if line.char(last) == ending {
+                length = last;
+            }
+        }
+    }
+
+    length
+}
+
+/// Returns the number of chars of indentation at the start of `line`.
+fn indentation(line: RopeSlice<'_>) -> usize {
+    line.chars()
+        .take_while(|&ch| ch == ' ' || ch == '\t')
+        .count()
+}
+
+/// Lays out the lines of `text` with indexes in `indexes`, the first of which starts at `first_row`.
+fn lay_out(
+    text: &Rope,
+    indexes: ops::Range<usize>,
+    first_row: Row,
+    width: usize,
+    tab_width: u8,
+) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut row = first_row;
+
+    for index in indexes {
+        let line = Line::new(row, text.line(index), width, tab_width);
+
+        row = line.end_row();
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// A file and the user's current interactions with it.
+///
+/// Clones of a [`Document`] share its text and layout until one of them is edited.
+#[derive(Clone, Debug)]
+pub(crate) struct Document {
+    /// The file of the document.
+    file: File,
+    /// The lines in `Self`.
+    lines: Rc<U32Vec<Line>>,
+    /// The [`Region`] of the page showing the document.
+    region: Region,
+    /// The first row that is visible.
+    first_visible_row: Row,
+    /// The version of the document.
+    version: i32,
+    /// The version of the document that was last written to its file.
+    saved_version: i32,
+    /// The text of the document when it was last written to or read from its file.
+    saved_text: Rope,
+    /// The root of all [`Symbol`]s in `Self`.
+    root_symbol: Rc<Symbol>,
+    /// Describes the [`Symbol`] that is selected.
+    selection: Vec<usize>,
+    /// A [`Range`] that is selected instead of a [`Symbol`], such as a match of a search.
+    ///
+    /// Cleared when the selection is moved or the text is edited.
+    range_selection: Option<Range>,
+    /// The pattern of the last search, whose matches are highlighted.
+    pattern: Option<Regex>,
+    /// The [`Position`] at which text is inserted.
+    ///
+    /// If `None`, text is not being inserted.
+    insertion: Option<Position>,
+    /// The history of changes made to `Self`.
+    history: Arc<History>,
+    /// The [`Settings`] that determine how `Self` is shown and described to the language server.
+    settings: Settings,
+    /// If the lines of `Self` wrap at the edge of its [`Region`].
+    ///
+    /// Otherwise each line is shown on a single row, starting at `first_visible_column`.
+    wraps: bool,
+    /// The first column of each line that is visible while lines do not wrap.
+    first_visible_column: usize,
+    /// The lines that are bookmarked.
+    bookmarks: Bookmarks,
+    /// The diagnostics last published for the file by the language server.
+    diagnostics: Diagnostics,
+    /// The lines that differ from the last commit of the file.
+    changes: VcsChanges,
+    /// The [`Position`] that was last clicked, from which dragging the mouse selects.
+    drag_origin: Option<Position>,
+}
+
+impl Document {
+    /// Creates a new [`Document`].
+    ///
+    /// If `file` has a restored history, the history of the [`Document`] continues from it.
+    #[throws(OverflowError)]
+    fn new(mut file: File, region: Region, settings: Settings) -> Self {
+        let history = file.take_history().unwrap_or_default();
+        let changes = file.take_changes();
+        let text = file.text();
+
+        Self {
+            lines: Rc::new(U32Vec::from(lay_out(
+                text,
+                0..text.len_lines(),
+                Row(0),
+                region.size.width.into(),
+                settings.tab_width,
+            ))),
+            root_symbol: Rc::new(Symbol::create_root(text)?),
+            region,
+            first_visible_row: Row(0),
+            version: 0,
+            saved_version: 0,
+            saved_text: text.clone(),
+            file,
+            selection: Vec::new(),
+            range_selection: None,
+            pattern: None,
+            insertion: None,
+            history: Arc::new(history),
+            settings,
+            wraps: true,
+            first_visible_column: 0,
+            bookmarks: Bookmarks::default(),
+            diagnostics: Diagnostics::default(),
+            changes,
+            drag_origin: None,
+        }
+    }
+
+    /// Sets the [`Region`] of `self` to `region`, laying out every line again.
+    fn resize(&mut self, region: Region) {
+        self.region = region;
+
+        let text = self.file.text();
+
+        self.lines = Rc::new(U32Vec::from(lay_out(
+            text,
+            0..text.len_lines(),
+            Row(0),
+            self.wrap_width(),
+            self.settings.tab_width,
+        )));
+        self.first_visible_row = cmp::min(self.first_visible_row, self.max_visible_row());
+    }
+
+    /// Replaces the text of `self` with the text of `other`, a [`Document`] of the same file, keeping the selection and scroll position of `self`.
+    fn sync(&mut self, other: &Self) {
+        self.file = other.file.clone();
+        self.root_symbol = Rc::clone(&other.root_symbol);
+        self.history = Arc::clone(&other.history);
+        self.version = other.version;
+        self.saved_version = other.saved_version;
+        self.saved_text = other.saved_text.clone();
+        self.bookmarks.clone_from(&other.bookmarks);
+        self.diagnostics.clone_from(&other.diagnostics);
+        self.changes.clone_from(&other.changes);
+        self.range_selection = None;
+        self.clamp_selection();
+
+        if self.wrap_width() == other.wrap_width() && self.settings == other.settings {
+            self.lines = Rc::clone(&other.lines);
+            self.first_visible_row = cmp::min(self.first_visible_row, self.max_visible_row());
+        } else {
+            self.resize(self.region);
+        }
+    }
+
+    /// Returns the number of columns at which the lines of `self` wrap.
+    fn wrap_width(&self) -> usize {
+        if self.wraps {
+            self.text_columns()
+        } else {
+            usize::MAX
+        }
+    }
+
+    /// Returns the number of columns of the [`Region`] of `self` that show its text, which are those right of the gutter.
+    fn text_columns(&self) -> usize {
+        usize::from(self.region.size.width).saturating_sub(self.gutter_width())
+    }
+
+    /// Returns the number of columns of the gutter, which holds the sign column and the line numbers, each followed by a space.
+    fn gutter_width(&self) -> usize {
+        let signs = if self.settings.signs { 2 } else { 0 };
+        let numbers = self.number_width();
+
+        if numbers == 0 {
+            signs
+        } else {
+            signs.saturating_add(numbers).saturating_add(1)
+        }
+    }
+
+    /// Returns the number of columns of the line numbers.
+    ///
+    /// Numbers are at least 3 digits wide so that the gutter seldom changes width while editing.
+    fn number_width(&self) -> usize {
+        match self.settings.number {
+            LineNumbers::Off => 0,
+            LineNumbers::Absolute | LineNumbers::Relative => {
+                cmp::max(self.file.text().len_lines().to_string().len(), 3)
+            }
+        }
+    }
+
+    /// Returns the [`StyledText`] of the gutter beside a row of the line at `index`, or `None` if no gutter is shown.
+    ///
+    /// Only the first row of a line shows its sign and number. Relative numbers count from `selected_line`.
+    fn gutter(&self, index: usize, is_first_row: bool, selected_line: usize) -> Option<StyledText> {
+        let mut text = String::new();
+
+        if self.settings.signs {
+            text.push(
+                self.sign_sources()
+                    .iter()
+                    .find_map(|source| source.sign(index))
+                    .filter(|_| is_first_row)
+                    .unwrap_or(' '),
+            );
+            text.push(' ');
+        }
+
+        let number = match self.settings.number {
+            LineNumbers::Off => None,
+            LineNumbers::Absolute => Some(index.saturating_add(1)),
+            LineNumbers::Relative if index == selected_line => Some(index.saturating_add(1)),
+            LineNumbers::Relative => {
+                Some(cmp::max(index, selected_line).saturating_sub(cmp::min(index, selected_line)))
+            }
+        };
+
+        if let Some(number) = number {
+            let shown = if is_first_row {
+                number.to_string()
+            } else {
+                String::new()
+            };
+
+            text.push_str(&format!("{:>1$} ", shown, self.number_width()));
+        }
+
+        (!text.is_empty()).then(|| StyledText::new(text, Style::Gutter))
+    }
+
+    /// Returns the sources of the signs shown in the sign column, from the highest priority to the lowest.
+    ///
+    /// Each line shows the sign of the first source that has one for it.
+    fn sign_sources(&self) -> [&dyn SignSource; 3] {
+        [&self.diagnostics, &self.changes, &self.bookmarks]
+    }
+
+    /// Adds or removes a bookmark on the first line of the selection of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn toggle_bookmark(&mut self) {
+        self.bookmarks.toggle(
+            usize::try_from(self.selection_range()?.start.line).map_err(OverflowError::from)?,
+        );
+    }
+
+    /// Switches between wrapping the lines of `self` and showing each line on a single row.
+    #[throws(OutOfBoundsError)]
+    fn toggle_wrap(&mut self) {
+        self.wraps = !self.wraps;
+        self.first_visible_column = 0;
+        self.resize(self.region);
+        self.reveal_selection()?;
+    }
+
+    /// Scrolls `self` so that the selected [`Range`] is visible.
+    ///
+    /// The rows of the selection are kept `scroll_off` rows away from the edges of the [`Region`] where possible; if the selection is taller than the [`Region`], its start is shown. Only lines that do not wrap need to scroll horizontally.
+    #[throws(OutOfBoundsError)]
+    fn reveal_selection(&mut self) {
+        let range = self.selected_range()?;
+        let start = range.start;
+        let start_row = self
+            .lines
+            .get(start.line)?
+            .address(usize::try_from(start.character).unwrap_or(usize::MAX))
+            .row;
+
+        self.first_visible_row = cmp::min(
+            revealing_row(
+                self.first_visible_row,
+                start_row..=cmp::max(start_row, self.last_row_of(range)),
+                self.region.size.height.into(),
+                self.settings.scroll_off.into(),
+            ),
+            self.max_visible_row(),
+        );
+
+        if !self.wraps {
+            let column = usize::try_from(start.line)
+                .ok()
+                .filter(|&line| line < self.file.text().len_lines())
+                .map_or(0, |line| {
+                    text_width(
+                        self.line_text(line)
+                            .chars()
+                            .take(usize::try_from(start.character).unwrap_or(usize::MAX)),
+                        self.settings.tab_width,
+                    )
+                });
+            let width = self.text_columns();
+
+            self.first_visible_column = cmp::max(
+                cmp::min(self.first_visible_column, column),
+                column.saturating_add(1).saturating_sub(width),
+            );
+        }
+    }
+
+    /// Returns the last row that shows a char of `range`.
+    ///
+    /// A `range` that ends beyond the text ends at the last row of `self`.
+    fn last_row_of(&self, range: Range) -> Row {
+        let (line, character) = if range.end.character > 0 {
+            (range.end.line, range.end.character.saturating_sub(1))
+        } else if range.end.line > range.start.line {
+            // The range ends at the end of the previous line.
+            (range.end.line.saturating_sub(1), u32::MAX)
+        } else {
+            (range.end.line, 0)
+        };
+
+        self.lines.get(line).map_or_else(
+            |_| self.row_count().saturating_sub(1),
+            |line| {
+                line.address(usize::try_from(character).unwrap_or(usize::MAX))
+                    .row
+            },
+        )
+    }
+
+    /// Scrolls `self` so that the start of the selected [`Range`] is shown at `alignment`.
+    ///
+    /// The start of the selection remains `scroll_off` rows away from the top of the [`Region`].
+    #[throws(OutOfBoundsError)]
+    fn align_view(&mut self, alignment: ViewAlignment) {
+        let start = self.selected_range()?.start;
+        let start_row = self
+            .lines
+            .get(start.line)?
+            .address(usize::try_from(start.character).unwrap_or(usize::MAX))
+            .row;
+        let height = u64::from(self.region.size.height);
+        let offset = match alignment {
+            ViewAlignment::Top => cmp::min(
+                u64::from(self.settings.scroll_off),
+                half(height.saturating_sub(1)),
+            ),
+            ViewAlignment::Center => half(height.saturating_sub(1)),
+        };
+
+        self.first_visible_row = cmp::min(start_row.saturating_sub(offset), self.max_visible_row());
+    }
+
+    /// Returns the number of rows in `self`.
+    fn row_count(&self) -> Row {
+        self.lines.last().map_or(Row(0), Line::end_row)
+    }
+
+    /// Returns the highest row that may be the first visible row.
+    fn max_visible_row(&self) -> Row {
+        self.row_count()
+            .saturating_sub(self.region.size.height.into())
+    }
+
+    /// Scrolls `self` by `amount` towards `direction`.
+    ///
+    /// Scrolling horizontally only moves lines that do not wrap, and stops once the end of the widest visible line is shown.
+    fn scroll(&mut self, direction: orient::ScreenDirection, amount: ScrollAmount) {
+        let step = self.settings.scroll.into();
+        let columns = usize::try_from(scroll_distance(
+            amount,
+            step,
+            u64::try_from(self.text_columns()).unwrap_or(u64::MAX),
+        ))
+        .unwrap_or(usize::MAX);
+
+        match direction {
+            orient::ScreenDirection::Left => {
+                self.first_visible_column = self.first_visible_column.saturating_sub(columns);
+            }
+            // Lines that wrap are never wider than the region.
+            orient::ScreenDirection::Right if !self.wraps => {
+                self.first_visible_column = cmp::max(
+                    self.first_visible_column,
+                    cmp::min(
+                        self.first_visible_column.saturating_add(columns),
+                        self.max_visible_column(),
+                    ),
+                );
+            }
+            orient::ScreenDirection::Right
+            | orient::ScreenDirection::Up
+            | orient::ScreenDirection::Down => {}
+        }
+
+        if let Some(vertical_direction) = direction.vertical_direction() {
+            let rows = scroll_distance(amount, step, self.region.size.height.into());
+
+            self.first_visible_row = match vertical_direction {
+                orient::AxialDirection::Positive => self.first_visible_row.saturating_add(rows),
+                orient::AxialDirection::Negative => self.first_visible_row.saturating_sub(rows),
+            };
+
+            self.first_visible_row = cmp::min(self.first_visible_row, self.max_visible_row());
+        }
+    }
+
+    /// Returns the [`Position`] of the char shown at `row` and `column` of the page, or `None` if no line is shown at `row`.
+    ///
+    /// A cell of the gutter or beyond the end of a row is treated as the first or last char of the row.
+    fn position_at(&self, row: Unit, column: Unit) -> Option<Position> {
+        let row = self
+            .first_visible_row
+            .saturating_add(u64::from(*row).saturating_sub(u64::from(*self.region.row)));
+        let column = usize::from(column)
+            .saturating_sub(usize::from(self.region.column))
+            .saturating_sub(self.gutter_width())
+            .saturating_add(self.first_visible_column);
+        let index = self
+            .lines
+            .as_slice()
+            .partition_point(|line| line.end_row() <= row);
+        let line = self.lines.as_slice().get(index)?;
+        let &(start, end) = line
+            .rows
+            .get(usize::try_from(row.0.saturating_sub(line.first_row.0)).ok()?)?;
+        let mut width = 0_usize;
+        let offset = self
+            .line_text(index)
+            .chars()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .position(|ch| {
+                width = width.saturating_add(if ch == '\t' {
+                    tab_columns(width, self.settings.tab_width)
+                } else {
+                    ch.width().unwrap_or(0)
+                });
+                width > column
+            })
+            .unwrap_or_else(|| end.saturating_sub(start).saturating_sub(1));
+
+        Some(Position::new(
+            u32::try_from(index).ok()?,
+            u32::try_from(start.saturating_add(offset)).ok()?,
+        ))
+    }
+
+    /// Selects the innermost [`Symbol`] shown at `row` and `column` of the page, from which a drag starts.
+    #[throws(OutOfBoundsError)]
+    fn click(&mut self, row: Unit, column: Unit) {
+        if let Some(position) = self.position_at(row, column) {
+            self.range_selection = None;
+            self.selection = self.root_symbol.path_to(position);
+            self.drag_origin = Some(position);
+            self.reveal_selection()?;
+        }
+    }
+
+    /// Selects the chars from the [`Position`] that was last clicked through the char shown at `row` and `column` of the page.
+    #[throws(OutOfBoundsError)]
+    fn drag(&mut self, row: Unit, column: Unit) {
+        if let (Some(origin), Some(position)) = (self.drag_origin, self.position_at(row, column)) {
+            let (start, end) = if position < origin {
+                (position, origin)
+            } else {
+                (origin, position)
+            };
+
+            self.select_range(Range::new(
+                start,
+                Position::new(end.line, end.character.saturating_add(1)),
+            ))?;
+        }
+    }
+
+    /// Returns the highest column that may be the first visible column, at which the end of the widest visible line is shown.
+    fn max_visible_column(&self) -> usize {
+        let first_line = self
+            .lines
+            .as_slice()
+            .partition_point(|line| line.end_row() <= self.first_visible_row);
+
+        (first_line..self.file.text().len_lines())
+            .take(self.region.size.height.into())
+            .map(|index| text_width(self.line_text(index).chars(), self.settings.tab_width))
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(self.text_columns())
+    }
+
+    /// Returns the [`Output`] for opening `self`.
+    fn open_output(&self) -> Output {
+        Output::EditDoc {
+            doc: Box::new(self.clone()),
+            edit: DocEdit::Open,
+        }
+    }
+
+    /// Returns the [`Output`] for changing `self`.
+    fn change_output(&mut self) -> Output {
+        Output::EditDoc {
+            doc: Box::new(self.clone()),
+            edit: DocEdit::Update,
+        }
+    }
+
+    /// Returns the [`Output`] for `changes` made to the text of `self`.
+    ///
+    /// A language server that syncs whole documents is sent the whole text instead, and one that does not sync is sent nothing.
+    fn edit_output(&self, changes: Vec<TextDocumentContentChangeEvent>) -> Output {
+        let changes = match self.settings.sync {
+            TextDocumentSyncKind::Incremental => changes,
+            TextDocumentSyncKind::Full => vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: self.file.text().to_string(),
+            }],
+            TextDocumentSyncKind::None => Vec::new(),
+        };
+
+        Output::EditDoc {
+            doc: Box::new(self.clone()),
+            edit: DocEdit::Change { changes },
+        }
+    }
+
+    /// Returns the [`Output`] for sending the whole text of `self` to the language server.
+    fn resend_output(&self) -> Output {
+        Output::EditDoc {
+            doc: Box::new(self.clone()),
+            edit: DocEdit::Resend,
+        }
+    }
+
+    /// Returns the [`TextDocumentContentChangeEvent`] of `change`, which is the most recent change to the text of `self`.
+    fn change_event(&self, change: Change) -> TextDocumentContentChangeEvent {
+        change.into_event(self.file.text(), self.settings.encoding)
+    }
+
+    /// Returns the [`Output`] for writing `self` to its file.
+    fn save_output(&self) -> Output {
+        Output::SaveFile {
+            url: self.url().clone(),
+            text: self.file.text().clone(),
+            version: self.version,
+        }
+    }
+
+    /// Returns the [`Output`] for storing the history of `self`.
+    ///
+    /// The history is keyed to the text last written to the file, which is the text found when the file is read again.
+    fn history_output(&self) -> Output {
+        Output::StoreHistory {
+            url: self.url().clone(),
+            text: self.saved_text.clone(),
+            history: Arc::clone(&self.history),
+        }
+    }
+
+    /// Returns the [`Output`] that shows the rows of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn view_output(&self) -> Output {
+        Output::UpdateView {
+            region: self.region,
+            rows: self.rows()?,
+        }
+    }
+
+    /// Returns the [`Region`] of the page showing `self`.
+    pub(crate) const fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Returns the name of the file of `self`.
+    fn name(&self) -> String {
+        self.url()
+            .path_segments()
+            .and_then(Iterator::last)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Returns the [`Purl`] of `self`.
+    pub(crate) const fn url(&self) -> &Url {
+        self.file.url()
+    }
+
+    /// Returns the version of `self`.
+    pub(crate) const fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Records that the text of `self` has been written to its file.
+    fn mark_saved(&mut self) {
+        self.saved_version = self.version;
+        self.saved_text = self.file.text().clone();
+        Arc::make_mut(&mut self.history).mark_saved();
+    }
+
+    /// Returns if `self` has changed since it was last written to its file.
+    const fn is_modified(&self) -> bool {
+        self.version != self.saved_version
+    }
+
+    /// Returns the index of the character at `position`.
+    ///
+    /// A `position` beyond the end of its line is treated as the end of the line.
+    #[throws(ScopeFromRangeError)]
+    fn char_index(&self, position: Position) -> usize {
+        let text = self.file.text();
+        let line = usize::try_from(position.line).map_err(OverflowError::from)?;
+
+        if line >= text.len_lines() {
+            throw!(OutOfBoundsError);
+        }
+
+        text.line_to_char(line).saturating_add(cmp::min(
+            usize::try_from(position.character).map_err(OverflowError::from)?,
+            line_length(text.line(line)),
+        ))
+    }
+
+    /// Returns the [`Position`] of the character at `index`.
+    #[throws(OverflowError)]
+    fn position(&self, index: usize) -> Position {
+        let text = self.file.text();
+        let line = text.char_to_line(index);
+
+        Position::new(
+            u32::try_from(line)?,
+            u32::try_from(index.saturating_sub(text.line_to_char(line)))?,
+        )
+    }
+
+    /// Replaces the text within `range` with `new_text`.
+    ///
+    /// Only the lines touched by the edit are laid out again. The [`Change`] is not recorded in the history of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn edit(&mut self, range: Range, new_text: &str) -> Change {
+        let start = self.char_index(range.start)?;
+        let end = cmp::max(start, self.char_index(range.end)?);
+        let first_line = usize::try_from(range.start.line).map_err(OverflowError::from)?;
+        let old_count = usize::try_from(range.end.line.saturating_sub(range.start.line))
+            .map_err(OverflowError::from)?
+            .saturating_add(1);
+        let new_end = start.saturating_add(new_text.chars().count());
+        let wrap_width = self.wrap_width();
+        // A position beyond the end of its line is recorded as the end of the line.
+        let replaced = Range::new(self.position(start)?, self.position(end)?);
+        let text = self.file.text_mut();
+        let old_text = text.slice(start..end).to_string();
+
+        text.remove(start..end);
+        text.insert(start, new_text);
+
+        let new_count = text
+            .char_to_line(new_end)
+            .saturating_sub(first_line)
+            .saturating_add(1);
+
+        // The width of the gutter depends on the number of lines.
+        if self.wrap_width() == wrap_width {
+            self.relayout(first_line, old_count, new_count);
+        } else {
+            self.resize(self.region);
+        }
+
+        self.bookmarks.shift(first_line, old_count, new_count);
+        self.diagnostics.shift(first_line, old_count, new_count);
+        self.changes.shift(first_line, old_count, new_count);
+        self.range_selection = None;
+        Rc::make_mut(&mut self.root_symbol).replace_lines(
+            self.file.text(),
+            first_line,
+            old_count,
+            new_count,
+        )?;
+        self.clamp_selection();
+        self.version = self.version.saturating_add(1);
+
+        Change::new(
+            replaced,
+            self.position(new_end)?,
+            old_text,
+            new_text.to_string(),
+        )
+    }
+
+    /// Replaces the text within `range` with `new_text`, recording the [`Change`] in the history of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn change(&mut self, range: Range, new_text: &str) -> Change {
+        let change = self.edit(range, new_text)?;
+
+        Arc::make_mut(&mut self.history).record(change.clone(), &self.selection);
+        change
+    }
+
+    /// Replaces the layout of the `old_count` lines starting at `first_line` with the layout of the `new_count` lines that now start at `first_line`.
+    fn relayout(&mut self, first_line: usize, old_count: usize, new_count: usize) {
+        let text = self.file.text();
+        let width = self.wrap_width();
+        let tab_width = self.settings.tab_width;
+        let lines = Rc::make_mut(&mut self.lines);
+        let first_row = first_line
+            .checked_sub(1)
+            .and_then(|previous| lines.as_slice().get(previous))
+            .map_or(Row(0), Line::end_row);
+        let new_end = first_line.saturating_add(new_count);
+
+        lines.splice(
+            first_line..first_line.saturating_add(old_count),
+            lay_out(text, first_line..new_end, first_row, width, tab_width),
+        );
+
+        let mut next_row = new_end
+            .checked_sub(1)
+            .and_then(|last| lines.as_slice().get(last))
+            .map_or(Row(0), Line::end_row);
+
+        for line in lines.iter_mut().skip(new_end) {
+            line.first_row = next_row;
+            next_row = line.end_row();
+        }
+    }
+
+    /// Ensures that the selection of `self` describes an existing [`Symbol`].
+    fn clamp_selection(&mut self) {
+        let mut symbol = &*self.root_symbol;
+        let mut depth = 0_usize;
+
+        for index in &mut self.selection {
+            match symbol.children.len().checked_sub(1) {
+                Some(last) => {
+                    *index = cmp::min(*index, last);
+
+                    if let Some(child) = symbol.children.get(*index) {
+                        symbol = child;
+                    }
+
+                    depth = depth.saturating_add(1);
+                }
+                None => break,
+            }
+        }
+
+        self.selection.truncate(depth);
+    }
+
+    /// Starts inserting text at `placement` relative to the selection.
+    ///
+    /// All changes made until the insertion ends are undone as a single step.
+    #[throws(OutOfBoundsError)]
+    fn start_insert(&mut self, placement: InsertPlacement) {
+        let range = self.selection_range()?;
+
+        self.insertion = Some(match placement {
+            InsertPlacement::Before => range.start,
+            InsertPlacement::After => range.end,
+        });
+        Arc::make_mut(&mut self.history).start_group(self.selection.clone());
+        self.reveal_selection()?;
+    }
+
+    /// Inserts `text` at the insertion point of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn insert(&mut self, text: &str) -> Option<Output> {
+        if let Some(position) = self.insertion {
+            let change = self.change(Range::new(position, position), text)?;
+
+            self.insertion = Some(change.new_end());
+            self.reveal_selection()?;
+            Some(self.edit_output(vec![self.change_event(change)]))
+        } else {
+            None
+        }
+    }
+
+    /// Deletes the character before the insertion point of `self`.
+    #[throws(ScopeFromRangeError)]
+    fn delete_backward(&mut self) -> Option<Output> {
+        let mut output = None;
+
+        if let Some(position) = self.insertion {
+            if let Some(mut start) = self.char_index(position)?.checked_sub(1) {
+                let text = self.file.text();
+
+                // Deleting only the "\n" of "\r\n" would leave a line break.
+                if let Some(previous) = start.checked_sub(1) {
+                    if text.char(start) == '\n' && text.char(previous) == '\r' {
+                        start = previous;
+                    }
+                }
+
+                let start_position = self.position(start)?;
+                let change = self.change(Range::new(start_position, position), "")?;
+
+                self.insertion = Some(start_position);
+                self.reveal_selection()?;
+                output = Some(self.edit_output(vec![self.change_event(change)]));
+            }
+        }
+
+        output
+    }
+
+    /// Stops inserting text into `self`.
+    fn end_insert(&mut self) {
+        self.insertion = None;
+        Arc::make_mut(&mut self.history).end_group(self.selection.clone());
+    }
+
+    /// Returns the text within `range`.
+    #[throws(ScopeFromRangeError)]
+    fn text_in(&self, range: Range) -> String {
+        let start = self.char_index(range.start)?;
+        let end = cmp::max(start, self.char_index(range.end)?);
+
+        self.file.text().slice(start..end).to_string()
+    }
+
+    /// Returns the number of lines in `self`.
+    #[throws(OverflowError)]
+    fn line_count(&self) -> u32 {
+        u32::try_from(self.file.text().len_lines())?
+    }
+
+    /// Returns the line at `line`.
+    #[throws(ScopeFromRangeError)]
+    fn line_slice(&self, line: u32) -> RopeSlice<'_> {
+        self.file
+            .text()
+            .get_line(usize::try_from(line).map_err(OverflowError::from)?)
+            .ok_or(OutOfBoundsError)?
+    }
+
+    /// Returns the [`Position`] of the end of the line at `line`, before its line break.
+    #[throws(ScopeFromRangeError)]
+    fn line_end(&self, line: u32) -> Position {
+        Position::new(
+            line,
+            u32::try_from(line_length(self.line_slice(line)?)).map_err(OverflowError::from)?,
+        )
+    }
+
+    /// Returns if `range` covers whole lines.
+    ///
+    /// A `range` that starts within the indentation of its first line covers that line.
+    #[throws(ScopeFromRangeError)]
+    fn covers_lines(&self, range: &Range) -> bool {
+        usize::try_from(range.start.character).map_err(OverflowError::from)?
+            <= indentation(self.line_slice(range.start.line)?)
+            && range.end.character >= self.line_end(range.end.line)?.character
+    }
 
-            if text.get(before_end_index..end_index) == Some("\r") {
-                end_index = before_end_index;
-            }
+    /// Returns the selected text as a [`Register`].
+    ///
+    /// If the selection covers whole lines, the [`Register`] is linewise.
+    #[throws(ScopeFromRangeError)]
+    fn yank(&self) -> Register {
+        let range = self.selection_range()?;
+
+        if self.covers_lines(&range)? {
+            Register::linewise(self.text_in(Range::new(
+                Position::new(range.start.line, 0),
+                self.line_end(range.end.line)?,
+            ))?)
+        } else {
+            Register::charwise(self.text_in(range)?)
+        }
+    }
 
-            while row_end_index < end_index {
-                row_indices.push((prev_index, row_end_index));
-                row_count = row_count.saturating_add(1);
-                prev_index = row_end_index;
-                row_end_index = row_end_index.saturating_add(max_length);
+    /// Deletes the selected text, returning the deleted text and the [`Output`] of the change.
+    ///
+    /// If the selection covers whole lines, the lines are deleted along with their line break.
+    #[throws(ScopeFromRangeError)]
+    fn delete(&mut self) -> (Register, Output) {
+        let register = self.yank()?;
+        let range = self.selection_range()?;
+        let deleted_range = if register.is_linewise() {
+            let next_line = range.end.line.saturating_add(1);
+
+            if next_line < self.line_count()? {
+                Range::new(
+                    Position::new(range.start.line, 0),
+                    Position::new(next_line, 0),
+                )
+            } else if let Some(previous_line) = range.start.line.checked_sub(1) {
+                Range::new(
+                    self.line_end(previous_line)?,
+                    self.line_end(range.end.line)?,
+                )
+            } else {
+                Range::new(Position::new(0, 0), self.line_end(range.end.line)?)
             }
+        } else {
+            range
+        };
+        let change = self.change(deleted_range, "")?;
 
-            row_indices.push((prev_index, end_index));
-            row_count = row_count.saturating_add(1);
-            prev_index = index.saturating_add(1);
-            row_end_index = prev_index.saturating_add(max_length);
-            lines.push(Line::new(Row(first_row), row_indices));
-        }
-
-        let first_row = row_count;
-        let mut row_indices = Vec::new();
-        let text_length = text.len();
-        let last_line_length = u32::try_from(text_length.saturating_sub(prev_index))?;
+        self.reveal_selection()?;
 
-        while row_end_index < text_length {
-            row_indices.push((prev_index, row_end_index));
-            row_count = row_count.saturating_add(1);
-            prev_index = row_end_index;
-            row_end_index = row_end_index.saturating_add(max_length);
-        }
+        (register, self.edit_output(vec![self.change_event(change)]))
+    }
 
-        row_indices.push((prev_index, text_length));
-        row_count = row_count.saturating_add(1);
-        lines.push(Line::new(Row(first_row), row_indices));
+    /// Puts the text of `register` at `placement` relative to the selection.
+    ///
+    /// A linewise `register` is put on its own lines before or after the lines of the selection.
+    #[throws(ScopeFromRangeError)]
+    fn put(&mut self, register: &Register, placement: InsertPlacement) -> Output {
+        let range = self.selection_range()?;
+        let (position, text) = match (register.is_linewise(), placement) {
+            (false, InsertPlacement::Before) => (range.start, register.text().to_string()),
+            (false, InsertPlacement::After) => (range.end, register.text().to_string()),
+            (true, InsertPlacement::Before) => (
+                Position::new(range.start.line, 0),
+                format!("{}\n", register.text()),
+            ),
+            (true, InsertPlacement::After) => (
+                self.line_end(range.end.line)?,
+                format!("\n{}", register.text()),
+            ),
+        };
+        let change = self.change(Range::new(position, position), &text)?;
 
-        Self {
-            max_visible_row: Row(row_count.saturating_sub(dimensions.height.into())),
-            lines,
-            dimensions,
-            first_visible_row: Row(0),
-            version: 0,
-            root_symbol: Symbol::create_root(text, last_line_length)?,
-            file,
-            selection: Vec::new(),
-        }
+        self.reveal_selection()?;
+        self.edit_output(vec![self.change_event(change)])
     }
 
-    /// Scrolls `self` towards `direction`.
-    fn scroll(&mut self, direction: orient::ScreenDirection) {
-        if let Some(vertical_direction) = direction.vertical_direction() {
-            self.first_visible_row = match vertical_direction {
-                orient::AxialDirection::Positive => self.first_visible_row.saturating_add(5),
-                orient::AxialDirection::Negative => self.first_visible_row.saturating_sub(5),
-            };
-
-            if self.first_visible_row > self.max_visible_row {
-                self.first_visible_row = self.max_visible_row;
+    /// Adds the matches of the pattern of `substitution` within `self` to `substitution`.
+    fn add_candidates(&self, substitution: &mut Substitution) {
+        for index in 0..self.file.text().len_lines() {
+            if let Ok(line) = u32::try_from(index) {
+                substitution.add_line(&Location::Document, line, &self.line_text(index));
             }
         }
     }
 
-    /// Returns the [`Output`] for opening `self`.
-    fn open_output(&self) -> Output {
-        Output::EditDoc {
-            doc: self.clone(),
-            edit: DocEdit::Open {
-                version: self.version,
-            },
+    /// Applies `replacements`, which do not overlap, recording them in the history of `self` as a single change.
+    #[throws(ScopeFromRangeError)]
+    fn substitute(&mut self, mut replacements: Vec<Replacement>) -> Option<Output> {
+        if replacements.is_empty() {
+            None
+        } else {
+            let selection = self.selection.clone();
+            let mut changes = Vec::new();
+            let mut events = Vec::new();
+
+            // Replacing from the end of the document keeps the ranges of the earlier replacements valid.
+            replacements.sort_by_key(|replacement| cmp::Reverse(replacement.range.start));
+
+            for replacement in replacements {
+                let change = self.edit(replacement.range, &replacement.text)?;
+
+                events.push(self.change_event(change.clone()));
+                changes.push(change);
+            }
+
+            Arc::make_mut(&mut self.history).commit(
+                changes.clone(),
+                selection,
+                self.selection.clone(),
+            );
+            self.reveal_selection()?;
+            Some(self.edit_output(events))
         }
     }
 
-    /// Returns the [`Output`] for changing `self`.
-    fn change_output(&mut self) -> Output {
-        Output::EditDoc {
-            doc: self.clone(),
-            edit: DocEdit::Update,
-        }
+    /// Reverts the most recent change to `self`.
+    #[throws(ScopeFromRangeError)]
+    fn undo(&mut self) -> Option<Output> {
+        Arc::make_mut(&mut self.history)
+            .undo()
+            .map(|(replacements, selection)| self.restore(replacements, selection))
+            .transpose()?
     }
 
-    /// Returns the [`Purl`] of `self`.
-    pub(crate) const fn url(&self) -> &Url {
-        self.file.url()
+    /// Reapplies the most recently reverted change to `self`.
+    #[throws(ScopeFromRangeError)]
+    fn redo(&mut self) -> Option<Output> {
+        Arc::make_mut(&mut self.history)
+            .redo()
+            .map(|(replacements, selection)| self.restore(replacements, selection))
+            .transpose()?
     }
 
-    /// Converts `character` into an [`Address`] relative to the first character of the line.
-    #[throws(DivideByZeroError)]
-    fn relative_address_from_character(&self, character: Character) -> Address {
-        Address {
-            row: character.try_div(self.dimensions.width)?,
-            column: usize::from(character.try_rem(self.dimensions.width)?),
+    /// Applies `replacements` without recording them and restores `selection`.
+    #[throws(ScopeFromRangeError)]
+    fn restore(&mut self, replacements: Vec<Replacement>, selection: Vec<usize>) -> Output {
+        let mut changes = Vec::new();
+
+        for replacement in replacements {
+            let change = self.edit(replacement.range, &replacement.text)?;
+
+            changes.push(self.change_event(change));
         }
+
+        self.selection = selection;
+        self.clamp_selection();
+        self.reveal_selection()?;
+        self.edit_output(changes)
     }
 
-    /// Converts `range` into a [`Scope`].
+    /// Converts `range`, which counts chars, into a [`Scope`].
     #[throws(ScopeFromRangeError)]
     fn scope_from_range(&self, range: &Range) -> Scope {
-        let start_character_relative_address =
-            self.relative_address_from_character(range.start.character.into())?;
-        let end_character_relative_address =
-            self.relative_address_from_character(range.end.character.into())?;
-
         Scope {
-            start: Address {
-                row: self
-                    .lines
-                    .get(range.start.line)?
-                    .first_row
-                    .try_add(start_character_relative_address.row)?,
-                column: start_character_relative_address.column,
-            },
-            end: Address {
-                row: self
-                    .lines
-                    .get(range.end.line)?
-                    .first_row
-                    .try_add(end_character_relative_address.row)?,
-                column: end_character_relative_address.column,
-            },
+            start: self
+                .lines
+                .get(range.start.line)?
+                .address(usize::try_from(range.start.character).map_err(OverflowError::from)?),
+            end: self
+                .lines
+                .get(range.end.line)?
+                .address(usize::try_from(range.end.character).map_err(OverflowError::from)?),
+        }
+    }
+
+    /// Returns the [`Range`] that is shown as selected.
+    ///
+    /// While inserting, this is the character at the insertion point.
+    #[throws(OutOfBoundsError)]
+    fn selected_range(&self) -> Range {
+        if let Some(position) = self.insertion {
+            Range::new(
+                position,
+                Position::new(position.line, position.character.saturating_add(1)),
+            )
+        } else {
+            self.selection_range()?
         }
     }
 
     /// Returns a [`Vec`] of the rows of `self`.
+    ///
+    /// Only the text of the visible lines is read.
     #[throws(ScopeFromRangeError)]
     pub(crate) fn rows(&self) -> Vec<RowText> {
-        let selection_scope = self.scope_from_range(&self.selected_symbol()?.range)?;
-        (0_u64..)
-            .map(Row)
-            .zip(self.lines.iter().flat_map(|line| line.rows.iter()))
-            .skip(self.first_visible_row.try_into()?)
-            .take((*self.dimensions.height).into())
-            .map(|(row, &(start, end))| {
-                let text = self.file.text().get(start..end).unwrap_or_default();
-                let mut styled_texts = Vec::new();
-
-                if row < selection_scope.start.row || row > selection_scope.end.row {
-                    styled_texts.push(StyledText::new(text.to_string(), Style::Default));
-                } else {
-                    if row == selection_scope.start.row && selection_scope.start.column > 0 {
-                        styled_texts.push(StyledText::new(
-                            text.get(..selection_scope.start.column)
-                                .map(ToString::to_string)
-                                .unwrap_or_default(),
-                            Style::Default,
-                        ));
-                    }
+        let selected_range = self.selected_range()?;
+        let selection_scope = self.scope_from_range(&selected_range)?;
+        let selected_line =
+            usize::try_from(selected_range.start.line).map_err(OverflowError::from)?;
+        let height = usize::from(self.region.size.height);
+        let first_line = self
+            .lines
+            .as_slice()
+            .partition_point(|line| line.end_row() <= self.first_visible_row);
+        let mut rows = Vec::new();
+
+        'lines: for (index, line) in self.lines.iter().enumerate().skip(first_line) {
+            let text = self.file.text().line(index);
+            let matches = self.line_matches(index);
+            let trailing = self
+                .settings
+                .whitespace
+                .then(|| self.line_text(index).trim_end().chars().count());
+
+            for (row, &(start, end)) in (line.first_row.0..).map(Row).zip(line.rows.iter()) {
+                if rows.len() >= height {
+                    break 'lines;
+                }
 
-                    styled_texts.push(StyledText::new(
-                        text.get(
-                            if row == selection_scope.start.row {
-                                selection_scope.start.column
-                            } else {
-                                0
-                            }..if row == selection_scope.end.row {
-                                selection_scope.end.column
-                            } else {
-                                text.len()
-                            },
-                        )
-                        .map(ToString::to_string)
-                        .unwrap_or_default(),
-                        Style::Selection,
+                if row >= self.first_visible_row {
+                    let row_matches: Vec<ops::Range<usize>> = matches
+                        .iter()
+                        .filter(|range| range.start < end && range.end > start)
+                        .map(|range| {
+                            range.start.saturating_sub(start)
+                                ..cmp::min(range.end, end).saturating_sub(start)
+                        })
+                        .collect();
+
+                    let mut texts: Vec<StyledText> = self
+                        .gutter(index, row == line.first_row, selected_line)
+                        .into_iter()
+                        .collect();
+
+                    texts.append(&mut row_texts(
+                        &text.slice(start..end).to_string(),
+                        row,
+                        &selection_scope,
+                        &row_matches,
+                        Whitespace {
+                            tab_width: self.settings.tab_width,
+                            trailing: trailing.map(|trailing| trailing.saturating_sub(start)),
+                        },
+                        self.first_visible_column,
                     ));
-
-                    if row == selection_scope.end.row && selection_scope.end.column < text.len() {
-                        styled_texts.push(StyledText::new(
-                            text.get(selection_scope.end.column..)
-                                .map(ToString::to_string)
-                                .unwrap_or_default(),
-                            Style::Default,
-                        ));
-                    }
+                    rows.push(RowText::new(texts));
                 }
+            }
+        }
 
-                RowText::new(styled_texts)
-            })
-            .collect()
+        // Empty rows clear what was previously shown below the end of the text.
+        rows.resize_with(height, || RowText::new(Vec::new()));
+        rows
+    }
+
+    /// Returns the text of the line at `index`, without its line ending.
+    fn line_text(&self, index: usize) -> String {
+        let line = self.file.text().line(index);
+
+        line.slice(..line_length(line)).to_string()
+    }
+
+    /// Returns the char ranges of the matches of the pattern within the line at `index`.
+    fn line_matches(&self, index: usize) -> Vec<ops::Range<usize>> {
+        self.pattern
+            .as_ref()
+            .map(|pattern| search::match_ranges(pattern, &self.line_text(index)))
+            .unwrap_or_default()
+    }
+
+    /// Returns the [`Range`] of the first match of the pattern from `position` towards `direction`, wrapping around the document.
+    ///
+    /// Searching forward finds a match that starts at `position`.
+    fn find(&self, position: Position, direction: SearchDirection) -> Option<Range> {
+        let line_count = self.file.text().len_lines();
+        let start_line = cmp::min(
+            usize::try_from(position.line).ok()?,
+            line_count.saturating_sub(1),
+        );
+        let to_range = |index: usize, range: ops::Range<usize>| {
+            let line = u32::try_from(index).ok()?;
+
+            Some(Range::new(
+                Position::new(line, u32::try_from(range.start).ok()?),
+                Position::new(line, u32::try_from(range.end).ok()?),
+            ))
+        };
+
+        match direction {
+            SearchDirection::Forward => {
+                // Lines after the first `wrap` lines are searched after wrapping around the end.
+                let wrap = line_count.saturating_sub(start_line);
+
+                (start_line..line_count)
+                    .chain(0..=start_line)
+                    .enumerate()
+                    .flat_map(|(count, index)| {
+                        self.line_matches(index)
+                            .into_iter()
+                            .filter_map(move |range| Some((count, to_range(index, range)?)))
+                    })
+                    .find(|&(count, range)| count >= wrap || range.start >= position)
+            }
+            SearchDirection::Backward => {
+                // Lines after the first `wrap` lines are searched after wrapping around the start.
+                let wrap = start_line.saturating_add(1);
+
+                (0..=start_line)
+                    .rev()
+                    .chain((start_line..line_count).rev())
+                    .enumerate()
+                    .flat_map(|(count, index)| {
+                        self.line_matches(index)
+                            .into_iter()
+                            .rev()
+                            .filter_map(move |range| Some((count, to_range(index, range)?)))
+                    })
+                    .find(|&(count, range)| count >= wrap || range.start < position)
+            }
+        }
+        .map(|(_, range)| range)
     }
 
     /// Returns the output to close `self`.
@@ -485,7 +3198,7 @@ impl Document {
     /// Returns the selected symbol.
     #[throws(OutOfBoundsError)]
     fn selected_symbol(&self) -> &Symbol {
-        let mut symbol = &self.root_symbol;
+        let mut symbol = &*self.root_symbol;
 
         for index in &self.selection {
             symbol = symbol.children.get(*index).ok_or(OutOfBoundsError)?;
@@ -494,10 +3207,27 @@ impl Document {
         symbol
     }
 
+    /// Returns the [`Range`] of the selection.
+    #[throws(OutOfBoundsError)]
+    fn selection_range(&self) -> Range {
+        match self.range_selection {
+            Some(range) => range,
+            None => self.selected_symbol()?.range,
+        }
+    }
+
+    /// Selects `range` instead of a [`Symbol`], scrolling so that `range` is visible.
+    #[throws(OutOfBoundsError)]
+    fn select_range(&mut self, range: Range) {
+        self.range_selection = Some(range);
+        self.reveal_selection()?;
+    }
+
     /// Changes the current selection as specified by `movement`.
     #[throws(OutOfBoundsError)]
     fn change_selection(&mut self, movement: &SelectionMovement) {
         log::trace!("Move selection {:?}", movement);
+        self.range_selection = None;
 
         match *movement {
             SelectionMovement::Descend => {
@@ -526,6 +3256,7 @@ impl Document {
         }
 
         log::trace!("Selection {:?}", self.selected_symbol()?.range);
+        self.reveal_selection()?;
     }
 }
 
@@ -567,21 +3298,115 @@ struct Address {
     column: usize,
 }
 
+/// The glyph that shows the start of a tab.
+const TAB_GLYPH: char = '\u{2192}';
+/// The glyph that shows a trailing space.
+const SPACE_GLYPH: char = '\u{b7}';
+
+/// Describes how the whitespace of a row is shown.
+#[derive(Clone, Copy, Debug)]
+struct Whitespace {
+    /// The number of columns between tab stops.
+    tab_width: u8,
+    /// The index of the first char of the trailing whitespace of the row if whitespace is shown with visible glyphs.
+    trailing: Option<usize>,
+}
+
+/// Returns the [`StyledText`]s of `text` shown at `row`, with the portion within `scope` styled as selected and the char ranges of `matches` styled as matches.
+///
+/// Tabs are expanded to spaces as described by `whitespace`. The columns before `first_column` are not shown.
+fn row_texts(
+    text: &str,
+    row: Row,
+    scope: &Scope,
+    matches: &[ops::Range<usize>],
+    whitespace: Whitespace,
+    first_column: usize,
+) -> Vec<StyledText> {
+    let length = text.chars().count();
+    let (start, end) = if row < scope.start.row || row > scope.end.row {
+        (length, length)
+    } else {
+        (
+            if row == scope.start.row {
+                cmp::min(scope.start.column, length)
+            } else {
+                0
+            },
+            if row == scope.end.row {
+                cmp::min(scope.end.column, length)
+            } else {
+                length
+            },
+        )
+    };
+    let end = cmp::max(start, end);
+    let mut styled_texts = Vec::new();
+    let mut segment = String::new();
+    let mut segment_style = Style::Default;
+    let mut width = 0_usize;
+
+    // The selection is shown over any match.
+    for (column, ch) in text.chars().enumerate() {
+        let style = if (start..end).contains(&column) {
+            Style::Selection
+        } else if matches.iter().any(|range| range.contains(&column)) {
+            Style::Match
+        } else {
+            Style::Default
+        };
+
+        if style != segment_style && !segment.is_empty() {
+            styled_texts.push(StyledText::new(segment.split_off(0), segment_style));
+        }
+
+        segment_style = style;
+
+        let (shown, columns) = match ch {
+            '\t' => {
+                let columns = tab_columns(width, whitespace.tab_width);
+
+                if whitespace.trailing.is_some() {
+                    (
+                        format!("{}{}", TAB_GLYPH, " ".repeat(columns.saturating_sub(1))),
+                        columns,
+                    )
+                } else {
+                    (" ".repeat(columns), columns)
+                }
+            }
+            ' ' if whitespace
+                .trailing
+                .is_some_and(|trailing| column >= trailing) =>
+            {
+                (SPACE_GLYPH.to_string(), 1)
+            }
+            _ => (ch.to_string(), ch.width().unwrap_or(0)),
+        };
+        let hidden = first_column.saturating_sub(width);
+
+        // A char that is partly hidden is replaced by spaces.
+        if hidden == 0 {
+            segment.push_str(&shown);
+        } else {
+            segment.push_str(&" ".repeat(columns.saturating_sub(hidden)));
+        }
+
+        width = width.saturating_add(columns);
+    }
+
+    if !segment.is_empty() {
+        styled_texts.push(StyledText::new(segment, segment_style));
+    }
+
+    styled_texts
+}
+
 /// An index of rows in a [`Document`].
-#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Debug)]
 struct Row(u64);
 
 impl Row {
-    /// Adds `rhs` to `self`.
-    ///
-    /// # Error(s)
-    ///
-    /// If result of addition overflows the bounds of [`Row`], throws [`OverflowError`].
-    #[throws(OverflowError)]
-    fn try_add(self, rhs: Self) -> Self {
-        self.0.checked_add(rhs.0).map(Self).ok_or(OverflowError)?
-    }
-
     /// Adds `rhs` to `self`, saturating at [`u64::MAX`].
     const fn saturating_add(self, rhs: u64) -> Self {
         Self(self.0.saturating_add(rhs))
@@ -602,37 +3427,6 @@ impl TryFrom<Row> for usize {
     }
 }
 
-/// An index of a single line of text.
-#[derive(Clone, Copy)]
-struct Character(u32);
-
-impl Character {
-    /// Returns the value from dividing `self` by `rhs`.
-    #[throws(DivideByZeroError)]
-    fn try_div(self, rhs: Unit) -> Row {
-        self.0
-            .checked_div(u32::from(rhs))
-            .map(|result| Row(u64::from(result)))
-            .ok_or(DivideByZeroError)?
-    }
-
-    /// Returns the remainder of dividing `self` by `rhs`.
-    #[throws(DivideByZeroError)]
-    fn try_rem(self, rhs: Unit) -> Unit {
-        #[allow(clippy::unwrap_used)] // rhs <= u16::MAX, thus result <= u16::MAX.
-        self.0
-            .checked_rem(u32::from(rhs))
-            .map(|result| Unit::from(u16::try_from(result).unwrap()))
-            .ok_or(DivideByZeroError)?
-    }
-}
-
-impl From<u32> for Character {
-    fn from(value: u32) -> Self {
-        Self(value)
-    }
-}
-
 /// Error converting a [`Range`] into a [`Scope`].
 #[derive(Copy, Clone, Debug, thiserror::Error)]
 pub enum ScopeFromRangeError {
@@ -642,9 +3436,6 @@ pub enum ScopeFromRangeError {
     /// Attempting to access element outside of array.
     #[error(transparent)]
     OutOfBounds(#[from] OutOfBoundsError),
-    /// Attempting to divide by zero.
-    #[error(transparent)]
-    DivideByZero(#[from] DivideByZeroError),
 }
 
 /// Error when incrementing past the highest value.
@@ -663,11 +3454,6 @@ impl From<TryFromIntError> for OverflowError {
 #[error("Out of bounds error occurred")]
 pub struct OutOfBoundsError;
 
-/// Error when attempting to divide by zero.
-#[derive(Clone, Copy, Debug, thiserror::Error)]
-#[error("Division by zero occurred")]
-pub struct DivideByZeroError;
-
 #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
 /// A wrapper around [`Vec`] where the index is [`u32`].
 ///
@@ -680,11 +3466,6 @@ struct U32Vec<T> {
 
 #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
 impl<T> U32Vec<T> {
-    /// Creates a new empty [`U32Vec`].
-    const fn new() -> Self {
-        Self { vec: Vec::new() }
-    }
-
     /// Returns a reference to the element at `index`.
     #[allow(clippy::unwrap_in_result)] // usize::try_from(u32) will always pass due to cfg attribute on target_pointer_width.
     #[throws(OutOfBoundsError)]
@@ -701,8 +3482,595 @@ impl<T> U32Vec<T> {
         self.vec.iter()
     }
 
-    /// Adds `value` to the end of `self`.
-    fn push(&mut self, value: T) {
-        self.vec.push(value);
+    /// Returns the [`IterMut`] of `self`.
+    fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.vec.iter_mut()
+    }
+
+    /// Returns a reference to the last element of `self`.
+    fn last(&self) -> Option<&T> {
+        self.vec.last()
+    }
+
+    /// Returns the elements of `self` as a slice.
+    fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    /// Replaces the elements of `self` within `range` with `values`.
+    ///
+    /// `range` is limited to the elements of `self`.
+    fn splice(&mut self, range: ops::Range<usize>, values: Vec<T>) {
+        let end = cmp::min(range.end, self.vec.len());
+        let start = cmp::min(range.start, end);
+        self.vec.splice(start..end, values).for_each(drop);
+    }
+}
+
+#[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+impl<T> From<Vec<T>> for U32Vec<T> {
+    fn from(vec: Vec<T>) -> Self {
+        Self { vec }
+    }
+}
+
+/// Testing of the `app` module.
+#[cfg(test)]
+mod test {
+    use {super::*, encoding::PositionEncoding};
+
+    /// Rows shall break between grapheme clusters once their display width fills the row.
+    #[test]
+    fn wrap() {
+        let wide = Rope::from("a\u{4e2d}\u{6587}b\n");
+        let combined = Rope::from("e\u{301}e\u{301}e\u{301}");
+        let line = Line::new(Row(0), wide.slice(..), 4, 4);
+
+        assert_eq!(line.rows, vec![(0, 2), (2, 4)]);
+        assert_eq!(
+            Line::new(Row(0), combined.slice(..), 2, 4).rows,
+            vec![(0, 4), (4, 6)]
+        );
+        assert_eq!(
+            Line::new(Row(0), wide.slice(..), 1, 4).rows,
+            vec![(0, 1), (1, 2), (2, 3), (3, 4)]
+        );
+
+        let start = line.address(2);
+        let end = line.address(4);
+
+        assert_eq!((start.row, start.column), (Row(1), 0));
+        assert_eq!((end.row, end.column), (Row(1), 2));
+    }
+
+    /// A tab shall extend to the next tab stop of its row.
+    #[test]
+    fn tabs() {
+        let text = Rope::from("a\tb\t \n");
+
+        assert_eq!(Line::new(Row(0), text.slice(..), 9, 4).rows, vec![(0, 5)]);
+        assert_eq!(
+            Line::new(Row(0), text.slice(..), 4, 4).rows,
+            vec![(0, 2), (2, 4), (4, 5)]
+        );
+        assert_eq!(tab_columns(5, 4), 3);
+        assert_eq!(tab_columns(5, 0), 1);
+    }
+
+    /// The indentation of a line shall count its leading spaces and tabs, but not its line break.
+    #[test]
+    fn indentation_length() {
+        let text = Rope::from("\t  a b\n  \n\n");
+
+        assert_eq!(indentation(text.line(0)), 3);
+        assert_eq!(indentation(text.line(1)), 2);
+        assert_eq!(indentation(text.line(2)), 0);
+    }
+
+    /// A line that does not wrap shall be shown on a single row, however wide it is.
+    #[test]
+    fn no_wrap() {
+        let text = Rope::from("a\t\u{4e2d}\u{6587}b\n");
+
+        assert_eq!(
+            Line::new(Row(0), text.slice(..), usize::MAX, 4).rows,
+            vec![(0, 5)]
+        );
+        assert_eq!(text_width("a\t\u{4e2d}\u{6587}b".chars(), 4), 9);
+    }
+
+    /// Clicking shall select the innermost symbol, and an empty symbol shall contain its start.
+    #[test]
+    fn click_path() {
+        let inner = Symbol {
+            range: Range::new(Position::new(1, 4), Position::new(1, 8)),
+            children: Vec::new(),
+        };
+        let outer = Symbol {
+            range: Range::new(Position::new(1, 0), Position::new(3, 0)),
+            children: vec![inner],
+        };
+        let empty = Symbol {
+            range: Range::new(Position::new(4, 0), Position::new(4, 0)),
+            children: Vec::new(),
+        };
+        let root = Symbol {
+            range: Range::new(Position::new(0, 0), Position::new(5, 0)),
+            children: vec![empty, outer],
+        };
+
+        assert_eq!(root.path_to(Position::new(1, 5)), vec![1, 0]);
+        assert_eq!(root.path_to(Position::new(1, 8)), vec![1]);
+        assert_eq!(root.path_to(Position::new(4, 0)), vec![0]);
+        assert_eq!(root.path_to(Position::new(0, 3)), Vec::<usize>::new());
+    }
+
+    /// Scrolling by part of a page shall depend on the size of the page.
+    #[test]
+    fn scroll_amounts() {
+        assert_eq!(scroll_distance(ScrollAmount::Step, 5, 20), 5);
+        assert_eq!(scroll_distance(ScrollAmount::Line, 5, 20), 1);
+        assert_eq!(scroll_distance(ScrollAmount::HalfPage, 5, 21), 10);
+        assert_eq!(scroll_distance(ScrollAmount::HalfPage, 5, 1), 1);
+        assert_eq!(scroll_distance(ScrollAmount::Page, 5, 20), 20);
+        assert_eq!(scroll_distance(ScrollAmount::Edge, 5, 20), u64::MAX);
+    }
+
+    /// Returns a [`Pane`] with a document of each of `names`, showing the document at `current`.
+    fn pane(names: &[&str], current: usize) -> Pane {
+        Pane {
+            docs: names
+                .iter()
+                .filter_map(|name| {
+                    Url::parse(&format!("file:///{}", name))
+                        .ok()
+                        .and_then(|url| {
+                            Document::new(
+                                File::new(url, "a\n"),
+                                Region::default(),
+                                Settings::default(),
+                            )
+                            .ok()
+                        })
+                })
+                .collect(),
+            current,
+            ..Pane::default()
+        }
+    }
+
+    /// Returns the names of the documents of `pane`.
+    fn names(pane: &Pane) -> Vec<String> {
+        pane.docs.iter().map(Document::name).collect()
+    }
+
+    /// A line shall show the sign of its diagnostics before that of its change and its bookmark, and only diagnostics of the shown version shall apply.
+    #[test]
+    fn sign_priority() {
+        let mut processor = Processor {
+            pane: pane(&["a.rs"], 0),
+            ..Processor::default()
+        };
+        let sign = |processor: &Processor| {
+            processor
+                .pane
+                .doc()
+                .and_then(|doc| doc.sign_sources().iter().find_map(|source| source.sign(0)))
+        };
+        let diagnose = |version| {
+            Url::parse("file:///a.rs")
+                .map(|uri| {
+                    Operation::Diagnose(PublishDiagnosticsParams::new(
+                        uri,
+                        vec![lsp_types::Diagnostic {
+                            severity: Some(lsp_types::DiagnosticSeverity::Warning),
+                            ..lsp_types::Diagnostic::default()
+                        }],
+                        version,
+                    ))
+                })
+                .ok()
+        };
+
+        assert!(processor.run(Command::ToggleBookmark).is_ok());
+        assert_eq!(sign(&processor), Some('*'));
+
+        if let Some(doc) = processor.pane.docs.first_mut() {
+            doc.changes = VcsChanges::from_diff("@@ -1 +1 @@\n");
+        }
+
+        assert_eq!(sign(&processor), Some('~'));
+
+        if let Some(operation) = diagnose(Some(1)) {
+            assert!(processor.operate(operation).is_ok());
+        }
+
+        assert_eq!(sign(&processor), Some('~'));
+
+        if let Some(operation) = diagnose(None) {
+            assert!(processor.operate(operation).is_ok());
+        }
+
+        assert_eq!(sign(&processor), Some('W'));
+    }
+
+    /// The open documents shall be listed by their number, marking the one that is shown.
+    #[test]
+    fn list_docs() {
+        assert_eq!(
+            pane(&["a.rs", "b.rs"], 1).list_rows(),
+            vec![
+                picker::row_text("1 /a.rs", &[], false),
+                picker::row_text("2 /b.rs", &[], true)
+            ]
+        );
+    }
+
+    /// Switching shall only show a document that is open.
+    #[test]
+    fn switch_doc() {
+        let mut pane = pane(&["a.rs", "b.rs"], 0);
+
+        assert!(pane.switch_doc(1).is_some());
+        assert_eq!(pane.current, 1);
+        assert!(pane.switch_doc(2).is_none());
+        assert_eq!(pane.current, 1);
+    }
+
+    /// Closing a document shall keep showing the same document, or the one after the closed document if it was shown.
+    #[test]
+    fn close_doc() {
+        let mut pane = pane(&["a.rs", "b.rs", "c.rs"], 2);
+
+        assert_eq!(
+            pane.close_doc(0).map(|(doc, _)| doc.name()),
+            Some("a.rs".to_string())
+        );
+        assert_eq!(pane.doc().map(Document::name), Some("c.rs".to_string()));
+        assert!(pane.close_doc(2).is_none());
+        assert_eq!(
+            pane.close_doc(1).map(|(doc, _)| doc.name()),
+            Some("c.rs".to_string())
+        );
+        assert_eq!(names(&pane), vec!["b.rs"]);
+        assert_eq!(pane.doc().map(Document::name), Some("b.rs".to_string()));
+        assert!(matches!(
+            pane.close_doc(0).map(|(_, outputs)| outputs).as_deref(),
+            Some([Output::UpdateView { .. }])
+        ));
+        assert!(pane.doc().is_none());
+    }
+
+    /// `buffer` shall show the document of its number, or report that no such document is open.
+    #[test]
+    fn switch_doc_command() {
+        let mut processor = Processor {
+            pane: pane(&["a.rs", "b.rs"], 0),
+            ..Processor::default()
+        };
+
+        assert!(processor.run(Command::SwitchDoc { number: 2 }).is_ok());
+        assert_eq!(processor.pane.current, 1);
+        assert!(matches!(
+            processor.run(Command::SwitchDoc { number: 3 }).as_deref(),
+            Ok([Output::Notify {
+                message: ShowMessageParams {
+                    typ: MessageType::Error,
+                    ..
+                }
+            }])
+        ));
+        assert_eq!(processor.pane.current, 1);
+    }
+
+    /// `close` shall not discard changes that have not been written, unless another pane shows the document.
+    #[test]
+    fn close_doc_command() {
+        let mut processor = Processor {
+            pane: pane(&["a.rs", "b.rs"], 0),
+            ..Processor::default()
+        };
+
+        if let Some(doc) = processor.pane.doc_mut() {
+            doc.version = 1;
+        }
+
+        assert!(processor.run(Command::CloseDoc { number: None }).is_ok());
+        assert_eq!(names(&processor.pane), vec!["a.rs", "b.rs"]);
+
+        processor.panes.push(processor.pane.split(1));
+        assert!(processor.run(Command::CloseDoc { number: Some(1) }).is_ok());
+        assert_eq!(names(&processor.pane), vec!["b.rs"]);
+
+        processor.panes.clear();
+        processor.pane = pane(&["a.rs"], 0);
+
+        if let Some(doc) = processor.pane.doc_mut() {
+            doc.version = 1;
+        }
+
+        // A write that does not succeed leaves the document modified.
+        assert!(processor.run(Command::Write).is_ok());
+        assert!(processor.run(Command::CloseDoc { number: None }).is_ok());
+        assert_eq!(names(&processor.pane), vec!["a.rs"]);
+
+        if let Some(url) = processor.pane.doc().map(|doc| doc.url().clone()) {
+            assert!(processor
+                .operate(Operation::MarkSaved(SavedFile {
+                    url,
+                    version: 1,
+                    changes: VcsChanges::default(),
+                }))
+                .is_ok());
+        }
+
+        assert!(processor.run(Command::CloseDoc { number: None }).is_ok());
+        assert!(processor.pane.docs.is_empty());
+    }
+
+    /// A history stored after a change is written and then undone shall be keyed to the written text and continue from it when reopened.
+    #[test]
+    fn reopen_history() {
+        let mut processor = Processor {
+            pane: pane(&["a.rs"], 0),
+            ..Processor::default()
+        };
+
+        assert!(processor
+            .operate(Operation::StartInsert(InsertPlacement::Before))
+            .is_ok());
+        assert!(processor
+            .operate(Operation::InsertText("b".to_string()))
+            .is_ok());
+        assert!(processor.operate(Operation::EndInsert).is_ok());
+        assert!(processor.run(Command::Write).is_ok());
+
+        if let Some(file) = processor.pane.doc().map(|doc| SavedFile {
+            url: doc.url().clone(),
+            version: doc.version(),
+            changes: VcsChanges::default(),
+        }) {
+            assert!(processor.operate(Operation::MarkSaved(file)).is_ok());
+        }
+
+        let stored = processor
+            .operate(Operation::Undo)
+            .ok()
+            .into_iter()
+            .flatten()
+            .find_map(|output| match output {
+                Output::StoreHistory { url, text, history } => Some((url, text, history)),
+                _ => None,
+            });
+
+        assert_eq!(
+            processor.pane.doc().map(|doc| doc.file.text().to_string()),
+            Some("a\n".to_string())
+        );
+
+        assert_eq!(
+            stored.as_ref().map(|(_, text, _)| text.to_string()),
+            Some("ba\n".to_string())
+        );
+
+        let reopened = stored.and_then(|(url, text, history)| {
+            let mut doc = Document::new(
+                File::new(url, &text.to_string()),
+                Region::default(),
+                Settings::default(),
+            )
+            .ok()?;
+
+            doc.history = Arc::new((*history).clone().into_saved()?);
+            doc.undo().ok()??;
+            Some(doc.file.text().to_string())
+        });
+
+        assert_eq!(reopened, Some("a\n".to_string()));
+    }
+
+    /// Quitting shall be refused while any document of any pane has unsaved changes.
+    #[test]
+    fn quit_unsaved() {
+        let mut processor = Processor {
+            pane: pane(&["a.rs"], 0),
+            panes: vec![pane(&["b.rs"], 0)],
+            ..Processor::default()
+        };
+
+        if let Some(doc) = processor.panes.first_mut().and_then(Pane::doc_mut) {
+            doc.version = 1;
+        }
+
+        assert!(processor
+            .run(Command::Quit)
+            .ok()
+            .into_iter()
+            .flatten()
+            .all(|output| !matches!(output, Output::Quit)));
+        assert_eq!(names(&processor.pane), vec!["a.rs"]);
+
+        if let Some(doc) = processor.panes.first_mut().and_then(Pane::doc_mut) {
+            doc.saved_version = 1;
+        }
+
+        assert!(processor
+            .run(Command::Quit)
+            .ok()
+            .into_iter()
+            .flatten()
+            .any(|output| matches!(output, Output::Quit)));
+    }
+
+    /// Negotiating another position encoding shall resend the text of each changed file once.
+    #[test]
+    fn negotiate_encoding() {
+        let mut processor = Processor {
+            pane: pane(&["a.rs", "b.rs"], 0),
+            ..Processor::default()
+        };
+
+        if let Some(doc) = processor.pane.doc_mut() {
+            doc.version = 1;
+        }
+
+        processor.panes.push(processor.pane.split(1));
+        assert!(matches!(
+            processor
+                .operate(Operation::SetPositionEncoding(PositionEncoding::Utf32))
+                .as_deref(),
+            Ok([
+                Output::EditDoc {
+                    edit: DocEdit::Resend,
+                    ..
+                },
+                Output::UpdateHeader { .. }
+            ])
+        ));
+        assert_eq!(processor.pane.doc().map(Document::version), Some(2));
+        assert_eq!(
+            processor
+                .panes
+                .first()
+                .and_then(Pane::doc)
+                .map(Document::version),
+            Some(2)
+        );
+        assert_eq!(processor.settings.encoding, PositionEncoding::Utf32);
+        assert!(matches!(
+            processor
+                .operate(Operation::SetPositionEncoding(PositionEncoding::Utf32))
+                .as_deref(),
+            Ok([Output::UpdateHeader { .. }])
+        ));
+    }
+
+    /// The changes sent to a language server shall follow how it syncs documents.
+    #[test]
+    fn sync_changes() {
+        let mut processor = Processor {
+            pane: pane(&["a.rs"], 0),
+            ..Processor::default()
+        };
+
+        if let Some(doc) = processor.pane.doc_mut() {
+            doc.version = 1;
+        }
+
+        assert!(matches!(
+            processor
+                .operate(Operation::SetTextDocumentSync(TextDocumentSyncKind::Full))
+                .as_deref(),
+            Ok([
+                Output::EditDoc {
+                    edit: DocEdit::Resend,
+                    ..
+                },
+                Output::UpdateHeader { .. }
+            ])
+        ));
+
+        let change = TextDocumentContentChangeEvent {
+            range: Some(Range::default()),
+            range_length: None,
+            text: "b".to_string(),
+        };
+
+        assert!(matches!(
+            processor
+                .pane
+                .doc()
+                .map(|doc| doc.edit_output(vec![change.clone()])),
+            Some(Output::EditDoc {
+                edit: DocEdit::Change { changes },
+                ..
+            }) if changes.len() == 1 && changes.iter().all(|change| change.range.is_none())
+        ));
+        assert!(matches!(
+            processor
+                .operate(Operation::SetTextDocumentSync(TextDocumentSyncKind::None))
+                .as_deref(),
+            Ok([Output::UpdateHeader { .. }])
+        ));
+        assert!(matches!(
+            processor
+                .pane
+                .doc()
+                .map(|doc| doc.edit_output(vec![change])),
+            Some(Output::EditDoc {
+                edit: DocEdit::Change { changes },
+                ..
+            }) if changes.is_empty()
+        ));
+    }
+
+    /// Clicks outside every pane shall be ignored and the wheel shall scroll the pane under the mouse without focusing it.
+    #[test]
+    fn mouse_regions() {
+        let left = Region {
+            row: Unit::from(0),
+            column: Unit::from(0),
+            size: Dimensions {
+                height: Unit::from(5),
+                width: Unit::from(10),
+            },
+        };
+        let right = Region {
+            column: Unit::from(11),
+            ..left
+        };
+        let mut processor = Processor {
+            pane: pane(&["a.rs"], 0),
+            panes: vec![Pane {
+                id: 1,
+                ..pane(&["b.rs"], 0)
+            }],
+            ..Processor::default()
+        };
+
+        processor.pane.set_region(left, &mut Vec::new());
+
+        if let Some(pane) = processor.panes.first_mut() {
+            pane.set_region(right, &mut Vec::new());
+        }
+
+        assert!(matches!(
+            processor
+                .operate(Operation::Click {
+                    row: Unit::from(2),
+                    column: Unit::from(10),
+                })
+                .as_deref(),
+            Ok([Output::UpdateHeader { .. }])
+        ));
+        assert_eq!(processor.pane.id, 0);
+        assert!(matches!(
+            processor
+                .operate(Operation::Wheel {
+                    direction: orient::ScreenDirection::Down,
+                    row: Unit::from(2),
+                    column: Unit::from(12),
+                })
+                .as_deref(),
+            Ok([Output::UpdateView { region, .. }, Output::UpdateHeader { .. }]) if *region == right
+        ));
+        assert_eq!(processor.pane.id, 0);
+        assert!(processor
+            .operate(Operation::Click {
+                row: Unit::from(2),
+                column: Unit::from(12),
+            })
+            .is_ok());
+        assert_eq!(processor.pane.id, 1);
+    }
+
+    /// Scrolling to the selection shall move as little as possible while keeping the scroll-off margin.
+    #[test]
+    fn scroll_off() {
+        assert_eq!(revealing_row(Row(0), Row(3)..=Row(3), 10, 2), Row(0));
+        assert_eq!(revealing_row(Row(0), Row(20)..=Row(20), 10, 2), Row(13));
+        assert_eq!(revealing_row(Row(13), Row(5)..=Row(5), 10, 2), Row(3));
+        assert_eq!(revealing_row(Row(0), Row(20)..=Row(40), 10, 2), Row(18));
+        assert_eq!(revealing_row(Row(0), Row(20)..=Row(20), 10, 100), Row(15));
     }
 }
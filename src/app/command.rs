@@ -0,0 +1,650 @@
+//! Implements the commands that can be executed from the command bar.
+use {
+    super::settings::{Setting, SettingError},
+    fehler::{throw, throws},
+    regex::Regex,
+    thiserror::Error as ThisError,
+};
+
+/// A command that has been parsed from the command bar.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Command {
+    /// Opens the file at `path`.
+    Open {
+        /// The path of the file, relative to the root directory.
+        path: String,
+    },
+    /// Writes the current document to its file.
+    Write,
+    /// Quits the application.
+    Quit,
+    /// Sets an option.
+    Set(Setting),
+    /// Shows help for the command called `command`, or for all commands if `None`.
+    Help {
+        /// The name of the command.
+        command: Option<&'static CommandSpec>,
+    },
+    /// Reverts the most recent change.
+    Undo,
+    /// Reapplies the most recently reverted change.
+    Redo,
+    /// Searches all files within the root directory for `pattern`.
+    Grep {
+        /// The regex that is searched for.
+        pattern: String,
+    },
+    /// Lists the open documents.
+    ListDocs,
+    /// Shows the open document numbered `number`.
+    SwitchDoc {
+        /// The number of the document, starting at 1.
+        number: usize,
+    },
+    /// Closes the open document numbered `number`, or the shown document if `None`.
+    CloseDoc {
+        /// The number of the document, starting at 1.
+        number: Option<usize>,
+    },
+    /// Replaces the matches of `pattern` within `scope` by `replacement`, confirming each replacement.
+    Substitute {
+        /// The regex that is replaced.
+        pattern: String,
+        /// The text that replaces each match, which may refer to the capture groups of `pattern`.
+        replacement: String,
+        /// The text that is searched.
+        scope: SubstituteScope,
+    },
+    /// Switches between wrapping the lines of the current document and scrolling them horizontally.
+    ToggleWrap,
+    /// Adds or removes a bookmark on the first selected line of the current document.
+    ToggleBookmark,
+    /// Scrolls the current document so that the selection is shown at the given place.
+    AlignView(ViewAlignment),
+}
+
+/// Describes where a scroll shows the selection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ViewAlignment {
+    /// The top of the document, apart from the scroll-off rows.
+    Top,
+    /// The middle of the document.
+    Center,
+}
+
+/// Describes the text that is searched by a substitution.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SubstituteScope {
+    /// The whole document.
+    Document,
+    /// The selection of the document.
+    Selection,
+    /// All files within the root directory.
+    Project,
+}
+
+/// An error parsing a command.
+#[derive(Debug, PartialEq, ThisError)]
+pub(crate) enum CommandError {
+    /// No command with the given name exists.
+    #[error("unknown command `{0}`")]
+    Unknown(String),
+    /// A required argument was not given.
+    #[error("`{command}` requires {argument}; usage: {usage}")]
+    MissingArgument {
+        /// The name of the command.
+        command: &'static str,
+        /// The name of the argument.
+        argument: &'static str,
+        /// The usage of the command.
+        usage: &'static str,
+    },
+    /// More arguments were given than the command accepts.
+    #[error("too many arguments to `{command}`; usage: {usage}")]
+    TooManyArguments {
+        /// The name of the command.
+        command: &'static str,
+        /// The usage of the command.
+        usage: &'static str,
+    },
+    /// An argument is not valid.
+    #[error("invalid argument `{argument}` to `{command}`; usage: {usage}")]
+    InvalidArgument {
+        /// The name of the command.
+        command: &'static str,
+        /// The argument.
+        argument: String,
+        /// The usage of the command.
+        usage: &'static str,
+    },
+    /// An option could not be set.
+    #[error(transparent)]
+    Setting(#[from] SettingError),
+    /// A pattern is not a valid regex.
+    #[error("invalid pattern: {0}")]
+    Pattern(#[from] regex::Error),
+}
+
+/// Describes a command that can be executed from the command bar.
+#[derive(Debug)]
+pub(crate) struct CommandSpec {
+    /// The name of the command.
+    pub(crate) name: &'static str,
+    /// Other names that execute the command.
+    pub(crate) aliases: &'static [&'static str],
+    /// Describes the arguments of the command.
+    pub(crate) usage: &'static str,
+    /// Describes what the command does.
+    pub(crate) help: &'static str,
+    /// Parses the arguments of the command.
+    parse: fn(&'static Self, &str) -> Result<Command, CommandError>,
+}
+
+impl PartialEq for CommandSpec {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl CommandSpec {
+    /// Returns if `name` refers to `self`.
+    fn is_called(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    /// Throws [`CommandError::TooManyArguments`] if `arguments` is not empty.
+    #[throws(CommandError)]
+    fn expect_none(&'static self, arguments: &str) {
+        if !arguments.is_empty() {
+            throw!(CommandError::TooManyArguments {
+                command: self.name,
+                usage: self.usage,
+            });
+        }
+    }
+
+    /// Parses `argument` as the number of a document, returning `None` if `argument` is empty.
+    #[throws(CommandError)]
+    fn parse_number(&'static self, argument: &str) -> Option<usize> {
+        if argument.is_empty() {
+            None
+        } else {
+            Some(
+                argument
+                    .parse()
+                    .ok()
+                    .ok_or_else(|| CommandError::InvalidArgument {
+                        command: self.name,
+                        argument: argument.to_string(),
+                        usage: self.usage,
+                    })?,
+            )
+        }
+    }
+
+    /// Returns a [`CommandError::MissingArgument`] for the argument called `argument`.
+    const fn missing(&'static self, argument: &'static str) -> CommandError {
+        CommandError::MissingArgument {
+            command: self.name,
+            argument,
+            usage: self.usage,
+        }
+    }
+}
+
+/// All commands that can be executed from the command bar.
+pub(crate) static COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "open",
+        aliases: &["o", "edit", "e"],
+        usage: "open <path>",
+        help: "opens the file at <path>, relative to the root directory",
+        parse: parse_open,
+    },
+    CommandSpec {
+        name: "write",
+        aliases: &["w"],
+        usage: "write",
+        help: "writes the current document to its file",
+        parse: parse_write,
+    },
+    CommandSpec {
+        name: "quit",
+        aliases: &["q"],
+        usage: "quit",
+        help: "quits the application, unless a document has unsaved changes",
+        parse: parse_quit,
+    },
+    CommandSpec {
+        name: "set",
+        aliases: &[],
+        usage: "set <option>=<value>",
+        help: "sets <option> to <value>; see `help set` for all options",
+        parse: parse_set,
+    },
+    CommandSpec {
+        name: "help",
+        aliases: &["h"],
+        usage: "help [<command>]",
+        help: "shows help for <command>, or for all commands",
+        parse: parse_help,
+    },
+    CommandSpec {
+        name: "undo",
+        aliases: &["u"],
+        usage: "undo",
+        help: "reverts the most recent change",
+        parse: parse_undo,
+    },
+    CommandSpec {
+        name: "redo",
+        aliases: &["r"],
+        usage: "redo",
+        help: "reapplies the most recently reverted change",
+        parse: parse_redo,
+    },
+    CommandSpec {
+        name: "grep",
+        aliases: &["g"],
+        usage: "grep [-F] <pattern>",
+        help: "searches all files for the regex <pattern>, or the literal text with -F",
+        parse: parse_grep,
+    },
+    CommandSpec {
+        name: "buffers",
+        aliases: &["ls"],
+        usage: "buffers",
+        help: "lists the open documents",
+        parse: parse_buffers,
+    },
+    CommandSpec {
+        name: "buffer",
+        aliases: &["b"],
+        usage: "buffer <number>",
+        help: "shows the open document numbered <number>",
+        parse: parse_buffer,
+    },
+    CommandSpec {
+        name: "close",
+        aliases: &["bd"],
+        usage: "close [<number>]",
+        help: "closes the open document numbered <number>, or the shown document, unless its changes are not written",
+        parse: parse_close,
+    },
+    CommandSpec {
+        name: "substitute",
+        aliases: &["s"],
+        usage: "substitute [-s|-p] /<pattern>/<replacement>/",
+        help: "replaces the regex <pattern> by <replacement> in the document, the selection with -s, or the document and all files that are not open with -p; $1 refers to a capture group",
+        parse: parse_substitute,
+    },
+    CommandSpec {
+        name: "wrap",
+        aliases: &[],
+        usage: "wrap",
+        help: "switches between wrapping the lines of the current document and scrolling them horizontally",
+        parse: parse_wrap,
+    },
+    CommandSpec {
+        name: "bookmark",
+        aliases: &["mark"],
+        usage: "bookmark",
+        help: "adds or removes a bookmark, shown in the sign column, on the first selected line",
+        parse: parse_bookmark,
+    },
+    CommandSpec {
+        name: "center",
+        aliases: &[],
+        usage: "center",
+        help: "scrolls the current document so that the selection is in the middle",
+        parse: parse_center,
+    },
+    CommandSpec {
+        name: "top",
+        aliases: &[],
+        usage: "top",
+        help: "scrolls the current document so that the selection is at the top",
+        parse: parse_top,
+    },
+];
+
+/// Returns the [`CommandSpec`] called `name`.
+pub(crate) fn find(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|spec| spec.is_called(name))
+}
+
+/// Parses `text`, which is a command name followed by its arguments.
+#[throws(CommandError)]
+pub(crate) fn parse(text: &str) -> Command {
+    let text = text.trim();
+    let (name, arguments) = text
+        .split_once(char::is_whitespace)
+        .map_or((text, ""), |(name, arguments)| (name, arguments.trim()));
+    let spec = find(name).ok_or_else(|| CommandError::Unknown(name.to_string()))?;
+
+    (spec.parse)(spec, arguments)?
+}
+
+/// Parses the arguments of `open`.
+#[throws(CommandError)]
+fn parse_open(spec: &'static CommandSpec, arguments: &str) -> Command {
+    if arguments.is_empty() {
+        throw!(spec.missing("<path>"));
+    }
+
+    Command::Open {
+        path: arguments.to_string(),
+    }
+}
+
+/// Parses the arguments of `write`.
+#[throws(CommandError)]
+fn parse_write(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::Write
+}
+
+/// Parses the arguments of `quit`.
+#[throws(CommandError)]
+fn parse_quit(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::Quit
+}
+
+/// Parses the arguments of `set`, which are either `<option>=<value>` or `<option> <value>`.
+#[throws(CommandError)]
+fn parse_set(spec: &'static CommandSpec, arguments: &str) -> Command {
+    if arguments.is_empty() {
+        throw!(spec.missing("<option>"));
+    }
+
+    let (name, value) = arguments
+        .split_once(|ch: char| ch == '=' || ch.is_whitespace())
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .ok_or_else(|| spec.missing("<value>"))?;
+
+    Command::Set(Setting::parse(name, value)?)
+}
+
+/// Parses the arguments of `help`.
+#[throws(CommandError)]
+fn parse_help(spec: &'static CommandSpec, arguments: &str) -> Command {
+    let mut names = arguments.split_whitespace();
+    let command = names
+        .next()
+        .map(|name| find(name).ok_or_else(|| CommandError::Unknown(name.to_string())))
+        .transpose()?;
+
+    spec.expect_none(names.next().unwrap_or_default())?;
+    Command::Help { command }
+}
+
+/// Parses the arguments of `undo`.
+#[throws(CommandError)]
+fn parse_undo(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::Undo
+}
+
+/// Parses the arguments of `redo`.
+#[throws(CommandError)]
+fn parse_redo(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::Redo
+}
+
+/// Parses the arguments of `grep`.
+#[throws(CommandError)]
+fn parse_grep(spec: &'static CommandSpec, arguments: &str) -> Command {
+    let pattern = match arguments.strip_prefix("-F") {
+        Some(text) if text.is_empty() || text.starts_with(char::is_whitespace) => {
+            regex::escape(text.trim())
+        }
+        Some(_) | None => arguments.to_string(),
+    };
+
+    if pattern.is_empty() {
+        throw!(spec.missing("<pattern>"));
+    }
+
+    // The pattern is validated so that an invalid regex is reported when the command is executed.
+    let _ = Regex::new(&pattern)?;
+    Command::Grep { pattern }
+}
+
+/// Parses the arguments of `buffers`.
+#[throws(CommandError)]
+fn parse_buffers(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::ListDocs
+}
+
+/// Parses the arguments of `buffer`.
+#[throws(CommandError)]
+fn parse_buffer(spec: &'static CommandSpec, arguments: &str) -> Command {
+    Command::SwitchDoc {
+        number: spec
+            .parse_number(arguments)?
+            .ok_or_else(|| spec.missing("<number>"))?,
+    }
+}
+
+/// Parses the arguments of `close`.
+#[throws(CommandError)]
+fn parse_close(spec: &'static CommandSpec, arguments: &str) -> Command {
+    Command::CloseDoc {
+        number: spec.parse_number(arguments)?,
+    }
+}
+
+/// Parses the arguments of `substitute`.
+///
+/// The first char of the expression delimits the pattern and the replacement; it is escaped by a preceding `\`.
+#[throws(CommandError)]
+fn parse_substitute(spec: &'static CommandSpec, arguments: &str) -> Command {
+    let (scope, expression) = match arguments
+        .split_once(char::is_whitespace)
+        .map_or((arguments, ""), |(flag, expression)| {
+            (flag, expression.trim_start())
+        }) {
+        ("-s", expression) => (SubstituteScope::Selection, expression),
+        ("-p", expression) => (SubstituteScope::Project, expression),
+        _ => (SubstituteScope::Document, arguments),
+    };
+    let mut chars = expression.chars();
+    let delimiter = chars.next().ok_or_else(|| spec.missing("/<pattern>/"))?;
+    let mut parts = vec![String::new()];
+    let mut is_escaped = false;
+
+    for ch in chars {
+        match (is_escaped, ch == delimiter, parts.last_mut()) {
+            (false, true, _) => parts.push(String::new()),
+            (false, false, _) if ch == '\\' => is_escaped = true,
+            (true, is_delimiter, Some(part)) => {
+                // Only an escaped delimiter loses its `\`.
+                if !is_delimiter {
+                    part.push('\\');
+                }
+
+                part.push(ch);
+                is_escaped = false;
+            }
+            (false, false, Some(part)) => part.push(ch),
+            (_, _, None) => {}
+        }
+    }
+
+    let mut parts = parts.into_iter();
+    let pattern = parts.next().unwrap_or_default();
+    let replacement = parts.next().ok_or_else(|| spec.missing("<replacement>"))?;
+
+    if pattern.is_empty() {
+        throw!(spec.missing("<pattern>"));
+    }
+
+    spec.expect_none(&parts.collect::<String>())?;
+    // The pattern is validated so that an invalid regex is reported when the command is executed.
+    let _ = Regex::new(&pattern)?;
+    Command::Substitute {
+        pattern,
+        replacement,
+        scope,
+    }
+}
+
+/// Parses the arguments of `wrap`.
+#[throws(CommandError)]
+fn parse_wrap(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::ToggleWrap
+}
+
+/// Parses the arguments of `bookmark`.
+#[throws(CommandError)]
+fn parse_bookmark(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::ToggleBookmark
+}
+
+/// Parses the arguments of `center`.
+#[throws(CommandError)]
+fn parse_center(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::AlignView(ViewAlignment::Center)
+}
+
+/// Parses the arguments of `top`.
+#[throws(CommandError)]
+fn parse_top(spec: &'static CommandSpec, arguments: &str) -> Command {
+    spec.expect_none(arguments)?;
+    Command::AlignView(ViewAlignment::Top)
+}
+
+/// Testing of the `command` module.
+#[cfg(test)]
+mod test {
+    use {super::*, crate::app::settings::LineNumbers};
+
+    /// Commands shall be found by their name or any of their aliases.
+    #[test]
+    fn aliases() {
+        assert_eq!(
+            parse("e src/lib.rs"),
+            Ok(Command::Open {
+                path: "src/lib.rs".to_string()
+            })
+        );
+        assert_eq!(parse("q"), Ok(Command::Quit));
+        assert_eq!(parse("bd"), Ok(Command::CloseDoc { number: None }));
+        assert_eq!(parse("b 2"), Ok(Command::SwitchDoc { number: 2 }));
+        assert_eq!(
+            parse("g -F a.b"),
+            Ok(Command::Grep {
+                pattern: "a\\.b".to_string()
+            })
+        );
+        assert_eq!(
+            parse(" set scroll=3 "),
+            Ok(Command::Set(Setting::Scroll(3)))
+        );
+        assert_eq!(parse("mark"), Ok(Command::ToggleBookmark));
+        assert_eq!(
+            parse("center"),
+            Ok(Command::AlignView(ViewAlignment::Center))
+        );
+        assert!(parse("zz").is_err());
+    }
+
+    /// Options with named values shall only accept those names.
+    #[test]
+    fn named_values() {
+        assert_eq!(
+            parse("set number relative"),
+            Ok(Command::Set(Setting::Number(LineNumbers::Relative)))
+        );
+        assert!(matches!(
+            parse("set number=all"),
+            Err(CommandError::Setting(SettingError::InvalidValue { .. }))
+        ));
+        assert!(matches!(
+            parse("set scrollkey down page"),
+            Err(CommandError::Setting(SettingError::InvalidValue { .. }))
+        ));
+    }
+
+    /// The expression of `substitute` shall be split at each unescaped delimiter.
+    #[test]
+    fn substitute() {
+        assert_eq!(
+            parse("s /(a+)/<$1>/"),
+            Ok(Command::Substitute {
+                pattern: "(a+)".to_string(),
+                replacement: "<$1>".to_string(),
+                scope: SubstituteScope::Document,
+            })
+        );
+        assert_eq!(
+            parse("substitute -p #a/b\\#\\d#c"),
+            Ok(Command::Substitute {
+                pattern: "a/b#\\d".to_string(),
+                replacement: "c".to_string(),
+                scope: SubstituteScope::Project,
+            })
+        );
+        assert_eq!(
+            parse("s -s /a"),
+            Err(CommandError::MissingArgument {
+                command: "substitute",
+                argument: "<replacement>",
+                usage: "substitute [-s|-p] /<pattern>/<replacement>/",
+            })
+        );
+        assert_eq!(
+            parse("s /a/b/c"),
+            Err(CommandError::TooManyArguments {
+                command: "substitute",
+                usage: "substitute [-s|-p] /<pattern>/<replacement>/",
+            })
+        );
+    }
+
+    /// Unknown commands and invalid arguments shall be errors.
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("frob"),
+            Err(CommandError::Unknown("frob".to_string()))
+        );
+        assert!(matches!(parse("grep ("), Err(CommandError::Pattern(_))));
+        assert_eq!(
+            parse("open"),
+            Err(CommandError::MissingArgument {
+                command: "open",
+                argument: "<path>",
+                usage: "open <path>",
+            })
+        );
+        assert_eq!(
+            parse("b two"),
+            Err(CommandError::InvalidArgument {
+                command: "buffer",
+                argument: "two".to_string(),
+                usage: "buffer <number>",
+            })
+        );
+        assert_eq!(
+            parse("write now"),
+            Err(CommandError::TooManyArguments {
+                command: "write",
+                usage: "write",
+            })
+        );
+        assert_eq!(
+            parse("set wrap=on"),
+            Err(CommandError::Setting(SettingError::Unknown(
+                "wrap".to_string()
+            )))
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Implements the history of executed commands.
+
+/// The commands that have been executed, from oldest to newest.
+///
+/// While recalling, the entries are walked from the newest; the text that was being edited before recalling started is kept so that it can be restored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct CommandHistory {
+    /// The commands.
+    entries: Vec<String>,
+    /// The index of the entry that is currently recalled.
+    recalled: Option<usize>,
+    /// The text that was being edited when recalling started.
+    draft: String,
+}
+
+impl CommandHistory {
+    /// Creates a new [`CommandHistory`] holding `entries`, which are ordered from oldest to newest.
+    pub(crate) const fn new(entries: Vec<String>) -> Self {
+        Self {
+            entries,
+            recalled: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Adds `command` as the newest entry and stops recalling, returning if `command` was added.
+    ///
+    /// Empty commands and repeats of the newest entry are not added.
+    pub(crate) fn push(&mut self, command: String) -> bool {
+        self.reset();
+
+        let is_added = !command.is_empty() && self.entries.last() != Some(&command);
+
+        if is_added {
+            self.entries.push(command);
+        }
+
+        is_added
+    }
+
+    /// Stops recalling.
+    pub(crate) fn reset(&mut self) {
+        self.recalled = None;
+        self.draft.clear();
+    }
+
+    /// Recalls the entry before the currently recalled entry.
+    ///
+    /// `current` is the text being edited, which is restored once recalling moves past the newest entry.
+    pub(crate) fn previous(&mut self, current: &str) -> Option<&str> {
+        self.search_backward(current, "")
+    }
+
+    /// Recalls the entry after the currently recalled entry.
+    ///
+    /// Once the newest entry is passed, recalling stops and the text that was being edited is returned.
+    pub(crate) fn next(&mut self) -> Option<String> {
+        let index = self.recalled?.saturating_add(1);
+
+        if let Some(entry) = self.entries.get(index) {
+            self.recalled = Some(index);
+            Some(entry.clone())
+        } else {
+            let draft = self.draft.clone();
+
+            self.reset();
+            Some(draft)
+        }
+    }
+
+    /// Recalls the newest entry before the currently recalled entry that starts with `prefix`.
+    ///
+    /// `current` is the text being edited, which is restored once recalling moves past the newest entry.
+    pub(crate) fn search_backward(&mut self, current: &str, prefix: &str) -> Option<&str> {
+        let end = self.recalled.unwrap_or(self.entries.len());
+        let index = self
+            .entries
+            .get(..end)?
+            .iter()
+            .rposition(|entry| entry.starts_with(prefix))?;
+
+        if self.recalled.is_none() {
+            self.draft = current.to_string();
+        }
+
+        self.recalled = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+}
+
+/// Testing of the `command_history` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn history() -> CommandHistory {
+        CommandHistory::new(vec![
+            "open a.rs".to_string(),
+            "write".to_string(),
+            "open b.rs".to_string(),
+        ])
+    }
+
+    /// Recalling shall walk entries from the newest and restore the edited text after the newest.
+    #[test]
+    fn recall() {
+        let mut history = history();
+
+        assert_eq!(history.previous("op"), Some("open b.rs"));
+        assert_eq!(history.previous("open b.rs"), Some("write"));
+        assert_eq!(history.next(), Some("open b.rs".to_string()));
+        assert_eq!(history.next(), Some("op".to_string()));
+        assert_eq!(history.next(), None);
+    }
+
+    /// Searching shall only recall entries that start with the prefix.
+    #[test]
+    fn search() {
+        let mut history = history();
+
+        assert_eq!(history.search_backward("open", "open"), Some("open b.rs"));
+        assert_eq!(
+            history.search_backward("open b.rs", "open"),
+            Some("open a.rs")
+        );
+        assert_eq!(history.search_backward("open a.rs", "open"), None);
+    }
+}
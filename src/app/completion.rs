@@ -0,0 +1,91 @@
+//! Implements the completion of the command being edited.
+use super::command::{self, COMMANDS};
+
+/// Describes the word before the cursor that is completed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Target<'text> {
+    /// The start of a command name.
+    Command(&'text str),
+    /// The start of a path, relative to the root directory.
+    Path(&'text str),
+}
+
+impl<'text> Target<'text> {
+    /// Returns the [`Target`] of `text`, the text of the command before the cursor.
+    ///
+    /// Paths are only completed for the argument of `open`.
+    pub(crate) fn of(text: &'text str) -> Option<Self> {
+        let text = text.trim_start();
+
+        match text.split_once(char::is_whitespace) {
+            None => Some(Self::Command(text)),
+            Some((name, argument)) => {
+                match (
+                    command::find(name).map(|spec| spec.name),
+                    argument.trim_start(),
+                ) {
+                    (Some("open"), path) if !path.contains(char::is_whitespace) => {
+                        Some(Self::Path(path))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Returns the names of the commands that start with `prefix`.
+pub(crate) fn command_names(prefix: &str) -> Vec<String> {
+    COMMANDS
+        .iter()
+        .filter(|spec| spec.name.starts_with(prefix))
+        .map(|spec| spec.name.to_string())
+        .collect()
+}
+
+/// Returns the longest text that starts every one of `candidates`.
+pub(crate) fn common_prefix(candidates: &[String]) -> String {
+    let mut iter = candidates.iter();
+    let mut prefix = iter.next().cloned().unwrap_or_default();
+
+    for candidate in iter {
+        let len: usize = prefix
+            .chars()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(ch, _)| ch.len_utf8())
+            .sum();
+
+        prefix.truncate(len);
+    }
+
+    prefix
+}
+
+/// Testing of the `completion` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Command names shall be completed before the first whitespace and paths only for the argument of `open`.
+    #[test]
+    fn target() {
+        assert_eq!(Target::of("wr"), Some(Target::Command("wr")));
+        assert_eq!(Target::of("e src/a"), Some(Target::Path("src/a")));
+        assert_eq!(Target::of("open  "), Some(Target::Path("")));
+        assert_eq!(Target::of("set sc"), None);
+        assert_eq!(Target::of("open a b"), None);
+    }
+
+    /// Candidates shall be limited to those that start with the prefix.
+    #[test]
+    fn candidates() {
+        assert_eq!(command_names("re"), vec!["redo".to_string()]);
+        assert_eq!(command_names("").len(), COMMANDS.len());
+        assert_eq!(
+            common_prefix(&["src/app.rs".to_string(), "src/app/".to_string()]),
+            "src/app"
+        );
+        assert_eq!(common_prefix(&[]), "");
+    }
+}
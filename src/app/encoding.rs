@@ -0,0 +1,153 @@
+//! Implements the conversion of [`Position`]s between chars and the code units of the position encoding used by the language server.
+use {
+    super::line_length,
+    core::{convert::TryFrom, str::FromStr},
+    fehler::{throw, throws},
+    lsp_types::Position,
+    parse_display::Display as ParseDisplay,
+    ropey::Rope,
+    thiserror::Error as ThisError,
+};
+
+/// The unit counted by the character offset of a [`Position`] exchanged with the language server.
+#[derive(Clone, Copy, Debug, Eq, ParseDisplay, PartialEq)]
+pub(crate) enum PositionEncoding {
+    /// UTF-8 code units, which are bytes.
+    #[display("utf-8")]
+    Utf8,
+    /// UTF-16 code units, which the Language Server Protocol uses unless another encoding is negotiated.
+    #[display("utf-16")]
+    Utf16,
+    /// UTF-32 code units, which are chars.
+    #[display("utf-32")]
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Returns the number of code units that encode `ch`.
+    fn char_len(self, ch: char) -> u32 {
+        match self {
+            // A char is encoded by at most 4 code units.
+            Self::Utf8 => u32::try_from(ch.len_utf8()).unwrap_or(u32::MAX),
+            Self::Utf16 => u32::try_from(ch.len_utf16()).unwrap_or(u32::MAX),
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// Returns the number of code units that encode `chars`.
+    fn len(self, chars: impl Iterator<Item = char>) -> u32 {
+        chars.fold(0, |len, ch| len.saturating_add(self.char_len(ch)))
+    }
+
+    /// Converts `position`, which counts the chars of its line within `text`, into a [`Position`] that counts code units.
+    ///
+    /// A `position` beyond the end of its line is treated as the end of the line.
+    pub(crate) fn encode(self, text: &Rope, position: Position) -> Position {
+        let character = usize::try_from(position.line)
+            .ok()
+            .and_then(|line| text.get_line(line))
+            .map_or(position.character, |line| {
+                self.len(
+                    line.chars()
+                        .take(line_length(line))
+                        .take(usize::try_from(position.character).unwrap_or(usize::MAX)),
+                )
+            });
+
+        Position::new(position.line, character)
+    }
+
+    /// Returns the [`Position`], counted in code units, of the end of `text` when it starts at `start`.
+    pub(crate) fn advance(self, start: Position, text: &str) -> Position {
+        let text = Rope::from_str(text);
+        let last = text.len_lines().saturating_sub(1);
+        let last_len = text.get_line(last).map_or(0, |line| self.len(line.chars()));
+
+        if last == 0 {
+            Position::new(start.line, start.character.saturating_add(last_len))
+        } else {
+            Position::new(
+                start
+                    .line
+                    .saturating_add(u32::try_from(last).unwrap_or(u32::MAX)),
+                last_len,
+            )
+        }
+    }
+}
+
+impl Default for PositionEncoding {
+    #[inline]
+    fn default() -> Self {
+        Self::Utf16
+    }
+}
+
+impl FromStr for PositionEncoding {
+    type Err = UnknownEncodingError;
+
+    #[inline]
+    #[throws(Self::Err)]
+    fn from_str(s: &str) -> Self {
+        match s {
+            "utf-8" => Self::Utf8,
+            "utf-16" => Self::Utf16,
+            "utf-32" => Self::Utf32,
+            _ => throw!(UnknownEncodingError(s.to_string())),
+        }
+    }
+}
+
+/// The name of a position encoding is not known.
+#[derive(Debug, ThisError)]
+#[error("expected `utf-8`, `utf-16` or `utf-32`, found `{0}`")]
+pub(crate) struct UnknownEncodingError(String);
+
+/// Testing of the `encoding` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Positions shall count the code units of the chars before them, up to the end of the line.
+    #[test]
+    fn encode() {
+        let text = Rope::from("a\u{1f600}b\u{e4}\nc");
+        let position = Position::new(0, 3);
+
+        assert_eq!(
+            PositionEncoding::Utf8.encode(&text, position),
+            Position::new(0, 6)
+        );
+        assert_eq!(
+            PositionEncoding::Utf16.encode(&text, position),
+            Position::new(0, 4)
+        );
+        assert_eq!(
+            PositionEncoding::Utf32.encode(&text, position),
+            Position::new(0, 3)
+        );
+        assert_eq!(
+            PositionEncoding::Utf16.encode(&text, Position::new(0, u32::MAX)),
+            Position::new(0, 5)
+        );
+    }
+
+    /// The end of a text shall follow its line breaks.
+    #[test]
+    fn advance() {
+        let start = Position::new(1, 4);
+
+        assert_eq!(
+            PositionEncoding::Utf8.advance(start, "\u{e4}b"),
+            Position::new(1, 7)
+        );
+        assert_eq!(
+            PositionEncoding::Utf16.advance(start, "a\r\n\u{1f600}"),
+            Position::new(2, 2)
+        );
+        assert_eq!(
+            "utf-32".parse::<PositionEncoding>().ok(),
+            Some(PositionEncoding::Utf32)
+        );
+    }
+}
@@ -0,0 +1,440 @@
+//! Implements the history of changes made to the text of a [`Document`].
+//!
+//! [`Document`]: ../struct.Document.html
+use {
+    super::encoding::PositionEncoding,
+    core::mem,
+    lsp_types::{Position, Range, TextDocumentContentChangeEvent},
+    ropey::Rope,
+    serde::{Deserialize, Serialize},
+};
+
+/// The maximum number of [`Revision`]s kept in a [`History`], including the root.
+const MAX_REVISIONS: usize = 1000;
+
+/// A replacement of text within a [`Document`].
+///
+/// [`Document`]: ../struct.Document.html
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct Change {
+    /// The [`Range`] of the replaced text before the change.
+    range: Range,
+    /// The [`Position`] of the end of `new_text` after the change.
+    new_end: Position,
+    /// The text that was replaced.
+    old_text: String,
+    /// The text that replaced `old_text`.
+    new_text: String,
+}
+
+impl Change {
+    /// Creates a new [`Change`].
+    pub(crate) const fn new(
+        range: Range,
+        new_end: Position,
+        old_text: String,
+        new_text: String,
+    ) -> Self {
+        Self {
+            range,
+            new_end,
+            old_text,
+            new_text,
+        }
+    }
+
+    /// Returns the [`Position`] of the end of the new text.
+    pub(crate) const fn new_end(&self) -> Position {
+        self.new_end
+    }
+
+    /// Returns the [`Replacement`] that reverts `self`.
+    fn revert(&self) -> Replacement {
+        Replacement {
+            range: Range::new(self.range.start, self.new_end),
+            text: self.old_text.clone(),
+        }
+    }
+
+    /// Returns the [`Replacement`] that reapplies `self`.
+    fn reapply(&self) -> Replacement {
+        Replacement {
+            range: self.range,
+            text: self.new_text.clone(),
+        }
+    }
+
+    /// Converts `self` into the [`TextDocumentContentChangeEvent`] that describes it to the language server.
+    ///
+    /// `text` is the text right after `self` was made; the positions of the event count the code units of `encoding`.
+    pub(crate) fn into_event(
+        self,
+        text: &Rope,
+        encoding: PositionEncoding,
+    ) -> TextDocumentContentChangeEvent {
+        // The text before the start of the range is not changed.
+        let start = encoding.encode(text, self.range.start);
+
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(start, encoding.advance(start, &self.old_text))),
+            range_length: None,
+            text: self.new_text,
+        }
+    }
+}
+
+/// Replaces the text within a [`Range`].
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Replacement {
+    /// The [`Range`] of the text to be replaced.
+    pub(crate) range: Range,
+    /// The text to be inserted.
+    pub(crate) text: String,
+}
+
+/// A group of [`Change`]s that is undone and redone as a single step.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct Revision {
+    /// The index of the [`Revision`] that precedes `Self`.
+    parent: usize,
+    /// The index of the most recent child of `Self`, which is the [`Revision`] restored by a redo.
+    last_child: Option<usize>,
+    /// The [`Change`]s of `Self`, in the order they were made.
+    changes: Vec<Change>,
+    /// The selection before `changes` were made.
+    selection_before: Vec<usize>,
+    /// The selection after `changes` were made.
+    selection_after: Vec<usize>,
+}
+
+impl Revision {
+    /// Creates a new [`Revision`] that follows `parent`.
+    const fn new(parent: usize, selection_before: Vec<usize>) -> Self {
+        Self {
+            parent,
+            last_child: None,
+            changes: Vec::new(),
+            selection_after: Vec::new(),
+            selection_before,
+        }
+    }
+}
+
+/// The undo tree of a [`Document`].
+///
+/// Undoing a [`Revision`] and then making a new change starts a new branch; the undone [`Revision`] remains in the tree. Once the tree holds more than [`MAX_REVISIONS`], the oldest [`Revision`]s are discarded.
+///
+/// [`Document`]: ../struct.Document.html
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct History {
+    /// All [`Revision`]s; the first is the root, which has no changes.
+    revisions: Vec<Revision>,
+    /// The index of the [`Revision`] that matches the current text.
+    current: usize,
+    /// The index of the [`Revision`] that matches the text last written to the file.
+    ///
+    /// If `None`, that [`Revision`] has been discarded.
+    saved: Option<usize>,
+    /// The [`Revision`] that is currently being grouped.
+    #[serde(skip)]
+    group: Option<Revision>,
+}
+
+impl History {
+    /// Creates a new [`History`] with no changes.
+    pub(crate) fn new() -> Self {
+        Self {
+            revisions: vec![Revision::new(0, Vec::new())],
+            current: 0,
+            saved: Some(0),
+            group: None,
+        }
+    }
+
+    /// Records that the current [`Revision`] matches the text written to the file.
+    pub(crate) fn mark_saved(&mut self) {
+        self.saved = Some(self.current);
+    }
+
+    /// Makes the [`Revision`] that matches the text last written to the file current, as it is when the file is read again.
+    ///
+    /// Returns `None` if that [`Revision`] has been discarded.
+    pub(crate) fn into_saved(mut self) -> Option<Self> {
+        self.current = self.saved?;
+        Some(self)
+    }
+
+    /// Starts grouping all recorded changes into a single [`Revision`].
+    pub(crate) fn start_group(&mut self, selection: Vec<usize>) {
+        self.end_group(selection.clone());
+        self.group = Some(Revision::new(self.current, selection));
+    }
+
+    /// Stops grouping changes, adding the group to `self` if it has any changes.
+    pub(crate) fn end_group(&mut self, selection: Vec<usize>) {
+        if let Some(mut group) = self.group.take() {
+            if !group.changes.is_empty() {
+                group.selection_after = selection;
+                self.push(group);
+            }
+        }
+    }
+
+    /// Records `change`.
+    ///
+    /// If no group has been started, `change` is added as its own [`Revision`].
+    pub(crate) fn record(&mut self, change: Change, selection: &[usize]) {
+        if let Some(group) = self.group.as_mut() {
+            group.changes.push(change);
+        } else {
+            self.commit(vec![change], selection.to_vec(), selection.to_vec());
+        }
+    }
+
+    /// Adds `changes` as a single [`Revision`].
+    pub(crate) fn commit(
+        &mut self,
+        changes: Vec<Change>,
+        selection_before: Vec<usize>,
+        selection_after: Vec<usize>,
+    ) {
+        if !changes.is_empty() {
+            let mut revision = Revision::new(self.current, selection_before);
+
+            revision.changes = changes;
+            revision.selection_after = selection_after;
+            self.push(revision);
+        }
+    }
+
+    /// Adds `revision` as a child of the current [`Revision`] and makes it current.
+    fn push(&mut self, revision: Revision) {
+        let index = self.revisions.len();
+
+        if let Some(parent) = self.revisions.get_mut(self.current) {
+            parent.last_child = Some(index);
+        }
+
+        self.revisions.push(revision);
+        self.current = index;
+
+        while self.revisions.len() > MAX_REVISIONS && self.advance_root() {}
+    }
+
+    /// Makes the child of the root that leads to the current [`Revision`] the new root, returning if the root was moved.
+    ///
+    /// The changes of the new root can no longer be undone, and the branches that do not lead through it are discarded.
+    fn advance_root(&mut self) -> bool {
+        let mut new_root = self.current;
+
+        while let Some(parent) = self
+            .revisions
+            .get(new_root)
+            .map(|revision| revision.parent)
+            .filter(|&parent| parent != 0)
+        {
+            new_root = parent;
+        }
+
+        if new_root == 0 {
+            return false;
+        }
+
+        let mut new_indexes = Vec::new();
+        let mut revisions = Vec::new();
+
+        // A parent always precedes its children, so the new index of each parent is known before its children are reached.
+        for (index, revision) in mem::take(&mut self.revisions).into_iter().enumerate() {
+            let is_kept = index == new_root
+                || (index > new_root
+                    && new_indexes
+                        .get(revision.parent)
+                        .copied()
+                        .flatten()
+                        .is_some());
+
+            new_indexes.push(is_kept.then_some(revisions.len()));
+
+            if is_kept {
+                revisions.push(revision);
+            }
+        }
+
+        let new_index = |index: usize| new_indexes.get(index).copied().flatten();
+
+        for revision in &mut revisions {
+            revision.parent = new_index(revision.parent).unwrap_or(0);
+            revision.last_child = revision.last_child.and_then(new_index);
+        }
+
+        if let Some(root) = revisions.first_mut() {
+            root.changes.clear();
+        }
+
+        self.current = new_index(self.current).unwrap_or(0);
+        self.saved = self.saved.and_then(new_index);
+        self.revisions = revisions;
+        true
+    }
+
+    /// Moves to the parent of the current [`Revision`].
+    ///
+    /// Returns the [`Replacement`]s that revert the current [`Revision`], in the order they must be applied, along with the selection to be restored.
+    pub(crate) fn undo(&mut self) -> Option<(Vec<Replacement>, Vec<usize>)> {
+        let revision = self
+            .revisions
+            .get(self.current)
+            .filter(|_| self.current != 0)?;
+        let replacements = revision.changes.iter().rev().map(Change::revert).collect();
+        let selection = revision.selection_before.clone();
+
+        self.current = revision.parent;
+        Some((replacements, selection))
+    }
+
+    /// Moves to the most recent child of the current [`Revision`].
+    ///
+    /// Returns the [`Replacement`]s that reapply the child, in the order they must be applied, along with the selection to be restored.
+    pub(crate) fn redo(&mut self) -> Option<(Vec<Replacement>, Vec<usize>)> {
+        let child = self
+            .revisions
+            .get(self.current)
+            .and_then(|revision| revision.last_child)?;
+        let revision = self.revisions.get(child)?;
+        let replacements = revision.changes.iter().map(Change::reapply).collect();
+        let selection = revision.selection_after.clone();
+
+        self.current = child;
+        Some((replacements, selection))
+    }
+}
+
+impl Default for History {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Testing of the history module.
+#[cfg(test)]
+mod test {
+    use {super::*, core::iter};
+
+    fn change(character: u32, text: &str) -> Change {
+        let start = Position::new(0, character);
+
+        Change::new(
+            Range::new(start, start),
+            Position::new(0, character.saturating_add(1)),
+            String::new(),
+            text.to_string(),
+        )
+    }
+
+    /// Changes recorded within a group shall be undone as a single step, in reverse order.
+    #[test]
+    fn undo_group() {
+        let mut history = History::new();
+
+        history.start_group(vec![1]);
+        history.record(change(0, "a"), &[]);
+        history.record(change(1, "b"), &[]);
+        history.end_group(vec![2]);
+
+        assert_eq!(
+            history.undo(),
+            Some((
+                vec![
+                    Replacement {
+                        range: Range::new(Position::new(0, 1), Position::new(0, 2)),
+                        text: String::new(),
+                    },
+                    Replacement {
+                        range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+                        text: String::new(),
+                    },
+                ],
+                vec![1]
+            ))
+        );
+        assert_eq!(history.undo(), None);
+    }
+
+    /// A redo shall reapply the most recently undone branch.
+    #[test]
+    fn redo_latest_branch() {
+        let mut history = History::new();
+
+        history.record(change(0, "a"), &[]);
+        let _ = history.undo();
+        history.record(change(0, "b"), &[]);
+        let _ = history.undo();
+
+        assert_eq!(
+            history.redo(),
+            Some((
+                vec![Replacement {
+                    range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+                    text: "b".to_string(),
+                }],
+                Vec::new()
+            ))
+        );
+        assert_eq!(history.redo(), None);
+    }
+
+    /// A restored history shall continue from the revision that was written, which is discarded along with its branch.
+    #[test]
+    fn restore_saved() {
+        let mut history = History::new();
+
+        history.record(change(0, "a"), &[]);
+        history.mark_saved();
+        let _ = history.undo();
+
+        let mut restored = history.clone().into_saved();
+
+        assert_eq!(
+            restored
+                .as_mut()
+                .and_then(History::undo)
+                .map(|(replacements, _)| replacements),
+            Some(vec![Replacement {
+                range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+                text: String::new(),
+            }])
+        );
+
+        history.record(change(0, "b"), &[]);
+
+        for _ in 0..MAX_REVISIONS {
+            history.record(change(0, "c"), &[]);
+        }
+
+        assert_eq!(history.into_saved(), None);
+    }
+
+    /// Only the most recent revisions shall be kept, along with the branches that lead through them.
+    #[test]
+    fn max_revisions() {
+        let mut history = History::new();
+
+        history.record(change(0, "a"), &[]);
+        let _ = history.undo();
+
+        for _ in 0..MAX_REVISIONS {
+            history.record(change(0, "b"), &[]);
+        }
+
+        assert_eq!(history.revisions.len(), MAX_REVISIONS);
+        assert!(history
+            .revisions
+            .iter()
+            .all(|revision| revision.changes.iter().all(|change| change.new_text == "b")));
+        assert_eq!(
+            iter::from_fn(|| history.undo()).count(),
+            MAX_REVISIONS.saturating_sub(1)
+        );
+    }
+}
@@ -0,0 +1,449 @@
+//! Implements the arrangement of the panes within the page.
+use {
+    super::translate::{PaneResize, SplitDirection},
+    crate::{
+        io::{Dimensions, Region, RowText, Style, StyledText, Unit},
+        orient::ScreenDirection,
+    },
+    core::{convert::TryFrom, mem, ops},
+};
+
+/// The percentage of a split that is given to the first side of a new split.
+const INITIAL_SHARE: u16 = 50;
+/// The percentage of a split by which each resize moves its border.
+const RESIZE_STEP: u16 = 5;
+/// The smallest percentage of a split that is given to either side.
+const MIN_SHARE: u16 = 10;
+
+/// An arrangement of panes within a region.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    /// The pane with the id.
+    Pane(usize),
+    /// Two arrangements that share a region, separated by a border.
+    Split {
+        /// Where `second` is placed relative to `first`.
+        direction: SplitDirection,
+        /// The percentage of the region that is given to `first`.
+        share: u16,
+        /// The first arrangement.
+        first: Box<Node>,
+        /// The second arrangement.
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    /// Returns if `self` contains the pane with `id`.
+    fn contains(&self, id: usize) -> bool {
+        match self {
+            Self::Pane(pane) => *pane == id,
+            Self::Split { first, second, .. } => first.contains(id) || second.contains(id),
+        }
+    }
+
+    /// Returns the id of the first pane of `self`.
+    fn first_pane(&self) -> usize {
+        match self {
+            Self::Pane(id) => *id,
+            Self::Split { first, .. } => first.first_pane(),
+        }
+    }
+
+    /// Adds the [`Region`]s of the panes and borders of `self` within `region` to `arrangement`.
+    fn arrange(&self, region: Region, arrangement: &mut Arrangement) {
+        match self {
+            Self::Pane(id) => arrangement.panes.push((*id, region)),
+            Self::Split {
+                direction,
+                share,
+                first,
+                second,
+            } => {
+                let (first_region, border, second_region) = divide(region, *direction, *share);
+
+                first.arrange(first_region, arrangement);
+                arrangement.borders.push((border, *direction));
+                second.arrange(second_region, arrangement);
+            }
+        }
+    }
+
+    /// Splits the pane with `id` so that the pane with `new_id` is placed towards `direction`, returning if the pane was found.
+    fn split(&mut self, id: usize, new_id: usize, direction: SplitDirection) -> bool {
+        match self {
+            Self::Pane(pane) => {
+                let is_found = *pane == id;
+
+                if is_found {
+                    *self = Self::Split {
+                        direction,
+                        share: INITIAL_SHARE,
+                        first: Box::new(Self::Pane(id)),
+                        second: Box::new(Self::Pane(new_id)),
+                    };
+                }
+
+                is_found
+            }
+            Self::Split { first, second, .. } => {
+                first.split(id, new_id, direction) || second.split(id, new_id, direction)
+            }
+        }
+    }
+
+    /// Removes the pane with `id`, giving its region to the other side of its split.
+    ///
+    /// Returns the id of the pane that takes the place of the removed pane, or `None` if the pane was not found within a split.
+    fn remove(&mut self, id: usize) -> Option<usize> {
+        match self {
+            Self::Pane(_) => None,
+            Self::Split { first, second, .. } => {
+                let remaining = if **first == Self::Pane(id) {
+                    Some(mem::replace(&mut **second, Self::Pane(id)))
+                } else if **second == Self::Pane(id) {
+                    Some(mem::replace(&mut **first, Self::Pane(id)))
+                } else {
+                    None
+                };
+
+                match remaining {
+                    Some(remaining) => {
+                        let focus = remaining.first_pane();
+
+                        *self = remaining;
+                        Some(focus)
+                    }
+                    None => first.remove(id).or_else(|| second.remove(id)),
+                }
+            }
+        }
+    }
+
+    /// Moves the border of the split nearest to the pane with `id` along `direction` so that the pane grows if `grows`, returning if such a split was found.
+    fn resize(&mut self, id: usize, direction: SplitDirection, grows: bool) -> bool {
+        match self {
+            Self::Pane(_) => false,
+            Self::Split {
+                direction: split_direction,
+                share,
+                first,
+                second,
+            } => {
+                // A split within `first` or `second` is nearer to the pane.
+                if first.resize(id, direction, grows) || second.resize(id, direction, grows) {
+                    true
+                } else if *split_direction == direction
+                    && (first.contains(id) || second.contains(id))
+                {
+                    *share = if grows == first.contains(id) {
+                        share
+                            .saturating_add(RESIZE_STEP)
+                            .min(100_u16.saturating_sub(MIN_SHARE))
+                    } else {
+                        share.saturating_sub(RESIZE_STEP).max(MIN_SHARE)
+                    };
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
+/// The [`Region`]s of the panes and borders within the page.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Arrangement {
+    /// The id and [`Region`] of each pane.
+    pub(crate) panes: Vec<(usize, Region)>,
+    /// The [`Region`] of each border and the direction of the split it separates.
+    pub(crate) borders: Vec<(Region, SplitDirection)>,
+}
+
+/// The arrangement of the panes within the page.
+#[derive(Debug)]
+pub(crate) struct Layout {
+    /// The arrangement of all panes.
+    root: Node,
+    /// The id of the next pane that is created.
+    next_id: usize,
+}
+
+impl Layout {
+    /// Splits the pane with `id`, returning the id of the new pane that is placed towards `direction`.
+    pub(crate) fn split(&mut self, id: usize, direction: SplitDirection) -> Option<usize> {
+        let new_id = self.next_id;
+
+        self.root.split(id, new_id, direction).then(|| {
+            self.next_id = self.next_id.saturating_add(1);
+            new_id
+        })
+    }
+
+    /// Removes the pane with `id`, returning the id of the pane that takes its place.
+    ///
+    /// The last pane is never removed.
+    pub(crate) fn remove(&mut self, id: usize) -> Option<usize> {
+        self.root.remove(id)
+    }
+
+    /// Resizes the pane with `id` as described by `resize`, returning if its size may have changed.
+    pub(crate) fn resize(&mut self, id: usize, resize: PaneResize) -> bool {
+        let (direction, grows) = match resize {
+            PaneResize::Taller => (SplitDirection::Below, true),
+            PaneResize::Shorter => (SplitDirection::Below, false),
+            PaneResize::Wider => (SplitDirection::Right, true),
+            PaneResize::Narrower => (SplitDirection::Right, false),
+        };
+
+        self.root.resize(id, direction, grows)
+    }
+
+    /// Returns the [`Arrangement`] of the panes within a page of `size`.
+    pub(crate) fn arrange(&self, size: Dimensions) -> Arrangement {
+        let mut arrangement = Arrangement::default();
+
+        self.root.arrange(
+            Region {
+                row: Unit::from(0),
+                column: Unit::from(0),
+                size,
+            },
+            &mut arrangement,
+        );
+        arrangement
+    }
+
+    /// Returns the id of the nearest pane towards `direction` from the pane with `id` that shares some of its rows or columns, when the panes are arranged within a page of `size`.
+    pub(crate) fn neighbor(
+        &self,
+        id: usize,
+        direction: ScreenDirection,
+        size: Dimensions,
+    ) -> Option<usize> {
+        let arrangement = self.arrange(size);
+        let from = arrangement
+            .panes
+            .iter()
+            .find(|&&(pane, _)| pane == id)
+            .map(|&(_, region)| region)?;
+
+        arrangement
+            .panes
+            .iter()
+            .filter(|&&(pane, _)| pane != id)
+            .filter_map(|&(pane, region)| {
+                // The span of each pane along the axis of `direction`.
+                let (is_aligned, target, origin) = match direction {
+                    ScreenDirection::Left | ScreenDirection::Right => (
+                        overlaps(&rows(from), &rows(region)),
+                        columns(region),
+                        columns(from),
+                    ),
+                    ScreenDirection::Up | ScreenDirection::Down => (
+                        overlaps(&columns(from), &columns(region)),
+                        rows(region),
+                        rows(from),
+                    ),
+                };
+                let distance = match direction {
+                    ScreenDirection::Left | ScreenDirection::Up => {
+                        origin.start.checked_sub(target.end)
+                    }
+                    ScreenDirection::Right | ScreenDirection::Down => {
+                        target.start.checked_sub(origin.end)
+                    }
+                };
+
+                distance
+                    .filter(|_| is_aligned)
+                    .map(|distance| (distance, pane))
+            })
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, pane)| pane)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            root: Node::Pane(0),
+            next_id: 1,
+        }
+    }
+}
+
+/// Divides `region` into the [`Region`] given `share` percent of it, a border and the [`Region`] of the rest of it towards `direction`.
+fn divide(region: Region, direction: SplitDirection, share: u16) -> (Region, Region, Region) {
+    let total = match direction {
+        SplitDirection::Below => *region.size.height,
+        SplitDirection::Right => *region.size.width,
+    };
+    // The border takes a single row or column.
+    let available = total.saturating_sub(1);
+    let first = u32::from(available)
+        .saturating_mul(u32::from(share))
+        .checked_div(100)
+        .and_then(|length| u16::try_from(length).ok())
+        .unwrap_or(available);
+    let second = available.saturating_sub(first);
+
+    match direction {
+        SplitDirection::Below => {
+            let part = |row: u16, height: u16| Region {
+                row: row.into(),
+                column: region.column,
+                size: Dimensions {
+                    height: height.into(),
+                    width: region.size.width,
+                },
+            };
+
+            (
+                part(*region.row, first),
+                part(region.row.saturating_add(first), total.min(1)),
+                part(region.row.saturating_add(first).saturating_add(1), second),
+            )
+        }
+        SplitDirection::Right => {
+            let part = |column: u16, width: u16| Region {
+                row: region.row,
+                column: column.into(),
+                size: Dimensions {
+                    height: region.size.height,
+                    width: width.into(),
+                },
+            };
+
+            (
+                part(*region.column, first),
+                part(region.column.saturating_add(first), total.min(1)),
+                part(
+                    region.column.saturating_add(first).saturating_add(1),
+                    second,
+                ),
+            )
+        }
+    }
+}
+
+/// Returns the rows of the page covered by `region`.
+fn rows(region: Region) -> ops::Range<u16> {
+    *region.row..region.row.saturating_add(*region.size.height)
+}
+
+/// Returns the columns of the page covered by `region`.
+fn columns(region: Region) -> ops::Range<u16> {
+    *region.column..region.column.saturating_add(*region.size.width)
+}
+
+/// Returns if `a` and `b` share an index.
+fn overlaps(a: &ops::Range<u16>, b: &ops::Range<u16>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+/// Returns the rows that draw the border within `region` that separates a split towards `direction`.
+pub(crate) fn border_rows(region: Region, direction: SplitDirection) -> Vec<RowText> {
+    let line = |text: String| RowText::new(vec![StyledText::new(text, Style::Default)]);
+
+    match direction {
+        SplitDirection::Below => vec![line("\u{2500}".repeat(region.size.width.into()))],
+        SplitDirection::Right => vec![line("\u{2502}".to_string()); region.size.height.into()],
+    }
+}
+
+/// Testing of the `layout` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn region(row: u16, column: u16, height: u16, width: u16) -> Region {
+        Region {
+            row: row.into(),
+            column: column.into(),
+            size: Dimensions {
+                height: height.into(),
+                width: width.into(),
+            },
+        }
+    }
+
+    fn size(height: u16, width: u16) -> Dimensions {
+        Dimensions {
+            height: height.into(),
+            width: width.into(),
+        }
+    }
+
+    /// Splits shall divide the region of the split pane, separated by a border.
+    #[test]
+    fn split() {
+        let mut layout = Layout::default();
+        let right = layout.split(0, SplitDirection::Right);
+        let below = right.and_then(|right| layout.split(right, SplitDirection::Below));
+
+        assert_eq!(right, Some(1));
+        assert_eq!(below, Some(2));
+        assert_eq!(
+            layout.arrange(size(21, 81)),
+            Arrangement {
+                panes: vec![
+                    (0, region(0, 0, 21, 40)),
+                    (1, region(0, 41, 10, 40)),
+                    (2, region(11, 41, 10, 40)),
+                ],
+                borders: vec![
+                    (region(0, 40, 21, 1), SplitDirection::Right),
+                    (region(10, 41, 1, 40), SplitDirection::Below),
+                ],
+            }
+        );
+    }
+
+    /// Focus shall move to the nearest pane that shares rows or columns with the focused pane.
+    #[test]
+    fn neighbor() {
+        let mut layout = Layout::default();
+
+        let _ = layout.split(0, SplitDirection::Right);
+        let _ = layout.split(1, SplitDirection::Below);
+
+        assert_eq!(
+            layout.neighbor(0, ScreenDirection::Right, size(21, 81)),
+            Some(1)
+        );
+        assert_eq!(
+            layout.neighbor(2, ScreenDirection::Up, size(21, 81)),
+            Some(1)
+        );
+        assert_eq!(
+            layout.neighbor(2, ScreenDirection::Left, size(21, 81)),
+            Some(0)
+        );
+        assert_eq!(layout.neighbor(0, ScreenDirection::Up, size(21, 81)), None);
+    }
+
+    /// Resizing shall move the border of the nearest split along the resized axis and removing a pane shall give its region to the other side of its split.
+    #[test]
+    fn resize_remove() {
+        let mut layout = Layout::default();
+
+        let _ = layout.split(0, SplitDirection::Right);
+        let _ = layout.split(1, SplitDirection::Below);
+
+        assert!(layout.resize(2, PaneResize::Narrower));
+        assert!(!layout.resize(0, PaneResize::Taller));
+        assert_eq!(
+            layout.arrange(size(21, 101)).panes.first(),
+            Some(&(0, region(0, 0, 21, 55)))
+        );
+        assert_eq!(layout.remove(1), Some(2));
+        assert_eq!(layout.remove(0), Some(2));
+        assert_eq!(layout.remove(2), None);
+        assert_eq!(
+            layout.arrange(size(21, 101)).panes,
+            vec![(2, region(0, 0, 21, 101))]
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Implements the editing of a single line of text, such as the command bar.
+use {super::translate::LineEdit, core::ops::Range};
+
+/// A single line of text with a cursor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct LineEditor {
+    /// The text.
+    text: String,
+    /// The char index of the cursor within `text`.
+    cursor: usize,
+}
+
+impl LineEditor {
+    /// Returns the text of `self`.
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the char index of the cursor.
+    pub(crate) const fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces the text of `self` with `text`, placing the cursor at the char index `cursor`.
+    ///
+    /// `cursor` is limited to the end of `text`.
+    pub(crate) fn set(&mut self, text: String, cursor: usize) {
+        self.text = text;
+        self.cursor = cursor.min(self.len());
+    }
+
+    /// Returns the text before the cursor.
+    pub(crate) fn text_before_cursor(&self) -> &str {
+        self.text
+            .get(..self.byte_index(self.cursor))
+            .unwrap_or_default()
+    }
+
+    /// Removes all text.
+    pub(crate) fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// Inserts `text` at the cursor, moving the cursor to the end of the inserted text.
+    ///
+    /// Line breaks are dropped as `self` is a single line.
+    pub(crate) fn insert(&mut self, text: &str) {
+        let index = self.byte_index(self.cursor);
+        let inserted: String = text
+            .chars()
+            .filter(|ch| *ch != '\n' && *ch != '\r')
+            .collect();
+
+        self.text.insert_str(index, &inserted);
+        self.cursor = self.cursor.saturating_add(inserted.chars().count());
+    }
+
+    /// Replaces the `count` chars before the cursor with `text`.
+    pub(crate) fn replace_before_cursor(&mut self, count: usize, text: &str) {
+        self.remove(self.cursor.saturating_sub(count)..self.cursor);
+        self.insert(text);
+    }
+
+    /// Performs `edit`.
+    pub(crate) fn edit(&mut self, edit: LineEdit) {
+        match edit {
+            LineEdit::MoveLeft => {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            LineEdit::MoveRight => {
+                self.cursor = self.cursor.saturating_add(1).min(self.len());
+            }
+            LineEdit::MoveWordLeft => {
+                self.cursor = self.previous_word_start();
+            }
+            LineEdit::MoveWordRight => {
+                self.cursor = self.next_word_end();
+            }
+            LineEdit::MoveStart => {
+                self.cursor = 0;
+            }
+            LineEdit::MoveEnd => {
+                self.cursor = self.len();
+            }
+            LineEdit::DeleteBackward => {
+                if let Some(start) = self.cursor.checked_sub(1) {
+                    self.remove(start..self.cursor);
+                }
+            }
+            LineEdit::DeleteForward => {
+                self.remove(self.cursor..self.cursor.saturating_add(1).min(self.len()));
+            }
+            LineEdit::DeleteWordBackward => {
+                self.remove(self.previous_word_start()..self.cursor);
+            }
+            LineEdit::DeleteToStart => {
+                self.remove(0..self.cursor);
+            }
+            LineEdit::DeleteToEnd => {
+                self.remove(self.cursor..self.len());
+            }
+        }
+    }
+
+    /// Returns the number of chars in `self`.
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Returns the byte index of the char at `char_index`.
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map_or(self.text.len(), |(index, _)| index)
+    }
+
+    /// Removes the chars within `range`, moving the cursor to the start of `range`.
+    fn remove(&mut self, range: Range<usize>) {
+        let start = self.byte_index(range.start);
+        let end = self.byte_index(range.end);
+
+        self.text.replace_range(start..end, "");
+        self.cursor = range.start;
+    }
+
+    /// Returns the char index of the start of the word before the cursor.
+    ///
+    /// Words are separated by whitespace.
+    fn previous_word_start(&self) -> usize {
+        let before: Vec<char> = self.text.chars().take(self.cursor).collect();
+        let word_end = before
+            .iter()
+            .rposition(|ch| !ch.is_whitespace())
+            .map_or(0, |index| index.saturating_add(1));
+
+        before
+            .iter()
+            .take(word_end)
+            .rposition(|ch| ch.is_whitespace())
+            .map_or(0, |index| index.saturating_add(1))
+    }
+
+    /// Returns the char index of the end of the word after the cursor.
+    ///
+    /// Words are separated by whitespace.
+    fn next_word_end(&self) -> usize {
+        let mut after = self.text.chars().enumerate().skip(self.cursor);
+
+        after
+            .find(|&(_, ch)| !ch.is_whitespace())
+            .and_then(|_| after.find(|&(_, ch)| ch.is_whitespace()))
+            .map_or(self.len(), |(index, _)| index)
+    }
+}
+
+/// Testing of the `line_editor` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        editor.insert(text);
+        editor
+    }
+
+    /// Text shall be inserted at the cursor.
+    #[test]
+    fn insert_at_cursor() {
+        let mut editor = line("open b");
+
+        editor.edit(LineEdit::MoveLeft);
+        editor.insert("a/");
+
+        assert_eq!(editor.text(), "open a/b");
+        assert_eq!(editor.cursor(), 7);
+    }
+
+    /// Word-wise edits shall treat whitespace as the separator between words.
+    #[test]
+    fn word_edits() {
+        let mut editor = line("open src/app.rs  ");
+
+        editor.edit(LineEdit::MoveWordLeft);
+        assert_eq!(editor.cursor(), 5);
+
+        editor.edit(LineEdit::MoveStart);
+        editor.edit(LineEdit::MoveWordRight);
+        assert_eq!(editor.cursor(), 4);
+
+        editor.edit(LineEdit::MoveEnd);
+        editor.edit(LineEdit::DeleteWordBackward);
+        assert_eq!(editor.text(), "open ");
+    }
+
+    /// Deleting shall remove chars around the cursor, including multi-byte chars.
+    #[test]
+    fn delete() {
+        let mut editor = line("a\u{e9}\u{20ac}b");
+
+        editor.edit(LineEdit::MoveLeft);
+        editor.edit(LineEdit::DeleteBackward);
+        assert_eq!(editor.text(), "a\u{e9}b");
+
+        editor.edit(LineEdit::MoveStart);
+        editor.edit(LineEdit::DeleteForward);
+        assert_eq!(editor.text(), "\u{e9}b");
+
+        editor.edit(LineEdit::DeleteToEnd);
+        assert_eq!(editor.text(), "");
+    }
+}
@@ -0,0 +1,376 @@
+//! Implements the picking of a file from the paths of all files within the root directory.
+use {
+    super::{
+        line_editor::LineEditor,
+        translate::{LineEdit, ListMovement},
+    },
+    crate::io::{RowText, Style, StyledText},
+    core::{cmp::Ordering, convert::TryFrom, mem},
+};
+
+/// The score of each matched char.
+const MATCH_SCORE: i64 = 16;
+/// The additional score of a matched char that follows the previously matched char.
+const CONSECUTIVE_BONUS: i64 = 16;
+/// The additional score of a matched char that starts a path component or word.
+const BOUNDARY_BONUS: i64 = 24;
+
+/// A path that matches the query.
+#[derive(Clone, Debug, PartialEq)]
+struct Match {
+    /// The index of the path.
+    index: usize,
+    /// How well the path matches; higher is better.
+    score: i64,
+    /// The char indexes of the path that match the chars of the query.
+    positions: Vec<usize>,
+}
+
+/// Returns how well `path` matches `query`, or `None` if `path` does not contain the chars of `query` in order.
+///
+/// Matching ignores case unless `query` contains an uppercase char. Matched chars score more when they are consecutive or start a path component or word, and less when separated by unmatched chars. Every occurrence of the first char of `query` is tried as the start of the match.
+fn fuzzy_match(query: &str, path: &str) -> Option<(i64, Vec<usize>)> {
+    let is_case_sensitive = query.chars().any(char::is_uppercase);
+    let normalize = |ch: char| {
+        if is_case_sensitive {
+            ch
+        } else {
+            ch.to_ascii_lowercase()
+        }
+    };
+    let query: Vec<char> = query.chars().map(normalize).collect();
+    let path: Vec<char> = path.chars().map(normalize).collect();
+    let mut best: Option<(i64, Vec<usize>)> = None;
+
+    let first = match query.first() {
+        Some(first) => *first,
+        None => return Some((0, Vec::new())),
+    };
+
+    for start in path
+        .iter()
+        .enumerate()
+        .filter(|&(_, ch)| *ch == first)
+        .map(|(index, _)| index)
+    {
+        let mut positions = vec![start];
+        let mut search_start = start.saturating_add(1);
+
+        for ch in query.iter().skip(1) {
+            match path
+                .iter()
+                .skip(search_start)
+                .position(|path_ch| path_ch == ch)
+            {
+                Some(offset) => {
+                    let index = search_start.saturating_add(offset);
+
+                    positions.push(index);
+                    search_start = index.saturating_add(1);
+                }
+                None => return best,
+            }
+        }
+
+        let score = score(&path, &positions);
+
+        if best
+            .as_ref()
+            .is_none_or(|&(best_score, _)| score > best_score)
+        {
+            best = Some((score, positions));
+        }
+    }
+
+    best
+}
+
+/// Returns the score of matching the chars of `path` at `positions`.
+fn score(path: &[char], positions: &[usize]) -> i64 {
+    let mut total = 0_i64;
+    let mut previous: Option<usize> = None;
+
+    for &position in positions {
+        total = total.saturating_add(MATCH_SCORE);
+
+        if position
+            .checked_sub(1)
+            .and_then(|index| path.get(index))
+            .is_none_or(|ch| matches!(ch, '/' | '\\' | '_' | '-' | '.' | ' '))
+        {
+            total = total.saturating_add(BOUNDARY_BONUS);
+        }
+
+        if let Some(previous) = previous {
+            let gap = position.saturating_sub(previous).saturating_sub(1);
+
+            if gap == 0 {
+                total = total.saturating_add(CONSECUTIVE_BONUS);
+            } else {
+                total = total.saturating_sub(i64::try_from(gap).unwrap_or(i64::MAX));
+            }
+        }
+
+        previous = Some(position);
+    }
+
+    total
+}
+
+/// Returns the order of `a` and `b`, the better match first.
+///
+/// Ties are broken by preferring shorter paths, then alphabetical order.
+fn rank(paths: &[String], a: &Match, b: &Match) -> Ordering {
+    b.score
+        .cmp(&a.score)
+        .then_with(|| match (paths.get(a.index), paths.get(b.index)) {
+            (Some(a_path), Some(b_path)) => a_path
+                .len()
+                .cmp(&b_path.len())
+                .then_with(|| a_path.cmp(b_path)),
+            (None, _) | (_, None) => Ordering::Equal,
+        })
+}
+
+/// Picks a path from a list that is filtered by a query.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Picker {
+    /// The query.
+    query: LineEditor,
+    /// All paths that can be picked.
+    paths: Vec<String>,
+    /// The paths that match the query, from best to worst.
+    matches: Vec<Match>,
+    /// The index of the selected match.
+    selected: usize,
+}
+
+impl Picker {
+    /// Returns the query.
+    pub(crate) const fn query(&self) -> &LineEditor {
+        &self.query
+    }
+
+    /// Inserts `text` into the query.
+    pub(crate) fn insert(&mut self, text: &str) {
+        self.query.insert(text);
+        self.filter();
+    }
+
+    /// Performs `edit` on the query.
+    pub(crate) fn edit(&mut self, edit: LineEdit) {
+        self.query.edit(edit);
+        self.filter();
+    }
+
+    /// Adds `paths` to the paths that can be picked.
+    pub(crate) fn add_paths(&mut self, paths: Vec<String>) {
+        let first_index = self.paths.len();
+
+        self.paths.extend(paths);
+        self.match_paths(first_index);
+    }
+
+    /// Moves the selection as described by `movement`.
+    pub(crate) fn move_selection(&mut self, movement: ListMovement) {
+        self.selected = match movement {
+            ListMovement::Previous => self.selected.saturating_sub(1),
+            ListMovement::Next => self
+                .selected
+                .saturating_add(1)
+                .min(self.matches.len().saturating_sub(1)),
+        };
+    }
+
+    /// Returns the selected path.
+    pub(crate) fn selected_path(&self) -> Option<&str> {
+        self.matches
+            .get(self.selected)
+            .and_then(|selected| self.paths.get(selected.index))
+            .map(String::as_str)
+    }
+
+    /// Returns the `height` rows that show the matches, scrolled so that the selected match is visible.
+    pub(crate) fn rows(&self, height: usize) -> Vec<RowText> {
+        let first = self.selected.saturating_sub(height.saturating_sub(1));
+        let mut rows: Vec<RowText> = self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .filter_map(|(index, path_match)| {
+                self.paths
+                    .get(path_match.index)
+                    .map(|path| row_text(path, &path_match.positions, index == self.selected))
+            })
+            .collect();
+
+        // Empty rows clear what was previously shown.
+        rows.resize_with(height, || RowText::new(Vec::new()));
+        rows
+    }
+
+    /// Matches all paths against the query.
+    fn filter(&mut self) {
+        self.matches.clear();
+        self.selected = 0;
+        self.match_paths(0);
+    }
+
+    /// Matches the paths starting at `first_index` against the query, keeping the matches sorted from best to worst.
+    ///
+    /// Only the new matches are sorted; they are then merged into the matches that are already sorted.
+    fn match_paths(&mut self, first_index: usize) {
+        let query = self.query.text();
+        let paths = &self.paths;
+        let mut new_matches: Vec<Match> = paths
+            .iter()
+            .enumerate()
+            .skip(first_index)
+            .filter_map(|(index, path)| {
+                fuzzy_match(query, path).map(|(score, positions)| Match {
+                    index,
+                    score,
+                    positions,
+                })
+            })
+            .collect();
+
+        new_matches.sort_by(|a, b| rank(paths, a, b));
+
+        let mut old_matches = mem::take(&mut self.matches).into_iter().peekable();
+        let mut new_matches = new_matches.into_iter().peekable();
+
+        while let Some(next) = match (old_matches.peek(), new_matches.peek()) {
+            (Some(old_match), Some(new_match)) => {
+                if rank(paths, new_match, old_match) == Ordering::Less {
+                    new_matches.next()
+                } else {
+                    old_matches.next()
+                }
+            }
+            (Some(_), None) => old_matches.next(),
+            (None, _) => new_matches.next(),
+        } {
+            self.matches.push(next);
+        }
+    }
+}
+
+/// Returns the [`RowText`] that shows `text` with the chars at `positions` highlighted.
+///
+/// The row is marked if `is_selected`.
+pub(crate) fn row_text(text: &str, positions: &[usize], is_selected: bool) -> RowText {
+    let mut texts = vec![StyledText::new(
+        if is_selected { "> " } else { "  " }.to_string(),
+        Style::Default,
+    )];
+    let mut segment = String::new();
+    let mut is_match = false;
+
+    for (index, ch) in text.chars().enumerate() {
+        let is_char_match = positions.contains(&index);
+
+        if is_char_match != is_match && !segment.is_empty() {
+            texts.push(StyledText::new(
+                segment.split_off(0),
+                if is_match {
+                    Style::Highlight
+                } else {
+                    Style::Default
+                },
+            ));
+        }
+
+        is_match = is_char_match;
+        segment.push(ch);
+    }
+
+    if !segment.is_empty() {
+        texts.push(StyledText::new(
+            segment,
+            if is_match {
+                Style::Highlight
+            } else {
+                Style::Default
+            },
+        ));
+    }
+
+    RowText::new(texts)
+}
+
+/// Testing of the `picker` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn picker(paths: &[&str]) -> Picker {
+        let mut picker = Picker::default();
+
+        picker.add_paths(paths.iter().map(|path| (*path).to_string()).collect());
+        picker
+    }
+
+    /// Paths shall only match if they contain the chars of the query in order.
+    #[test]
+    fn subsequence() {
+        assert_eq!(
+            fuzzy_match("lib", "src/lib.rs").map(|m| m.1),
+            Some(vec![4, 5, 6])
+        );
+        assert_eq!(fuzzy_match("bil", "src/lib.rs"), None);
+        assert_eq!(fuzzy_match("", "a"), Some((0, Vec::new())));
+    }
+
+    /// Matching shall ignore case unless the query contains an uppercase char.
+    #[test]
+    fn smart_case() {
+        assert!(fuzzy_match("readme", "README.md").is_some());
+        assert!(fuzzy_match("Readme", "readme.md").is_none());
+    }
+
+    /// Paths where the query matches consecutive chars at the start of a component shall be first.
+    #[test]
+    fn ranking() {
+        let mut picker = picker(&["src/app/translate.rs", "src/app.rs", "tests/paper.rs"]);
+
+        picker.insert("app");
+        assert_eq!(picker.selected_path(), Some("src/app.rs"));
+
+        picker.move_selection(ListMovement::Next);
+        assert_eq!(picker.selected_path(), Some("src/app/translate.rs"));
+
+        picker.move_selection(ListMovement::Next);
+        assert_eq!(picker.selected_path(), Some("src/app/translate.rs"));
+
+        picker.insert("tr");
+        assert_eq!(picker.selected_path(), Some("src/app/translate.rs"));
+    }
+
+    /// Paths added after the query shall be ranked together with the paths that were already matched.
+    #[test]
+    fn batches() {
+        let mut picker = picker(&["src/app/translate.rs", "tests/app_test.rs"]);
+
+        picker.insert("app");
+        picker.add_paths(vec!["src/app.rs".to_string(), "apps.rs".to_string()]);
+        assert_eq!(picker.selected_path(), Some("apps.rs"));
+
+        picker.move_selection(ListMovement::Next);
+        assert_eq!(picker.selected_path(), Some("src/app.rs"));
+
+        picker.move_selection(ListMovement::Next);
+        assert_eq!(picker.selected_path(), Some("tests/app_test.rs"));
+    }
+
+    /// Rows shall fill the given height.
+    #[test]
+    fn rows() {
+        let picker = picker(&["a", "b", "c"]);
+
+        assert_eq!(picker.rows(2).len(), 2);
+        assert_eq!(picker.rows(5).len(), 5);
+    }
+}
@@ -0,0 +1,125 @@
+//! Implements the registers that hold text yanked from documents.
+use std::collections::HashMap;
+
+/// The name of the register that is shared with the system clipboard.
+pub(crate) const CLIPBOARD_REGISTER: char = '+';
+
+/// Text held by a register.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Register {
+    /// The text, excluding the final line ending of whole lines.
+    text: String,
+    /// If the text consists of whole lines.
+    is_linewise: bool,
+}
+
+impl Register {
+    /// Creates a new [`Register`] that holds `text`, which is part of a line or spans multiple lines.
+    pub(crate) const fn charwise(text: String) -> Self {
+        Self {
+            text,
+            is_linewise: false,
+        }
+    }
+
+    /// Creates a new [`Register`] that holds `text`, which consists of whole lines.
+    pub(crate) const fn linewise(text: String) -> Self {
+        Self {
+            text,
+            is_linewise: true,
+        }
+    }
+
+    /// Creates a new [`Register`] that holds `text` read from the system clipboard.
+    ///
+    /// As the clipboard does not record how its text was yanked, `text` is considered to consist of whole lines if it ends with a line ending.
+    pub(crate) fn from_clipboard(mut text: String) -> Self {
+        if text.ends_with('\n') {
+            let _ = text.pop();
+
+            if text.ends_with('\r') {
+                let _ = text.pop();
+            }
+
+            Self::linewise(text)
+        } else {
+            Self::charwise(text)
+        }
+    }
+
+    /// Returns the text of `self` as it is to be copied to the system clipboard.
+    ///
+    /// The text of a linewise [`Register`] ends with a line ending so that it is recognized as whole lines when it is read.
+    pub(crate) fn clipboard_text(&self) -> String {
+        if self.is_linewise {
+            format!("{}\n", self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+
+    /// Returns the text of `self`.
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns if `self` consists of whole lines.
+    pub(crate) const fn is_linewise(&self) -> bool {
+        self.is_linewise
+    }
+}
+
+/// The registers of the application.
+///
+/// Every yank and delete is stored in the unnamed register, which is also used when no register is named.
+#[derive(Debug, Default)]
+pub(crate) struct Registers {
+    /// The unnamed register.
+    unnamed: Register,
+    /// The named registers.
+    named: HashMap<char, Register>,
+}
+
+impl Registers {
+    /// Stores `register` in the unnamed register and in the register called `name`.
+    pub(crate) fn set(&mut self, name: Option<char>, register: Register) {
+        if let Some(register_name) = name {
+            let _ = self.named.insert(register_name, register.clone());
+        }
+
+        self.unnamed = register;
+    }
+
+    /// Returns the register called `name`, or the unnamed register if `name` is `None`.
+    pub(crate) fn get(&self, name: Option<char>) -> Option<&Register> {
+        name.map_or(Some(&self.unnamed), |register_name| {
+            self.named.get(&register_name)
+        })
+    }
+}
+
+/// Testing of the registers module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Text copied from a linewise register shall be linewise when read back from the clipboard.
+    #[test]
+    fn clipboard_round_trip() {
+        let linewise = Register::linewise("a\nb".to_string());
+        let charwise = Register::charwise("a\nb".to_string());
+
+        assert_eq!(
+            Register::from_clipboard(linewise.clipboard_text()),
+            linewise
+        );
+        assert_eq!(
+            Register::from_clipboard(charwise.clipboard_text()),
+            charwise
+        );
+        assert_eq!(
+            Register::from_clipboard("a\r\n".to_string()),
+            Register::linewise("a".to_string())
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Implements the list of the lines that match a search of all files.
+use {
+    super::{picker, translate::ListMovement},
+    crate::io::{GrepMatch, RowText},
+    core::convert::TryFrom,
+    lsp_types::{Position, Range},
+    url::Url,
+};
+
+/// The lines that match a search, in the order they were found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ResultList {
+    /// The regex that is searched for.
+    pattern: String,
+    /// The lines that match `pattern`.
+    matches: Vec<GrepMatch>,
+    /// The index of the selected line.
+    selected: usize,
+}
+
+impl ResultList {
+    /// Creates a new [`ResultList`] for a search of `pattern`.
+    pub(crate) fn new(pattern: String) -> Self {
+        Self {
+            pattern,
+            ..Self::default()
+        }
+    }
+
+    /// Adds `matches` to the end of `self`.
+    pub(crate) fn add(&mut self, matches: Vec<GrepMatch>) {
+        self.matches.extend(matches);
+    }
+
+    /// Moves the selection as described by `movement`.
+    pub(crate) fn move_selection(&mut self, movement: ListMovement) {
+        self.selected = match movement {
+            ListMovement::Previous => self.selected.saturating_sub(1),
+            ListMovement::Next => self
+                .selected
+                .saturating_add(1)
+                .min(self.matches.len().saturating_sub(1)),
+        };
+    }
+
+    /// Returns the text that summarizes `self`.
+    pub(crate) fn summary(&self) -> String {
+        format!("{} lines match `{}`", self.matches.len(), self.pattern)
+    }
+
+    /// Returns the path and URL of the file of the selected line and the [`Range`] of its first match.
+    pub(crate) fn selected(&self) -> Option<(String, Option<Url>, Range)> {
+        self.matches.get(self.selected).map(|selected| {
+            let range = selected.ranges.first().cloned().unwrap_or_default();
+
+            (
+                selected.path.clone(),
+                selected.url.clone(),
+                Range::new(
+                    Position::new(selected.line, range.start),
+                    Position::new(selected.line, range.end),
+                ),
+            )
+        })
+    }
+
+    /// Returns the `height` rows that show the lines of `self`, scrolled so that the selected line is visible.
+    ///
+    /// Each row is limited to `width` chars.
+    pub(crate) fn rows(&self, height: usize, width: usize) -> Vec<RowText> {
+        let first = self.selected.saturating_sub(height.saturating_sub(1));
+        // The first 2 columns mark the selected row.
+        let text_width = width.saturating_sub(2);
+        let mut rows: Vec<RowText> = self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .map(|(index, line)| {
+                let location = format!("{}:{}: ", line.path, line.line.saturating_add(1));
+                let offset = location.chars().count();
+                let positions: Vec<usize> = line
+                    .ranges
+                    .iter()
+                    .flat_map(|range| range.start..range.end)
+                    .filter_map(|position| usize::try_from(position).ok())
+                    .map(|position| position.saturating_add(offset))
+                    .collect();
+                let text: String = location
+                    .chars()
+                    .chain(line.text.chars())
+                    .take(text_width)
+                    .collect();
+
+                picker::row_text(&text, &positions, index == self.selected)
+            })
+            .collect();
+
+        // Empty rows clear what was previously shown.
+        rows.resize_with(height, || RowText::new(Vec::new()));
+        rows
+    }
+}
+
+/// Testing of the `results` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grep_match(path: &str, line: u32, ranges: &[(u32, u32)]) -> GrepMatch {
+        GrepMatch {
+            path: path.to_string(),
+            url: None,
+            line,
+            text: "fn main() {}".to_string(),
+            ranges: ranges.iter().map(|&(start, end)| start..end).collect(),
+        }
+    }
+
+    /// Jumping shall select the first match of the selected line.
+    #[test]
+    fn selected() {
+        let mut results = ResultList::new("main".to_string());
+
+        results.add(vec![grep_match("a.rs", 2, &[(3, 7)])]);
+        results.add(vec![grep_match("b.rs", 0, &[(3, 7), (9, 10)])]);
+        results.move_selection(ListMovement::Next);
+        results.move_selection(ListMovement::Next);
+
+        assert_eq!(
+            results.selected(),
+            Some((
+                "b.rs".to_string(),
+                None,
+                Range::new(Position::new(0, 3), Position::new(0, 7))
+            ))
+        );
+        assert_eq!(results.summary(), "2 lines match `main`");
+    }
+}
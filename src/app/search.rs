@@ -0,0 +1,141 @@
+//! Implements the search for the matches of a pattern within the document.
+use {
+    super::{
+        line_editor::LineEditor,
+        translate::{LineEdit, SearchDirection},
+    },
+    core::ops,
+    lsp_types::{Position, Range},
+    regex::{Regex, RegexBuilder},
+};
+
+/// A search that is being edited.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Search {
+    /// The pattern.
+    query: LineEditor,
+    /// The direction in which the pattern is searched.
+    direction: SearchDirection,
+    /// The start of the selection when the search started; matches are found relative to it.
+    origin: Position,
+    /// The range that was selected when the search started, restored if the search is cancelled.
+    range_selection: Option<Range>,
+}
+
+impl Search {
+    /// Creates a new [`Search`] towards `direction` from the selection starting at `origin`.
+    pub(crate) fn new(
+        direction: SearchDirection,
+        origin: Position,
+        range_selection: Option<Range>,
+    ) -> Self {
+        Self {
+            query: LineEditor::default(),
+            direction,
+            origin,
+            range_selection,
+        }
+    }
+
+    /// Returns the pattern.
+    pub(crate) const fn query(&self) -> &LineEditor {
+        &self.query
+    }
+
+    /// Returns the prompt that precedes the pattern.
+    pub(crate) const fn prompt(&self) -> &'static str {
+        match self.direction {
+            SearchDirection::Forward => "/",
+            SearchDirection::Backward => "?",
+        }
+    }
+
+    /// Returns the direction in which the pattern is searched.
+    pub(crate) const fn direction(&self) -> SearchDirection {
+        self.direction
+    }
+
+    /// Returns the start of the selection when the search started.
+    pub(crate) const fn origin(&self) -> Position {
+        self.origin
+    }
+
+    /// Returns the range that was selected when the search started.
+    pub(crate) const fn range_selection(&self) -> Option<Range> {
+        self.range_selection
+    }
+
+    /// Inserts `text` into the pattern.
+    pub(crate) fn insert(&mut self, text: &str) {
+        self.query.insert(text);
+    }
+
+    /// Performs `edit` on the pattern.
+    pub(crate) fn edit(&mut self, edit: LineEdit) {
+        self.query.edit(edit);
+    }
+}
+
+/// Returns the [`Regex`] that matches `pattern`, or `None` if `pattern` is empty or invalid.
+///
+/// Matching ignores case unless `pattern` contains an uppercase char.
+pub(crate) fn matcher(pattern: &str) -> Option<Regex> {
+    if pattern.is_empty() {
+        None
+    } else {
+        RegexBuilder::new(pattern)
+            .case_insensitive(!pattern.chars().any(char::is_uppercase))
+            .build()
+            .ok()
+    }
+}
+
+/// Returns the char ranges of the non-empty matches of `regex` within `line`.
+pub(crate) fn match_ranges(regex: &Regex, line: &str) -> Vec<ops::Range<usize>> {
+    regex
+        .find_iter(line)
+        .filter(|found| !found.as_str().is_empty())
+        .filter_map(|found| {
+            let start = line.get(..found.start())?.chars().count();
+
+            Some(start..start.saturating_add(found.as_str().chars().count()))
+        })
+        .collect()
+}
+
+/// Testing of the `search` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Patterns shall ignore case unless they contain an uppercase char.
+    #[test]
+    fn smart_case() {
+        let lowercase = matcher("main");
+        let uppercase = matcher("Main");
+
+        assert_eq!(
+            lowercase.map(|regex| regex.is_match("fn MAIN()")),
+            Some(true)
+        );
+        assert_eq!(
+            uppercase.map(|regex| regex.is_match("fn main()")),
+            Some(false)
+        );
+        assert!(matcher("").is_none());
+        assert!(matcher("(").is_none());
+    }
+
+    /// Matches shall be described by char ranges and empty matches shall be ignored.
+    #[test]
+    fn char_ranges() {
+        assert_eq!(
+            matcher("b").map(|regex| match_ranges(&regex, "\u{e4}b ab")),
+            Some(vec![1..2, 4..5])
+        );
+        assert_eq!(
+            matcher("x*").map(|regex| match_ranges(&regex, "ab")),
+            Some(Vec::new())
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Implements the options that the user can set while the application is running.
+use {
+    super::{encoding::PositionEncoding, translate::ScrollAmount},
+    crate::orient::ScreenDirection,
+    core::str::FromStr,
+    crossterm::event::KeyCode,
+    enum_map::{enum_map, EnumMap},
+    fehler::{throw, throws},
+    lsp_types::TextDocumentSyncKind,
+    parse_display::Display as ParseDisplay,
+    thiserror::Error as ThisError,
+};
+
+/// Describes an option that can be set.
+#[derive(Debug)]
+pub(crate) struct OptionSpec {
+    /// The name of the option.
+    pub(crate) name: &'static str,
+    /// Describes the value of the option.
+    pub(crate) help: &'static str,
+}
+
+/// All options that can be set.
+pub(crate) static OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        name: "scroll",
+        help: "number of rows or columns scrolled by a step, such as by `d` and `u` (default 5)",
+    },
+    OptionSpec {
+        name: "scrollkey",
+        help: "key that scrolls towards a direction by an amount, as `<direction> <amount> <key>` such as `down halfpage D`; <key> none unbinds the scroll",
+    },
+    OptionSpec {
+        name: "scrolloff",
+        help: "number of rows kept between the selection and the top and bottom of the document (default 2)",
+    },
+    OptionSpec {
+        name: "hidden",
+        help: "whether the file finder lists hidden files (default false)",
+    },
+    OptionSpec {
+        name: "tabwidth",
+        help: "number of columns between tab stops (default 4)",
+    },
+    OptionSpec {
+        name: "whitespace",
+        help: "whether tabs and trailing whitespace are shown with visible glyphs (default false)",
+    },
+    OptionSpec {
+        name: "number",
+        help: "line numbers shown in the gutter: off, absolute or relative (default off)",
+    },
+    OptionSpec {
+        name: "signs",
+        help: "whether the gutter shows a sign column (default false)",
+    },
+];
+
+/// An error setting an option.
+#[derive(Debug, PartialEq, ThisError)]
+pub(crate) enum SettingError {
+    /// The option does not exist.
+    #[error("unknown option `{0}`")]
+    Unknown(String),
+    /// The value is not valid for the option.
+    #[error("invalid value for `{name}`: {error}")]
+    InvalidValue {
+        /// The name of the option.
+        name: String,
+        /// The error parsing the value.
+        error: String,
+    },
+}
+
+/// A value given to an option.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Setting {
+    /// The number of rows or columns scrolled by a single scroll.
+    Scroll(u32),
+    /// The number of rows kept between the selection and the edges of the document.
+    ScrollOff(u32),
+    /// If the file finder lists hidden files.
+    Hidden(bool),
+    /// The number of columns between tab stops.
+    TabWidth(u8),
+    /// If tabs and trailing whitespace are shown with visible glyphs.
+    Whitespace(bool),
+    /// The line numbers shown in the gutter.
+    Number(LineNumbers),
+    /// If the gutter shows a sign column.
+    Signs(bool),
+    /// The key that scrolls the document towards a direction by an amount.
+    ScrollKey(ScrollBinding),
+}
+
+impl Setting {
+    /// Parses `value` as the value of the option called `name`.
+    #[throws(SettingError)]
+    pub(crate) fn parse(name: &str, value: &str) -> Self {
+        match name {
+            "scroll" => Self::Scroll(parse_value(name, value)?),
+            "scrolloff" => Self::ScrollOff(parse_value(name, value)?),
+            "hidden" => Self::Hidden(parse_value(name, value)?),
+            "tabwidth" => Self::TabWidth(parse_value(name, value)?),
+            "whitespace" => Self::Whitespace(parse_value(name, value)?),
+            "number" => Self::Number(parse_value(name, value)?),
+            "signs" => Self::Signs(parse_value(name, value)?),
+            "scrollkey" => Self::ScrollKey(parse_value(name, value)?),
+            _ => throw!(SettingError::Unknown(name.to_string())),
+        }
+    }
+}
+
+/// Parses `value` as the value of the option called `name`.
+#[throws(SettingError)]
+fn parse_value<T: FromStr>(name: &str, value: &str) -> T
+where
+    T::Err: ToString,
+{
+    value
+        .parse()
+        .map_err(|error: T::Err| SettingError::InvalidValue {
+            name: name.to_string(),
+            error: error.to_string(),
+        })?
+}
+
+/// Describes the line numbers shown in the gutter.
+#[derive(Clone, Copy, Debug, Eq, ParseDisplay, PartialEq)]
+#[display(style = "lowercase")]
+pub(crate) enum LineNumbers {
+    /// No line numbers are shown.
+    Off,
+    /// Each line shows its number.
+    Absolute,
+    /// Each line shows its distance from the selected line, which shows its number.
+    Relative,
+}
+
+impl FromStr for LineNumbers {
+    type Err = UnknownLineNumbersError;
+
+    #[inline]
+    #[throws(Self::Err)]
+    fn from_str(s: &str) -> Self {
+        match s {
+            "off" => Self::Off,
+            "absolute" => Self::Absolute,
+            "relative" => Self::Relative,
+            _ => throw!(UnknownLineNumbersError(s.to_string())),
+        }
+    }
+}
+
+/// The name of a kind of line numbers is not known.
+#[derive(Debug, ThisError)]
+#[error("expected `off`, `absolute` or `relative`, found `{0}`")]
+pub(crate) struct UnknownLineNumbersError(String);
+
+/// Binds a key to a scroll of the document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ScrollBinding {
+    /// The direction of the scroll.
+    direction: ScreenDirection,
+    /// The amount of the scroll.
+    amount: ScrollAmount,
+    /// The key, or `None` if the scroll is not bound to a key.
+    key: Option<KeyCode>,
+}
+
+impl FromStr for ScrollBinding {
+    type Err = InvalidScrollBindingError;
+
+    #[inline]
+    #[throws(Self::Err)]
+    fn from_str(s: &str) -> Self {
+        let error = || InvalidScrollBindingError(s.to_string());
+        let mut words = s.split_whitespace();
+        let direction = match words.next() {
+            Some("up") => ScreenDirection::Up,
+            Some("down") => ScreenDirection::Down,
+            Some("left") => ScreenDirection::Left,
+            Some("right") => ScreenDirection::Right,
+            _ => throw!(error()),
+        };
+        let amount = match words.next() {
+            Some("step") => ScrollAmount::Step,
+            Some("line") => ScrollAmount::Line,
+            Some("halfpage") => ScrollAmount::HalfPage,
+            Some("page") => ScrollAmount::Page,
+            Some("edge") => ScrollAmount::Edge,
+            _ => throw!(error()),
+        };
+        let key = match words.next() {
+            Some("none") => None,
+            Some("left") => Some(KeyCode::Left),
+            Some("right") => Some(KeyCode::Right),
+            Some("up") => Some(KeyCode::Up),
+            Some("down") => Some(KeyCode::Down),
+            Some("pageup") => Some(KeyCode::PageUp),
+            Some("pagedown") => Some(KeyCode::PageDown),
+            Some("home") => Some(KeyCode::Home),
+            Some("end") => Some(KeyCode::End),
+            Some("space") => Some(KeyCode::Char(' ')),
+            Some(name) => {
+                let mut chars = name.chars();
+
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => Some(KeyCode::Char(ch)),
+                    _ => throw!(error()),
+                }
+            }
+            None => throw!(error()),
+        };
+
+        if words.next().is_some() {
+            throw!(error());
+        }
+
+        Self {
+            direction,
+            amount,
+            key,
+        }
+    }
+}
+
+/// A scroll binding is not formatted as `<direction> <amount> <key>`.
+#[derive(Debug, ThisError)]
+#[error("expected `<direction> <amount> <key>`, found `{0}`")]
+pub(crate) struct InvalidScrollBindingError(String);
+
+/// The keys that scroll the document in view mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ScrollKeys {
+    /// The key bound to the scroll towards each direction by each amount.
+    keys: EnumMap<ScreenDirection, EnumMap<ScrollAmount, Option<KeyCode>>>,
+}
+
+impl ScrollKeys {
+    /// Returns the direction and amount of the scroll bound to `key`, or `None` if `key` does not scroll.
+    pub(crate) fn scroll(&self, key: KeyCode) -> Option<(ScreenDirection, ScrollAmount)> {
+        self.keys.iter().find_map(|(direction, amounts)| {
+            amounts
+                .iter()
+                .find(|&(_, &bound)| bound == Some(key))
+                .map(|(amount, _)| (direction, amount))
+        })
+    }
+
+    /// Binds the key of `binding` to its scroll, unbinding the key from any other scroll.
+    fn bind(&mut self, binding: ScrollBinding) {
+        for (_, amounts) in self.keys.iter_mut() {
+            for (_, key) in amounts.iter_mut() {
+                if binding.key.is_some() && *key == binding.key {
+                    *key = None;
+                }
+            }
+        }
+
+        #[allow(clippy::indexing_slicing)] // EnumMap guarantees that index is in bounds.
+        let key = &mut self.keys[binding.direction][binding.amount];
+
+        *key = binding.key;
+    }
+}
+
+impl Default for ScrollKeys {
+    #[inline]
+    fn default() -> Self {
+        // TODO: Change `d` and `u` to Space and Shift+Space.
+        Self {
+            keys: enum_map! {
+                ScreenDirection::Up => enum_map! {
+                    ScrollAmount::Step => Some(KeyCode::Char('u')),
+                    ScrollAmount::Line => Some(KeyCode::Up),
+                    ScrollAmount::HalfPage => Some(KeyCode::Char('U')),
+                    ScrollAmount::Page => Some(KeyCode::PageUp),
+                    ScrollAmount::Edge => Some(KeyCode::Home),
+                },
+                ScreenDirection::Down => enum_map! {
+                    ScrollAmount::Step => Some(KeyCode::Char('d')),
+                    ScrollAmount::Line => Some(KeyCode::Down),
+                    ScrollAmount::HalfPage => Some(KeyCode::Char('D')),
+                    ScrollAmount::Page => Some(KeyCode::PageDown),
+                    ScrollAmount::Edge => Some(KeyCode::End),
+                },
+                ScreenDirection::Left => enum_map! {
+                    ScrollAmount::Step => Some(KeyCode::Left),
+                    ScrollAmount::Line
+                    | ScrollAmount::HalfPage
+                    | ScrollAmount::Page
+                    | ScrollAmount::Edge => None,
+                },
+                ScreenDirection::Right => enum_map! {
+                    ScrollAmount::Step => Some(KeyCode::Right),
+                    ScrollAmount::Line
+                    | ScrollAmount::HalfPage
+                    | ScrollAmount::Page
+                    | ScrollAmount::Edge => None,
+                },
+            },
+        }
+    }
+}
+
+/// The current values of all options.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Settings {
+    /// The number of rows or columns scrolled by a single scroll.
+    pub(crate) scroll: u32,
+    /// The number of rows kept between the selection and the edges of the document.
+    pub(crate) scroll_off: u32,
+    /// If the file finder lists hidden files.
+    pub(crate) hidden: bool,
+    /// The number of columns between tab stops.
+    pub(crate) tab_width: u8,
+    /// If tabs and trailing whitespace are shown with visible glyphs.
+    pub(crate) whitespace: bool,
+    /// The line numbers shown in the gutter.
+    pub(crate) number: LineNumbers,
+    /// If the gutter shows a sign column.
+    pub(crate) signs: bool,
+    /// The keys that scroll the document.
+    pub(crate) scroll_keys: ScrollKeys,
+    /// The position encoding negotiated with the language server, which is not an option.
+    pub(crate) encoding: PositionEncoding,
+    /// How the language server syncs the changes to documents, which is negotiated like `encoding`.
+    pub(crate) sync: TextDocumentSyncKind,
+}
+
+impl Settings {
+    /// Sets the option specified by `setting`.
+    pub(crate) fn apply(&mut self, setting: Setting) {
+        match setting {
+            Setting::Scroll(scroll) => {
+                self.scroll = scroll;
+            }
+            Setting::ScrollOff(scroll_off) => {
+                self.scroll_off = scroll_off;
+            }
+            Setting::Hidden(hidden) => {
+                self.hidden = hidden;
+            }
+            Setting::TabWidth(tab_width) => {
+                self.tab_width = tab_width;
+            }
+            Setting::Whitespace(whitespace) => {
+                self.whitespace = whitespace;
+            }
+            Setting::Number(number) => {
+                self.number = number;
+            }
+            Setting::Signs(signs) => {
+                self.signs = signs;
+            }
+            Setting::ScrollKey(binding) => {
+                self.scroll_keys.bind(binding);
+            }
+        }
+    }
+}
+
+impl Default for Settings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            scroll: 5,
+            scroll_off: 2,
+            hidden: false,
+            tab_width: 4,
+            whitespace: false,
+            number: LineNumbers::Off,
+            signs: false,
+            scroll_keys: ScrollKeys::default(),
+            encoding: PositionEncoding::default(),
+            // Changes made before the language server chooses are sent incrementally.
+            sync: TextDocumentSyncKind::Incremental,
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Implements the sources of the signs shown in the sign column of a document.
+use {
+    core::convert::TryFrom,
+    lsp_types::{Diagnostic, DiagnosticSeverity},
+};
+
+/// The sign of a bookmarked line.
+const BOOKMARK_SIGN: char = '*';
+/// The sign of a line with an error.
+const ERROR_SIGN: char = 'E';
+/// The sign of a line with a warning.
+const WARNING_SIGN: char = 'W';
+/// The sign of a line with information.
+const INFORMATION_SIGN: char = 'I';
+/// The sign of a line with a hint.
+const HINT_SIGN: char = 'H';
+/// The sign of a line that was added since the last commit.
+const ADDED_SIGN: char = '+';
+/// The sign of a line that was modified since the last commit.
+const MODIFIED_SIGN: char = '~';
+/// The sign of a line that follows lines removed since the last commit.
+const REMOVED_SIGN: char = '_';
+
+/// Returns the index of `line` after the `old_count` lines starting at `first_line` are replaced by `new_count` lines, or `None` if `line` was removed.
+fn shift_line(line: usize, first_line: usize, old_count: usize, new_count: usize) -> Option<usize> {
+    let old_end = first_line.saturating_add(old_count);
+    let new_end = first_line.saturating_add(new_count);
+
+    if line >= old_end {
+        Some(line.saturating_sub(old_end).saturating_add(new_end))
+    } else if line < new_end {
+        Some(line)
+    } else {
+        None
+    }
+}
+
+/// Provides the signs of the lines of a document.
+pub(crate) trait SignSource {
+    /// Returns the sign of the line at `index`, or `None` if the line has no sign from `self`.
+    fn sign(&self, index: usize) -> Option<char>;
+}
+
+/// The lines of a document that are bookmarked.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Bookmarks {
+    /// The indexes of the bookmarked lines.
+    lines: Vec<usize>,
+}
+
+impl Bookmarks {
+    /// Adds a bookmark on the line at `index`, or removes it if the line is already bookmarked.
+    pub(crate) fn toggle(&mut self, index: usize) {
+        if let Some(position) = self.lines.iter().position(|&line| line == index) {
+            let _ = self.lines.remove(position);
+        } else {
+            self.lines.push(index);
+        }
+    }
+
+    /// Moves the bookmarks after the `old_count` lines starting at `first_line` are replaced by `new_count` lines.
+    ///
+    /// The bookmarks of removed lines are removed.
+    pub(crate) fn shift(&mut self, first_line: usize, old_count: usize, new_count: usize) {
+        self.lines = self
+            .lines
+            .iter()
+            .filter_map(|&line| shift_line(line, first_line, old_count, new_count))
+            .collect();
+    }
+}
+
+impl SignSource for Bookmarks {
+    fn sign(&self, index: usize) -> Option<char> {
+        self.lines.contains(&index).then_some(BOOKMARK_SIGN)
+    }
+}
+
+/// The severities of the diagnostics published for the lines of a document.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Diagnostics {
+    /// The index of the first line of each diagnostic with its severity.
+    lines: Vec<(usize, DiagnosticSeverity)>,
+}
+
+impl Diagnostics {
+    /// Creates the [`Diagnostics`] of `diagnostics`.
+    ///
+    /// A diagnostic without a severity is treated as an error.
+    pub(crate) fn new(diagnostics: &[Diagnostic]) -> Self {
+        Self {
+            lines: diagnostics
+                .iter()
+                .filter_map(|diagnostic| {
+                    usize::try_from(diagnostic.range.start.line)
+                        .ok()
+                        .map(|line| {
+                            (
+                                line,
+                                diagnostic.severity.unwrap_or(DiagnosticSeverity::Error),
+                            )
+                        })
+                })
+                .collect(),
+        }
+    }
+
+    /// Moves the diagnostics after the `old_count` lines starting at `first_line` are replaced by `new_count` lines.
+    ///
+    /// The diagnostics of removed lines are removed until the server publishes them again.
+    pub(crate) fn shift(&mut self, first_line: usize, old_count: usize, new_count: usize) {
+        self.lines = self
+            .lines
+            .iter()
+            .filter_map(|&(line, severity)| {
+                shift_line(line, first_line, old_count, new_count).map(|line| (line, severity))
+            })
+            .collect();
+    }
+}
+
+impl SignSource for Diagnostics {
+    /// Returns the sign of the most severe diagnostic of the line at `index`.
+    fn sign(&self, index: usize) -> Option<char> {
+        self.lines
+            .iter()
+            .filter(|&&(line, _)| line == index)
+            .map(|&(_, severity)| severity)
+            .min()
+            .map(|severity| match severity {
+                DiagnosticSeverity::Error => ERROR_SIGN,
+                DiagnosticSeverity::Warning => WARNING_SIGN,
+                DiagnosticSeverity::Information => INFORMATION_SIGN,
+                DiagnosticSeverity::Hint => HINT_SIGN,
+            })
+    }
+}
+
+/// How a line differs from the last commit of its file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LineChange {
+    /// The line was added.
+    Added,
+    /// The line was modified.
+    Modified,
+    /// Lines before the line were removed.
+    Removed,
+}
+
+/// The lines of a document that differ from the last commit of its file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct VcsChanges {
+    /// The index of each changed line with its [`LineChange`].
+    lines: Vec<(usize, LineChange)>,
+}
+
+impl VcsChanges {
+    /// Creates the [`VcsChanges`] of `diff`, the output of `git diff` with no lines of context.
+    ///
+    /// Only the hunk headers of `diff` are read.
+    pub(crate) fn from_diff(diff: &str) -> Self {
+        let mut lines = Vec::new();
+
+        for (start, old_count, new_count) in diff.lines().filter_map(hunk_header) {
+            if new_count == 0 {
+                // The range of a removal starts at the number of the line before the removed lines, which is the index of the line after them.
+                lines.push((start, LineChange::Removed));
+            } else {
+                let change = if old_count == 0 {
+                    LineChange::Added
+                } else {
+                    LineChange::Modified
+                };
+
+                lines.extend(
+                    (start.saturating_sub(1)..start.saturating_sub(1).saturating_add(new_count))
+                        .map(|line| (line, change)),
+                );
+            }
+        }
+
+        Self { lines }
+    }
+
+    /// Moves the changes after the `old_count` lines starting at `first_line` are replaced by `new_count` lines.
+    ///
+    /// The changes are read again when the document is written.
+    pub(crate) fn shift(&mut self, first_line: usize, old_count: usize, new_count: usize) {
+        self.lines = self
+            .lines
+            .iter()
+            .filter_map(|&(line, change)| {
+                shift_line(line, first_line, old_count, new_count).map(|line| (line, change))
+            })
+            .collect();
+    }
+}
+
+impl SignSource for VcsChanges {
+    fn sign(&self, index: usize) -> Option<char> {
+        self.lines
+            .iter()
+            .find(|&&(line, _)| line == index)
+            .map(|&(_, change)| match change {
+                LineChange::Added => ADDED_SIGN,
+                LineChange::Modified => MODIFIED_SIGN,
+                LineChange::Removed => REMOVED_SIGN,
+            })
+    }
+}
+
+/// Returns the start of the new range and the counts of old and new lines of the hunk header `line`, or `None` if `line` is not a hunk header.
+///
+/// A hunk header is formatted as `@@ -<start>[,<count>] +<start>[,<count>] @@`, where an omitted count is 1.
+fn hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let mut ranges = line.strip_prefix("@@ -")?.split(' ');
+    let (_, old_count) = hunk_range(ranges.next()?)?;
+    let (start, new_count) = hunk_range(ranges.next()?.strip_prefix('+')?)?;
+
+    Some((start, old_count, new_count))
+}
+
+/// Returns the start and count of the hunk range `range`.
+fn hunk_range(range: &str) -> Option<(usize, usize)> {
+    let mut parts = range.splitn(2, ',');
+
+    Some((
+        parts.next()?.parse().ok()?,
+        parts.next().map_or(Ok(1), str::parse).ok()?,
+    ))
+}
+
+/// Testing of the `signs` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Bookmarks shall follow their lines when lines are inserted or removed before them.
+    #[test]
+    fn shift_bookmarks() {
+        let mut bookmarks = Bookmarks::default();
+
+        bookmarks.toggle(1);
+        bookmarks.toggle(4);
+        bookmarks.toggle(6);
+        bookmarks.toggle(6);
+        bookmarks.shift(2, 1, 3);
+
+        assert_eq!(bookmarks.sign(1), Some(BOOKMARK_SIGN));
+        assert_eq!(bookmarks.sign(4), None);
+        assert_eq!(bookmarks.sign(6), Some(BOOKMARK_SIGN));
+
+        bookmarks.shift(5, 2, 0);
+
+        assert_eq!(bookmarks.sign(5), None);
+        assert_eq!(bookmarks.sign(6), None);
+        assert_eq!(bookmarks.lines, vec![1]);
+    }
+
+    /// The most severe diagnostic of a line shall decide its sign, and diagnostics shall follow their lines.
+    #[test]
+    fn diagnostic_signs() {
+        let diagnostic = |line, severity| Diagnostic {
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(line, 4),
+                lsp_types::Position::new(line.saturating_add(1), 0),
+            ),
+            severity,
+            ..Diagnostic::default()
+        };
+        let mut diagnostics = Diagnostics::new(&[
+            diagnostic(1, Some(DiagnosticSeverity::Hint)),
+            diagnostic(1, Some(DiagnosticSeverity::Warning)),
+            diagnostic(3, None),
+            diagnostic(5, Some(DiagnosticSeverity::Information)),
+        ]);
+
+        assert_eq!(diagnostics.sign(1), Some(WARNING_SIGN));
+        assert_eq!(diagnostics.sign(2), None);
+        assert_eq!(diagnostics.sign(3), Some(ERROR_SIGN));
+
+        diagnostics.shift(2, 2, 1);
+
+        assert_eq!(diagnostics.sign(1), Some(WARNING_SIGN));
+        assert_eq!(diagnostics.sign(3), None);
+        assert_eq!(diagnostics.sign(4), Some(INFORMATION_SIGN));
+    }
+
+    /// The hunk headers of a diff shall mark the lines that were added, modified and removed.
+    #[test]
+    fn vcs_change_signs() {
+        let mut changes = VcsChanges::from_diff(
+            "diff --git a/lib.rs b/lib.rs\n\
+             --- a/lib.rs\n\
+             +++ b/lib.rs\n\
+             @@ -2 +2 @@ fn main() {\n\
+             -    old();\n\
+             +    new();\n\
+             @@ -4,0 +5,2 @@ fn main() {\n\
+             +    one();\n\
+             +    two();\n\
+             @@ -9,2 +10,0 @@\n\
+             -@@ -1 +1 @@\n\
+             -}\n",
+        );
+
+        assert_eq!(changes.sign(0), None);
+        assert_eq!(changes.sign(1), Some(MODIFIED_SIGN));
+        assert_eq!(changes.sign(4), Some(ADDED_SIGN));
+        assert_eq!(changes.sign(5), Some(ADDED_SIGN));
+        assert_eq!(changes.sign(6), None);
+        assert_eq!(changes.sign(10), Some(REMOVED_SIGN));
+
+        changes.shift(0, 1, 0);
+
+        assert_eq!(changes.sign(0), Some(MODIFIED_SIGN));
+        assert_eq!(changes.sign(9), Some(REMOVED_SIGN));
+    }
+}
@@ -0,0 +1,401 @@
+//! Implements the replacement of the matches of a pattern, each of which is confirmed by the user.
+use {
+    super::Replacement,
+    crate::io::GrepMatch,
+    core::convert::TryFrom,
+    lsp_types::{Position, Range},
+    regex::Regex,
+    std::collections::VecDeque,
+    url::Url,
+};
+
+/// Describes where a match was found.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Location {
+    /// The document.
+    Document,
+    /// A file that is not open.
+    File {
+        /// The path of the file, relative to the root directory.
+        path: String,
+        /// The URL of the file.
+        url: Url,
+    },
+}
+
+/// A [`Replacement`] within a file that is not open, along with the text it expects to replace.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FileReplacement {
+    /// The replacement.
+    pub(crate) replacement: Replacement,
+    /// The text of the match when it was found.
+    pub(crate) old_text: String,
+}
+
+/// A replacement of a match that awaits confirmation.
+#[derive(Clone, Debug, PartialEq)]
+struct Candidate {
+    /// Where the match was found.
+    location: Location,
+    /// The [`Range`] of the match.
+    range: Range,
+    /// The text of the line that contains the match.
+    line: String,
+    /// The text that replaces the match.
+    text: String,
+}
+
+impl Candidate {
+    /// Returns the char indexes of the start and end of the match within the line.
+    fn columns(&self) -> (usize, usize) {
+        (
+            usize::try_from(self.range.start.character).unwrap_or(usize::MAX),
+            usize::try_from(self.range.end.character).unwrap_or(usize::MAX),
+        )
+    }
+
+    /// Returns the text of the match.
+    fn matched_text(&self) -> String {
+        let (start, end) = self.columns();
+
+        self.line
+            .chars()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .collect()
+    }
+
+    /// Returns the text of the line once the match is replaced.
+    fn replaced_line(&self) -> String {
+        let (start, end) = self.columns();
+
+        self.line
+            .chars()
+            .take(start)
+            .chain(self.text.chars())
+            .chain(self.line.chars().skip(end))
+            .collect()
+    }
+}
+
+/// The replacements of the matches of a pattern, which are confirmed one at a time.
+#[derive(Debug)]
+pub(crate) struct Substitution {
+    /// The pattern.
+    regex: Regex,
+    /// The text that replaces each match, which may refer to the capture groups of `regex`.
+    replacement: String,
+    /// The [`Range`] outside of which matches are ignored.
+    bounds: Option<Range>,
+    /// The replacements that have not been confirmed, in the order they are confirmed.
+    pending: VecDeque<Candidate>,
+    /// The replacements that have been accepted.
+    accepted: Vec<Candidate>,
+    /// The number of replacements that have been skipped.
+    skipped: usize,
+    /// If files are still being searched for matches.
+    is_searching: bool,
+    /// The URLs of the open documents, whose files may no longer match their text.
+    open_urls: Vec<Url>,
+}
+
+impl Substitution {
+    /// Creates a new [`Substitution`] of the matches of `regex` within `bounds` by `replacement`.
+    pub(crate) fn new(regex: Regex, replacement: String, bounds: Option<Range>) -> Self {
+        Self {
+            regex,
+            replacement,
+            bounds,
+            pending: VecDeque::new(),
+            accepted: Vec::new(),
+            skipped: 0,
+            is_searching: false,
+            open_urls: Vec::new(),
+        }
+    }
+
+    /// Returns the pattern.
+    pub(crate) fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Waits for the matches within the files of the root directory to be added, other than those within the files at `open_urls`.
+    pub(crate) fn start_search(&mut self, open_urls: Vec<Url>) {
+        self.is_searching = true;
+        self.open_urls = open_urls;
+    }
+
+    /// Adds the matches within `text`, the text of the line at `line` of `location`.
+    pub(crate) fn add_line(&mut self, location: &Location, line: u32, text: &str) {
+        for captures in self.regex.captures_iter(text) {
+            if let Some(found) = captures.get(0).filter(|found| !found.as_str().is_empty()) {
+                if let (Some(start), Some(end)) = (
+                    text.get(..found.start())
+                        .and_then(|before| u32::try_from(before.chars().count()).ok()),
+                    text.get(..found.end())
+                        .and_then(|before| u32::try_from(before.chars().count()).ok()),
+                ) {
+                    let range = Range::new(Position::new(line, start), Position::new(line, end));
+
+                    if self
+                        .bounds
+                        .is_none_or(|bounds| bounds.start <= range.start && range.end <= bounds.end)
+                    {
+                        let mut replacement = String::new();
+
+                        captures.expand(&self.replacement, &mut replacement);
+                        self.pending.push_back(Candidate {
+                            location: location.clone(),
+                            range,
+                            line: text.to_string(),
+                            text: replacement,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds `matches` found by searching the files of the root directory.
+    ///
+    /// Matches within the file of an open document are ignored, as writing them would leave the document stale and a later write of the document would revert them. An empty batch signals that the search is complete.
+    pub(crate) fn add_matches(&mut self, matches: Vec<GrepMatch>) {
+        if matches.is_empty() {
+            self.is_searching = false;
+        }
+
+        for found in matches {
+            if let Some(url) = found.url.filter(|url| !self.open_urls.contains(url)) {
+                self.add_line(
+                    &Location::File {
+                        path: found.path,
+                        url,
+                    },
+                    found.line,
+                    &found.text,
+                );
+            }
+        }
+    }
+
+    /// Returns the text that shows the replacement awaiting confirmation.
+    pub(crate) fn preview(&self) -> Option<String> {
+        self.pending.front().map(|candidate| {
+            let line = candidate.range.start.line.saturating_add(1);
+
+            format!(
+                "{}\n- {}\n+ {}",
+                match &candidate.location {
+                    Location::Document => format!("line {}", line),
+                    Location::File { path, .. } => format!("{}:{}", path, line),
+                },
+                candidate.line,
+                candidate.replaced_line()
+            )
+        })
+    }
+
+    /// Accepts the replacement awaiting confirmation.
+    pub(crate) fn accept(&mut self) {
+        self.accepted.extend(self.pending.pop_front());
+    }
+
+    /// Skips the replacement awaiting confirmation.
+    pub(crate) fn skip(&mut self) {
+        if self.pending.pop_front().is_some() {
+            self.skipped = self.skipped.saturating_add(1);
+        }
+    }
+
+    /// Returns if every replacement has been confirmed.
+    pub(crate) fn is_done(&self) -> bool {
+        self.pending.is_empty() && !self.is_searching
+    }
+
+    /// Returns the text that summarizes the confirmed replacements.
+    pub(crate) fn summary(&self) -> String {
+        let total = self
+            .accepted
+            .len()
+            .saturating_add(self.skipped)
+            .saturating_add(self.pending.len());
+
+        if total == 0 {
+            format!("no matches of `{}`", self.pattern())
+        } else {
+            format!("replaced {} of {} matches", self.accepted.len(), total)
+        }
+    }
+
+    /// Returns the accepted [`Replacement`]s within the document and the accepted [`FileReplacement`]s within each file.
+    pub(crate) fn into_replacements(self) -> (Vec<Replacement>, Vec<(Url, Vec<FileReplacement>)>) {
+        let mut document = Vec::new();
+        let mut files: Vec<(Url, Vec<FileReplacement>)> = Vec::new();
+
+        for candidate in self.accepted {
+            let old_text = candidate.matched_text();
+            let replacement = Replacement {
+                range: candidate.range,
+                text: candidate.text,
+            };
+
+            match candidate.location {
+                Location::Document => document.push(replacement),
+                Location::File { url, .. } => {
+                    let replacement = FileReplacement {
+                        replacement,
+                        old_text,
+                    };
+
+                    match files.iter_mut().find(|(file_url, _)| *file_url == url) {
+                        Some((_, replacements)) => replacements.push(replacement),
+                        None => files.push((url, vec![replacement])),
+                    }
+                }
+            }
+        }
+
+        (document, files)
+    }
+}
+
+/// Testing of the `substitution` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn substitution(
+        pattern: &str,
+        replacement: &str,
+        bounds: Option<Range>,
+    ) -> Option<Substitution> {
+        Regex::new(pattern)
+            .ok()
+            .map(|regex| Substitution::new(regex, replacement.to_string(), bounds))
+    }
+
+    /// Replacements shall expand capture groups and only the accepted replacements shall be applied.
+    #[test]
+    fn confirm() {
+        let mut substitution = substitution("(\\w+)=(\\w+)", "$2=$1", None);
+
+        if let Some(substitution) = substitution.as_mut() {
+            substitution.add_line(&Location::Document, 3, "a=b, c=d");
+        }
+
+        assert_eq!(
+            substitution.as_ref().and_then(Substitution::preview),
+            Some("line 4\n- a=b, c=d\n+ b=a, c=d".to_string())
+        );
+
+        if let Some(substitution) = substitution.as_mut() {
+            substitution.skip();
+            substitution.accept();
+        }
+
+        assert_eq!(
+            substitution.as_ref().map(Substitution::summary),
+            Some("replaced 1 of 2 matches".to_string())
+        );
+        assert_eq!(
+            substitution.map(Substitution::into_replacements),
+            Some((
+                vec![Replacement {
+                    range: Range::new(Position::new(3, 5), Position::new(3, 8)),
+                    text: "d=c".to_string(),
+                }],
+                Vec::new()
+            ))
+        );
+    }
+
+    /// Replacements within a file shall carry the text of their match so that the file can be checked before it is written.
+    #[test]
+    fn file_replacements() {
+        let mut substitution = substitution("b+", "c", None);
+        let url = Url::parse("file:///a.rs").ok();
+
+        if let (Some(substitution), Some(url)) = (substitution.as_mut(), url.clone()) {
+            substitution.add_line(
+                &Location::File {
+                    path: "a.rs".to_string(),
+                    url,
+                },
+                1,
+                "\u{e9}bb",
+            );
+            substitution.accept();
+        }
+
+        assert_eq!(
+            substitution
+                .map(Substitution::into_replacements)
+                .map(|(_, files)| files),
+            url.map(|url| vec![(
+                url,
+                vec![FileReplacement {
+                    replacement: Replacement {
+                        range: Range::new(Position::new(1, 1), Position::new(1, 3)),
+                        text: "c".to_string(),
+                    },
+                    old_text: "bb".to_string(),
+                }]
+            )])
+        );
+    }
+
+    /// Matches found within the files of open documents shall be ignored.
+    #[test]
+    fn open_files() {
+        let mut substitution = substitution("a", "b", None);
+        let grep_match = |name: &str| GrepMatch {
+            path: name.to_string(),
+            url: Url::parse(&format!("file:///{}", name)).ok(),
+            line: 0,
+            text: "a".to_string(),
+            ranges: Vec::new(),
+        };
+
+        if let Some(substitution) = substitution.as_mut() {
+            substitution.start_search(Url::parse("file:///b.rs").ok().into_iter().collect());
+            substitution.add_matches(vec![grep_match("a.rs"), grep_match("b.rs")]);
+            substitution.add_matches(Vec::new());
+        }
+
+        assert_eq!(
+            substitution.as_ref().and_then(Substitution::preview),
+            Some("a.rs:1\n- a\n+ b".to_string())
+        );
+
+        if let Some(substitution) = substitution.as_mut() {
+            substitution.accept();
+        }
+
+        assert_eq!(substitution.as_ref().map(Substitution::is_done), Some(true));
+    }
+
+    /// Matches outside of the bounds shall be ignored.
+    #[test]
+    fn bounds() {
+        let mut substitution = substitution(
+            "a",
+            "b",
+            Some(Range::new(Position::new(0, 2), Position::new(1, 1))),
+        );
+
+        if let Some(substitution) = substitution.as_mut() {
+            substitution.add_line(&Location::Document, 0, "aaa");
+            substitution.add_line(&Location::Document, 1, "aaa");
+            substitution.accept();
+            substitution.accept();
+            substitution.accept();
+        }
+
+        assert_eq!(
+            substitution
+                .map(Substitution::into_replacements)
+                .map(|(document, _)| document.len()),
+            Some(2)
+        );
+    }
+}
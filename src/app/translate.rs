@@ -1,14 +1,18 @@
 //! Implements the functionality of interpreting an [`Input`] into [`Operation`]s.
 #![allow(clippy::pattern_type_mismatch)] // False positive.
 use {
+    super::{encoding::PositionEncoding, settings::ScrollKeys},
     crate::{
-        io::{Dimensions, File, Input, UserAction},
+        io::{Dimensions, File, GrepMatch, Input, PathCompletion, SavedFile, Unit, UserAction},
         orient,
     },
     core::fmt::{self, Debug},
-    crossterm::event::KeyCode,
+    crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind},
+    docuglot::Reception,
     enum_map::{enum_map, Enum, EnumMap},
-    lsp_types::{MessageType, ShowMessageRequestParams},
+    lsp_types::{
+        MessageType, PublishDiagnosticsParams, ShowMessageRequestParams, TextDocumentSyncKind,
+    },
     parse_display::Display as ParseDisplay,
 };
 
@@ -22,22 +26,233 @@ pub(crate) enum Operation {
     },
     /// Resets the application.
     Reset,
-    /// Confirms that the action is desired.
+    /// Asks to confirm that the action is desired.
     Confirm(ConfirmAction),
+    /// Accepts the action that is being confirmed.
+    Accept,
+    /// Declines the action that is being confirmed.
+    Skip,
     /// Quits the application.
     Quit,
     /// Open input box for a command.
     StartCommand,
     /// Input to input box.
     Collect(char),
+    /// Inputs pasted text to input box.
+    CollectText(String),
+    /// Edits the text of the input box.
+    EditCommand(LineEdit),
+    /// Replaces the text of the input box with a previously executed command.
+    RecallCommand(CommandRecall),
+    /// Completes the word before the cursor of the input box.
+    Complete,
+    /// Completes the word before the cursor of the input box to one of the listed paths.
+    CompletePaths(PathCompletion),
     /// Executes the current command.
     Execute,
     /// Creates a document from the file.
     CreateDoc(File),
-    /// Scrolls the document.
-    Scroll(orient::ScreenDirection),
+    /// Records that a document was written to its file.
+    MarkSaved(SavedFile),
+    /// Counts the character offsets of the positions exchanged with the language server in the code units of the encoding.
+    SetPositionEncoding(PositionEncoding),
+    /// Sends the changes to documents the way the language server syncs them.
+    SetTextDocumentSync(TextDocumentSyncKind),
+    /// Shows the diagnostics published for a file.
+    Diagnose(PublishDiagnosticsParams),
+    /// Scrolls the document towards the direction by the amount.
+    Scroll(orient::ScreenDirection, ScrollAmount),
     /// Changes the selection.
     ChangeSelection(SelectionMovement),
+    /// Focuses the pane at a cell of the page and selects the innermost symbol shown at the cell.
+    Click {
+        /// The row of the page.
+        row: Unit,
+        /// The column of the page.
+        column: Unit,
+    },
+    /// Selects the text between the cell that was last clicked and a cell of the page.
+    Drag {
+        /// The row of the page.
+        row: Unit,
+        /// The column of the page.
+        column: Unit,
+    },
+    /// Scrolls the pane at a cell of the page towards the direction, without focusing it.
+    Wheel {
+        /// The direction.
+        direction: orient::ScreenDirection,
+        /// The row of the page.
+        row: Unit,
+        /// The column of the page.
+        column: Unit,
+    },
+    /// Starts inserting text.
+    StartInsert(InsertPlacement),
+    /// Inserts text.
+    InsertText(String),
+    /// Deletes the character before the insertion point.
+    DeleteBackward,
+    /// Stops inserting text.
+    EndInsert,
+    /// Reverts the most recent change.
+    Undo,
+    /// Reapplies the most recently reverted change.
+    Redo,
+    /// Selects the register used by the next yank, delete or put.
+    SelectRegister(char),
+    /// Copies the selection into a register.
+    Yank,
+    /// Moves the selection into a register.
+    Delete,
+    /// Inserts the text of a register relative to the selection.
+    Put(InsertPlacement),
+    /// Inserts the text read from the system clipboard.
+    PutClipboard(String),
+    /// Opens the file finder.
+    StartPick,
+    /// Inputs text to the query of the file finder.
+    InputQuery(String),
+    /// Edits the query of the file finder.
+    EditQuery(LineEdit),
+    /// Moves the selection of the file finder.
+    MovePick(ListMovement),
+    /// Opens the file selected by the file finder.
+    Pick,
+    /// Adds indexed paths to the file finder.
+    AddPickPaths(Vec<String>),
+    /// Adds the lines that match a search to the result list.
+    AddResults(Vec<GrepMatch>),
+    /// Moves the selection of the result list.
+    MoveResult(ListMovement),
+    /// Opens the file of the selected result and selects the match.
+    Jump,
+    /// Opens the input box for a search of the document towards the direction.
+    StartSearch(SearchDirection),
+    /// Inputs text to the pattern of the search.
+    InputSearch(String),
+    /// Edits the pattern of the search.
+    EditSearch(LineEdit),
+    /// Stops editing the search, keeping the selected match.
+    EndSearch,
+    /// Selects the next match of the search towards the direction.
+    FindMatch(SearchDirection),
+    /// Splits the focused pane, showing its document in a new pane that is focused.
+    SplitPane(SplitDirection),
+    /// Focuses the pane next to the focused pane towards the direction.
+    FocusPane(orient::ScreenDirection),
+    /// Changes the size of the focused pane.
+    ResizePane(PaneResize),
+    /// Closes the focused pane.
+    ClosePane,
+    /// Opens a new tab after the shown tab, showing the document of the focused pane.
+    NewTab,
+    /// Shows the tab next to the shown tab in the tab strip.
+    SwitchTab(ListMovement),
+    /// Closes the shown tab.
+    CloseTab,
+}
+
+/// Describes where text is inserted relative to the selection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum InsertPlacement {
+    /// Inserts text at the start of the selection.
+    Before,
+    /// Inserts text at the end of the selection.
+    After,
+}
+
+/// Describes an edit of a single line of text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum LineEdit {
+    /// Moves the cursor back one char.
+    MoveLeft,
+    /// Moves the cursor forward one char.
+    MoveRight,
+    /// Moves the cursor to the start of the previous word.
+    MoveWordLeft,
+    /// Moves the cursor to the end of the next word.
+    MoveWordRight,
+    /// Moves the cursor to the start of the line.
+    MoveStart,
+    /// Moves the cursor to the end of the line.
+    MoveEnd,
+    /// Deletes the char before the cursor.
+    DeleteBackward,
+    /// Deletes the char after the cursor.
+    DeleteForward,
+    /// Deletes the word before the cursor.
+    DeleteWordBackward,
+    /// Deletes all text before the cursor.
+    DeleteToStart,
+    /// Deletes all text after the cursor.
+    DeleteToEnd,
+}
+
+/// Describes how the selection of a list moves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ListMovement {
+    /// Selects the item above the selected item.
+    Previous,
+    /// Selects the item below the selected item.
+    Next,
+}
+
+/// Describes how far the document is scrolled.
+#[derive(Clone, Copy, Debug, Enum, PartialEq)]
+pub(crate) enum ScrollAmount {
+    /// The number of rows or columns set by the `scroll` option.
+    Step,
+    /// A single row or column.
+    Line,
+    /// Half of the rows or columns that show the document.
+    HalfPage,
+    /// All of the rows or columns that show the document.
+    Page,
+    /// As far as the document can be scrolled, such as to its top or bottom.
+    Edge,
+}
+
+/// Describes the direction in which the document is searched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SearchDirection {
+    /// Searches towards the end of the document.
+    Forward,
+    /// Searches towards the start of the document.
+    Backward,
+}
+
+/// Describes where a new pane is placed relative to the pane that is split.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SplitDirection {
+    /// Places the new pane below the split pane.
+    Below,
+    /// Places the new pane to the right of the split pane.
+    Right,
+}
+
+/// Describes how the size of a pane changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PaneResize {
+    /// Adds rows to the pane.
+    Taller,
+    /// Removes rows from the pane.
+    Shorter,
+    /// Adds columns to the pane.
+    Wider,
+    /// Removes columns from the pane.
+    Narrower,
+}
+
+/// Describes which previously executed command is recalled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CommandRecall {
+    /// Recalls the command before the currently recalled command.
+    Previous,
+    /// Recalls the command after the currently recalled command.
+    Next,
+    /// Recalls the command before the currently recalled command that starts with the text before the cursor.
+    SearchBackward,
 }
 
 /// Describes the movement of a selection.
@@ -54,15 +269,20 @@ pub(crate) enum SelectionMovement {
 }
 
 /// Signifies actions that require a confirmation prior to their execution.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum ConfirmAction {
     /// Quit the application.
     Quit,
+    /// Replace a match of a substitution.
+    Replace,
 }
 
 impl fmt::Display for ConfirmAction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "You have input that you want to quit the application.\nPlease confirm this action by pressing `y`. To cancel this action, press any other key.")
+        match *self {
+            Self::Quit => write!(f, "You have input that you want to quit the application.\nPlease confirm this action by pressing `y`. To cancel this action, press any other key."),
+            Self::Replace => write!(f, "Press `y` to replace this match or `n` to skip it. To stop replacing, press any other key."),
+        }
     }
 }
 
@@ -106,9 +326,31 @@ pub(crate) struct Interpreter {
     mode: Mode,
     /// Map of [`ModeInterpreter`]s.
     map: EnumMap<Mode, &'static dyn ModeInterpreter>,
+    /// The keys that scroll the document in [`Mode::View`].
+    scroll_keys: ScrollKeys,
 }
 
 impl Interpreter {
+    /// Sets the keys that scroll the document in [`Mode::View`] to `scroll_keys`.
+    pub(crate) fn set_scroll_keys(&mut self, scroll_keys: ScrollKeys) {
+        self.scroll_keys = scroll_keys;
+    }
+
+    /// Switches to [`Mode::List`] so that the result list can be navigated.
+    pub(crate) fn start_list(&mut self) {
+        self.mode = Mode::List;
+    }
+
+    /// Switches to [`Mode::Replace`] so that each replacement can be confirmed.
+    pub(crate) fn start_replace(&mut self) {
+        self.mode = Mode::Replace;
+    }
+
+    /// Switches to [`Mode::View`] once every replacement has been confirmed.
+    pub(crate) fn end_replace(&mut self) {
+        self.mode = Mode::View;
+    }
+
     /// Returns the [`Operation`] that maps to `input` given the current [`Mode`].
     pub(crate) fn translate(&mut self, input: Input) -> Option<Operation> {
         let mut output = Output::new();
@@ -117,14 +359,40 @@ impl Interpreter {
             Input::File(file) => {
                 output.add_op(Operation::CreateDoc(file));
             }
-            Input::Lsp(_reception) => {
-                // Processing of receptions to be added here.
+            Input::Lsp(Reception::PositionEncoding(name)) => match name.parse() {
+                Ok(encoding) => output.add_op(Operation::SetPositionEncoding(encoding)),
+                // The server only chooses from the offered encodings.
+                Err(error) => log::warn!("{}", error),
+            },
+            Input::Lsp(Reception::TextDocumentSync(sync)) => {
+                output.add_op(Operation::SetTextDocumentSync(sync));
+            }
+            Input::Lsp(Reception::Diagnostics(params)) => {
+                output.add_op(Operation::Diagnose(params));
+            }
+            Input::Lsp(Reception::DocumentSymbols(_)) => {
+                // Processing of document symbols to be added here.
+            }
+            Input::Clipboard(text) => {
+                output.add_op(Operation::PutClipboard(text));
+            }
+            Input::Saved(file) => {
+                output.add_op(Operation::MarkSaved(file));
+            }
+            Input::Paths(completion) => {
+                output.add_op(Operation::CompletePaths(completion));
+            }
+            Input::Index(paths) => {
+                output.add_op(Operation::AddPickPaths(paths));
+            }
+            Input::Grep(matches) => {
+                output.add_op(Operation::AddResults(matches));
             }
             Input::User(user_input) => {
                 #[allow(clippy::indexing_slicing)] // EnumMap guarantees that index is in bounds.
                 let mode_interpreter = self.map[self.mode];
 
-                output = mode_interpreter.decode(user_input);
+                output = mode_interpreter.decode(user_input, &self.scroll_keys);
             }
         }
 
@@ -142,8 +410,22 @@ impl Default for Interpreter {
         static VIEW_INTERPRETER: ViewInterpreter = ViewInterpreter::new();
         /// The [`ModeInterpreter`] for [`Mode::Confirm`].
         static CONFIRM_INTERPRETER: ConfirmInterpreter = ConfirmInterpreter::new();
+        /// The [`ModeInterpreter`] for [`Mode::Replace`].
+        static REPLACE_INTERPRETER: ReplaceInterpreter = ReplaceInterpreter::new();
         /// The [`ModeInterpreter`] for [`Mode::Collect`].
         static COLLECT_INTERPRETER: CollectInterpreter = CollectInterpreter::new();
+        /// The [`ModeInterpreter`] for [`Mode::Insert`].
+        static INSERT_INTERPRETER: InsertInterpreter = InsertInterpreter::new();
+        /// The [`ModeInterpreter`] for [`Mode::Register`].
+        static REGISTER_INTERPRETER: RegisterInterpreter = RegisterInterpreter::new();
+        /// The [`ModeInterpreter`] for [`Mode::Pick`].
+        static PICK_INTERPRETER: PickInterpreter = PickInterpreter::new();
+        /// The [`ModeInterpreter`] for [`Mode::List`].
+        static LIST_INTERPRETER: ListInterpreter = ListInterpreter::new();
+        /// The [`ModeInterpreter`] for [`Mode::Search`].
+        static SEARCH_INTERPRETER: SearchInterpreter = SearchInterpreter::new();
+        /// The [`ModeInterpreter`] for [`Mode::Pane`].
+        static PANE_INTERPRETER: PaneInterpreter = PaneInterpreter::new();
 
         // Required to establish value type in enum_map.
         let view_interpreter: &dyn ModeInterpreter = &VIEW_INTERPRETER;
@@ -152,9 +434,17 @@ impl Default for Interpreter {
             map: enum_map! {
                 Mode::View => view_interpreter,
                 Mode::Confirm => &CONFIRM_INTERPRETER,
+                Mode::Replace => &REPLACE_INTERPRETER,
                 Mode::Collect => &COLLECT_INTERPRETER,
+                Mode::Insert => &INSERT_INTERPRETER,
+                Mode::Register => &REGISTER_INTERPRETER,
+                Mode::Pick => &PICK_INTERPRETER,
+                Mode::List => &LIST_INTERPRETER,
+                Mode::Search => &SEARCH_INTERPRETER,
+                Mode::Pane => &PANE_INTERPRETER,
             },
             mode: Mode::default(),
+            scroll_keys: ScrollKeys::default(),
         }
     }
 }
@@ -167,8 +457,22 @@ enum Mode {
     View,
     /// Confirms the user's action
     Confirm,
+    /// Confirms each replacement of a substitution.
+    Replace,
     /// Collects input from the user.
     Collect,
+    /// Inserts text into the current file.
+    Insert,
+    /// Selects a register.
+    Register,
+    /// Picks a file to open.
+    Pick,
+    /// Navigates the result list.
+    List,
+    /// Edits a search of the document.
+    Search,
+    /// Arranges the panes.
+    Pane,
 }
 
 impl Default for Mode {
@@ -214,8 +518,8 @@ impl Output {
 
 /// Defines the functionality to convert [`Input`] to [`Output`].
 trait ModeInterpreter: Debug {
-    /// Converts `input` to [`Operation`]s.
-    fn decode(&self, input: UserAction) -> Output;
+    /// Converts `input` to [`Operation`]s, scrolling by the keys of `scroll_keys` where the mode scrolls.
+    fn decode(&self, input: UserAction, scroll_keys: &ScrollKeys) -> Output;
 }
 
 /// The [`ModeInterpreter`] for [`Mode::View`].
@@ -229,7 +533,15 @@ impl ViewInterpreter {
     }
 
     /// Converts `output` appropriate to `key`.
-    fn decode_key(key: KeyCode, output: &mut Output) {
+    ///
+    /// A key bound by `scroll_keys` scrolls instead of doing what it otherwise would.
+    #[allow(clippy::too_many_lines)] // Splitting the match over keys would only obscure it.
+    fn decode_key(key: KeyCode, scroll_keys: &ScrollKeys, output: &mut Output) {
+        if let Some((direction, amount)) = scroll_keys.scroll(key) {
+            output.add_op(Operation::Scroll(direction, amount));
+            return;
+        }
+
         match key {
             KeyCode::Esc => {
                 output.add_op(Operation::Reset);
@@ -243,14 +555,6 @@ impl ViewInterpreter {
                 output.add_op(Operation::StartCommand);
                 output.set_mode(Mode::Collect);
             }
-            // TODO: Change this to Space.
-            KeyCode::Char('d') => {
-                output.add_op(Operation::Scroll(orient::ScreenDirection::Down));
-            }
-            // TODO: Change this to Shift+Space.
-            KeyCode::Char('u') => {
-                output.add_op(Operation::Scroll(orient::ScreenDirection::Up));
-            }
             KeyCode::Char('h') => {
                 output.add_op(Operation::ChangeSelection(SelectionMovement::Ascend));
             }
@@ -263,39 +567,139 @@ impl ViewInterpreter {
             KeyCode::Char('l') => {
                 output.add_op(Operation::ChangeSelection(SelectionMovement::Descend));
             }
-            KeyCode::Backspace
-            | KeyCode::Enter
-            | KeyCode::Left
+            KeyCode::Char('i') => {
+                output.add_op(Operation::StartInsert(InsertPlacement::Before));
+                output.set_mode(Mode::Insert);
+            }
+            KeyCode::Char('a') => {
+                output.add_op(Operation::StartInsert(InsertPlacement::After));
+                output.set_mode(Mode::Insert);
+            }
+            KeyCode::Char('z') => {
+                output.add_op(Operation::Undo);
+            }
+            KeyCode::Char('Z') => {
+                output.add_op(Operation::Redo);
+            }
+            KeyCode::Char('"') => {
+                output.set_mode(Mode::Register);
+            }
+            KeyCode::Char('y') => {
+                output.add_op(Operation::Yank);
+            }
+            KeyCode::Char('x') => {
+                output.add_op(Operation::Delete);
+            }
+            KeyCode::Char('p') => {
+                output.add_op(Operation::Put(InsertPlacement::After));
+            }
+            KeyCode::Char('P') => {
+                output.add_op(Operation::Put(InsertPlacement::Before));
+            }
+            KeyCode::Char('f') => {
+                output.add_op(Operation::StartPick);
+                output.set_mode(Mode::Pick);
+            }
+            KeyCode::Char('/') => {
+                output.add_op(Operation::StartSearch(SearchDirection::Forward));
+                output.set_mode(Mode::Search);
+            }
+            KeyCode::Char('?') => {
+                output.add_op(Operation::StartSearch(SearchDirection::Backward));
+                output.set_mode(Mode::Search);
+            }
+            KeyCode::Char('n') => {
+                output.add_op(Operation::FindMatch(SearchDirection::Forward));
+            }
+            KeyCode::Char('N') => {
+                output.add_op(Operation::FindMatch(SearchDirection::Backward));
+            }
+            KeyCode::Char('s') => {
+                output.set_mode(Mode::Pane);
+            }
+            // The scroll keys are decoded from the scroll keys setting.
+            KeyCode::Left
             | KeyCode::Right
             | KeyCode::Up
             | KeyCode::Down
-            | KeyCode::Home
-            | KeyCode::End
             | KeyCode::PageUp
             | KeyCode::PageDown
+            | KeyCode::Home
+            | KeyCode::End
+            | KeyCode::Backspace
+            | KeyCode::Enter
             | KeyCode::Tab
             | KeyCode::BackTab
             | KeyCode::Delete
             | KeyCode::Insert
             | KeyCode::F(..)
             | KeyCode::Null
+            | KeyCode::CapsLock
+            | KeyCode::ScrollLock
+            | KeyCode::NumLock
+            | KeyCode::PrintScreen
+            | KeyCode::Pause
+            | KeyCode::Menu
+            | KeyCode::KeypadBegin
+            | KeyCode::Media(..)
+            | KeyCode::Modifier(..)
             | KeyCode::Char(..) => {}
         }
     }
+
+    /// Converts `output` appropriate to the mouse doing `kind` at `row` and `column` of the page.
+    ///
+    /// Only the left button selects; the wheel scrolls the pane under the mouse.
+    fn decode_mouse(kind: MouseEventKind, row: Unit, column: Unit, output: &mut Output) {
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                output.add_op(Operation::Click { row, column });
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                output.add_op(Operation::Drag { row, column });
+            }
+            MouseEventKind::ScrollDown => {
+                output.add_op(Operation::Wheel {
+                    direction: orient::ScreenDirection::Down,
+                    row,
+                    column,
+                });
+            }
+            MouseEventKind::ScrollUp => {
+                output.add_op(Operation::Wheel {
+                    direction: orient::ScreenDirection::Up,
+                    row,
+                    column,
+                });
+            }
+            MouseEventKind::Down(MouseButton::Right | MouseButton::Middle)
+            | MouseEventKind::Drag(MouseButton::Right | MouseButton::Middle)
+            | MouseEventKind::Up(..)
+            | MouseEventKind::Moved => {}
+        }
+    }
 }
 
 impl ModeInterpreter for ViewInterpreter {
-    fn decode(&self, input: UserAction) -> Output {
+    fn decode(&self, input: UserAction, scroll_keys: &ScrollKeys) -> Output {
         let mut output = Output::new();
 
         match input {
             UserAction::Key { code, .. } => {
-                Self::decode_key(code, &mut output);
+                Self::decode_key(code, scroll_keys, &mut output);
+            }
+            UserAction::Mouse {
+                kind,
+                row: Some(row),
+                column,
+            } => {
+                Self::decode_mouse(kind, row, column, &mut output);
             }
             UserAction::Resize { dimensions } => {
                 output.add_op(Operation::Resize { dimensions });
             }
-            UserAction::Mouse => {}
+            // Pasted text is ignored so that it is not interpreted as commands.
+            UserAction::Mouse { row: None, .. } | UserAction::Paste(..) | UserAction::Focus => {}
         }
 
         output
@@ -314,7 +718,44 @@ impl ConfirmInterpreter {
 }
 
 impl ModeInterpreter for ConfirmInterpreter {
-    fn decode(&self, input: UserAction) -> Output {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
+        let mut output = Output::new();
+
+        match input {
+            UserAction::Key {
+                code: KeyCode::Char('y'),
+                ..
+            } => {
+                // The action may be refused, such as quitting with unsaved changes.
+                output.add_op(Operation::Accept);
+                output.set_mode(Mode::View);
+            }
+            UserAction::Key { .. }
+            | UserAction::Mouse { .. }
+            | UserAction::Resize { .. }
+            | UserAction::Paste(..)
+            | UserAction::Focus => {
+                output.reset();
+            }
+        }
+
+        output
+    }
+}
+
+/// The [`ModeInterpreter`] for [`Mode::Replace`].
+#[derive(Clone, Debug)]
+struct ReplaceInterpreter {}
+
+impl ReplaceInterpreter {
+    /// Creates a new `ReplaceInterpreter`.
+    const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ModeInterpreter for ReplaceInterpreter {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
         let mut output = Output::new();
 
         match input {
@@ -322,9 +763,19 @@ impl ModeInterpreter for ConfirmInterpreter {
                 code: KeyCode::Char('y'),
                 ..
             } => {
-                output.add_op(Operation::Quit);
+                output.add_op(Operation::Accept);
+            }
+            UserAction::Key {
+                code: KeyCode::Char('n'),
+                ..
+            } => {
+                output.add_op(Operation::Skip);
             }
-            UserAction::Key { .. } | UserAction::Mouse | UserAction::Resize { .. } => {
+            UserAction::Key { .. }
+            | UserAction::Mouse { .. }
+            | UserAction::Resize { .. }
+            | UserAction::Paste(..)
+            | UserAction::Focus => {
                 output.reset();
             }
         }
@@ -342,10 +793,46 @@ impl CollectInterpreter {
     const fn new() -> Self {
         Self {}
     }
+
+    /// Returns the [`LineEdit`] performed by `code` when `modifiers` are held.
+    ///
+    /// Ctrl and Alt shortcuts follow those of readline.
+    fn line_edit(code: KeyCode, modifiers: KeyModifiers) -> Option<LineEdit> {
+        match (
+            code,
+            modifiers.contains(KeyModifiers::CONTROL),
+            modifiers.contains(KeyModifiers::ALT),
+        ) {
+            (KeyCode::Left, false, false) | (KeyCode::Char('b'), true, false) => {
+                Some(LineEdit::MoveLeft)
+            }
+            (KeyCode::Right, false, false) | (KeyCode::Char('f'), true, false) => {
+                Some(LineEdit::MoveRight)
+            }
+            (KeyCode::Left, true, _) | (KeyCode::Char('b'), false, true) => {
+                Some(LineEdit::MoveWordLeft)
+            }
+            (KeyCode::Right, true, _) | (KeyCode::Char('f'), false, true) => {
+                Some(LineEdit::MoveWordRight)
+            }
+            (KeyCode::Home, ..) | (KeyCode::Char('a'), true, false) => Some(LineEdit::MoveStart),
+            (KeyCode::End, ..) | (KeyCode::Char('e'), true, false) => Some(LineEdit::MoveEnd),
+            (KeyCode::Backspace, ..) | (KeyCode::Char('h'), true, false) => {
+                Some(LineEdit::DeleteBackward)
+            }
+            (KeyCode::Delete, ..) | (KeyCode::Char('d'), true, false) => {
+                Some(LineEdit::DeleteForward)
+            }
+            (KeyCode::Char('w'), true, false) => Some(LineEdit::DeleteWordBackward),
+            (KeyCode::Char('u'), true, false) => Some(LineEdit::DeleteToStart),
+            (KeyCode::Char('k'), true, false) => Some(LineEdit::DeleteToEnd),
+            _ => None,
+        }
+    }
 }
 
 impl ModeInterpreter for CollectInterpreter {
-    fn decode(&self, input: UserAction) -> Output {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
         let mut output = Output::new();
 
         match input {
@@ -362,131 +849,872 @@ impl ModeInterpreter for CollectInterpreter {
                 output.set_mode(Mode::View);
             }
             UserAction::Key {
-                code: KeyCode::Char(c),
+                code: KeyCode::Up, ..
+            } => {
+                output.add_op(Operation::RecallCommand(CommandRecall::Previous));
+            }
+            UserAction::Key {
+                code: KeyCode::Down,
                 ..
             } => {
-                output.add_op(Operation::Collect(c));
+                output.add_op(Operation::RecallCommand(CommandRecall::Next));
+            }
+            UserAction::Key {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::CONTROL,
+            } => {
+                output.add_op(Operation::RecallCommand(CommandRecall::SearchBackward));
             }
-            UserAction::Key { .. } | UserAction::Mouse | UserAction::Resize { .. } => {}
+            UserAction::Key {
+                code: KeyCode::Tab, ..
+            } => {
+                output.add_op(Operation::Complete);
+            }
+            UserAction::Key { code, modifiers } => match (Self::line_edit(code, modifiers), code) {
+                (Some(edit), _) => {
+                    output.add_op(Operation::EditCommand(edit));
+                }
+                (None, KeyCode::Char(c))
+                    if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    output.add_op(Operation::Collect(c));
+                }
+                (None, _) => {}
+            },
+            UserAction::Paste(text) => {
+                output.add_op(Operation::CollectText(text));
+            }
+            UserAction::Mouse { .. } | UserAction::Resize { .. } | UserAction::Focus => {}
         }
 
         output
     }
 }
 
-/// Testing of the translate module.
-#[cfg(test)]
-mod test {
-    use {super::*, crossterm::event::KeyModifiers};
-
-    /// Tests decoding user input while the [`Interpreter`] is in [`Mode::View`].
-    mod view {
-        use super::*;
-
-        fn view_mode() -> Interpreter {
-            Interpreter::default()
-        }
-
-        /// The `Ctrl-w` key shall confirm the user wants to quit.
-        #[test]
-        fn quit() {
-            let mut int = view_mode();
+/// The [`ModeInterpreter`] for [`Mode::Insert`].
+#[derive(Clone, Debug)]
+struct InsertInterpreter {}
 
-            assert_eq!(
-                int.translate(Input::User(UserAction::Key {
-                    code: KeyCode::Char('w'),
-                    modifiers: KeyModifiers::CONTROL,
-                })),
-                Some(Operation::Confirm(ConfirmAction::Quit))
-            );
-            assert_eq!(int.mode, Mode::Confirm);
-        }
+impl InsertInterpreter {
+    /// Creates a new `InsertInterpreter`.
+    const fn new() -> Self {
+        Self {}
     }
+}
 
-    /// Tests decoding user input while in the Confirm mode.
-    mod confirm {
-        use super::*;
-
-        fn confirm_mode() -> Interpreter {
-            let mut int = Interpreter::default();
-            int.mode = Mode::Confirm;
-            int
-        }
-
-        /// The `y` key shall confirm the action.
-        #[test]
-        fn confirm() {
-            let mut int = confirm_mode();
+impl ModeInterpreter for InsertInterpreter {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
+        let mut output = Output::new();
 
-            assert_eq!(
-                int.translate(Input::User(UserAction::Key {
-                    code: KeyCode::Char('y'),
-                    modifiers: KeyModifiers::empty(),
-                })),
-                Some(Operation::Quit)
-            );
+        match input {
+            UserAction::Key { code, .. } => match code {
+                KeyCode::Esc => {
+                    output.add_op(Operation::EndInsert);
+                    output.set_mode(Mode::View);
+                }
+                KeyCode::Enter => {
+                    output.add_op(Operation::InsertText("\n".to_string()));
+                }
+                KeyCode::Tab => {
+                    output.add_op(Operation::InsertText("\t".to_string()));
+                }
+                KeyCode::Backspace => {
+                    output.add_op(Operation::DeleteBackward);
+                }
+                KeyCode::Char(c) => {
+                    output.add_op(Operation::InsertText(c.to_string()));
+                }
+                KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Up
+                | KeyCode::Down
+                | KeyCode::Home
+                | KeyCode::End
+                | KeyCode::PageUp
+                | KeyCode::PageDown
+                | KeyCode::BackTab
+                | KeyCode::Delete
+                | KeyCode::Insert
+                | KeyCode::F(..)
+                | KeyCode::Null
+                | KeyCode::CapsLock
+                | KeyCode::ScrollLock
+                | KeyCode::NumLock
+                | KeyCode::PrintScreen
+                | KeyCode::Pause
+                | KeyCode::Menu
+                | KeyCode::KeypadBegin
+                | KeyCode::Media(..)
+                | KeyCode::Modifier(..) => {}
+            },
+            // Pasted text is inserted as a single edit.
+            UserAction::Paste(text) => {
+                output.add_op(Operation::InsertText(text));
+            }
+            UserAction::Resize { dimensions } => {
+                output.add_op(Operation::Resize { dimensions });
+            }
+            UserAction::Mouse { .. } | UserAction::Focus => {}
         }
 
-        /// Any other key shall cancel the action, resetting the application to View mode.
-        #[test]
-        fn cancel() {
-            let mut int = confirm_mode();
-
-            assert_eq!(
-                int.translate(Input::User(UserAction::Key {
-                    code: KeyCode::Char('n'),
-                    modifiers: KeyModifiers::empty(),
-                })),
-                Some(Operation::Reset)
-            );
-            assert_eq!(int.mode, Mode::View);
+        output
+    }
+}
 
-            int = confirm_mode();
+/// The [`ModeInterpreter`] for [`Mode::Register`].
+#[derive(Clone, Debug)]
+struct RegisterInterpreter {}
 
-            assert_eq!(
-                int.translate(Input::User(UserAction::Key {
-                    code: KeyCode::Char('1'),
-                    modifiers: KeyModifiers::empty(),
-                })),
-                Some(Operation::Reset)
-            );
-            assert_eq!(int.mode, Mode::View);
-        }
+impl RegisterInterpreter {
+    /// Creates a new `RegisterInterpreter`.
+    const fn new() -> Self {
+        Self {}
     }
+}
 
-    /// Tests decoding user input while mode is [`Mode::Collect`].
-    #[cfg(test)]
-    mod collect {
-        use super::*;
+impl ModeInterpreter for RegisterInterpreter {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
+        let mut output = Output::new();
 
-        fn collect_mode() -> Interpreter {
-            let mut int = Interpreter::default();
-            int.mode = Mode::Collect;
-            int
+        match input {
+            UserAction::Key {
+                code: KeyCode::Char(c),
+                ..
+            } => {
+                output.add_op(Operation::SelectRegister(c));
+                output.set_mode(Mode::View);
+            }
+            UserAction::Resize { dimensions } => {
+                output.add_op(Operation::Resize { dimensions });
+            }
+            UserAction::Key { .. }
+            | UserAction::Mouse { .. }
+            | UserAction::Paste(..)
+            | UserAction::Focus => {
+                output.reset();
+            }
         }
 
-        /// The `Esc` key shall return to [`Mode::View`].
-        #[test]
-        fn reset() {
-            let mut int = collect_mode();
+        output
+    }
+}
 
-            assert_eq!(
-                int.translate(Input::User(UserAction::Key {
-                    code: KeyCode::Esc,
-                    modifiers: KeyModifiers::empty(),
-                })),
-                Some(Operation::Reset)
-            );
-            assert_eq!(int.mode, Mode::View);
-        }
+/// The [`ModeInterpreter`] for [`Mode::Pick`].
+#[derive(Clone, Debug)]
+struct PickInterpreter {}
 
-        /// All char keys shall be collected.
-        #[test]
-        fn collect() {
-            let mut int = collect_mode();
+impl PickInterpreter {
+    /// Creates a new `PickInterpreter`.
+    const fn new() -> Self {
+        Self {}
+    }
+}
 
-            assert_eq!(
-                int.translate(Input::User(UserAction::Key {
+impl ModeInterpreter for PickInterpreter {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
+        let mut output = Output::new();
+
+        match input {
+            UserAction::Key {
+                code: KeyCode::Esc, ..
+            } => {
+                output.reset();
+            }
+            UserAction::Key {
+                code: KeyCode::Enter,
+                ..
+            } => {
+                output.add_op(Operation::Pick);
+                output.set_mode(Mode::View);
+            }
+            UserAction::Key {
+                code: KeyCode::Up | KeyCode::BackTab,
+                ..
+            }
+            | UserAction::Key {
+                code: KeyCode::Char('p'),
+                modifiers: KeyModifiers::CONTROL,
+            } => {
+                output.add_op(Operation::MovePick(ListMovement::Previous));
+            }
+            UserAction::Key {
+                code: KeyCode::Down | KeyCode::Tab,
+                ..
+            }
+            | UserAction::Key {
+                code: KeyCode::Char('n'),
+                modifiers: KeyModifiers::CONTROL,
+            } => {
+                output.add_op(Operation::MovePick(ListMovement::Next));
+            }
+            UserAction::Key { code, modifiers } => {
+                match (CollectInterpreter::line_edit(code, modifiers), code) {
+                    (Some(edit), _) => {
+                        output.add_op(Operation::EditQuery(edit));
+                    }
+                    (None, KeyCode::Char(c))
+                        if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                    {
+                        output.add_op(Operation::InputQuery(c.to_string()));
+                    }
+                    (None, _) => {}
+                }
+            }
+            UserAction::Paste(text) => {
+                output.add_op(Operation::InputQuery(text));
+            }
+            UserAction::Resize { dimensions } => {
+                output.add_op(Operation::Resize { dimensions });
+            }
+            UserAction::Mouse { .. } | UserAction::Focus => {}
+        }
+
+        output
+    }
+}
+
+/// The [`ModeInterpreter`] for [`Mode::List`].
+#[derive(Clone, Debug)]
+struct ListInterpreter {}
+
+impl ListInterpreter {
+    /// Creates a new `ListInterpreter`.
+    const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ModeInterpreter for ListInterpreter {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
+        let mut output = Output::new();
+
+        match input {
+            UserAction::Key { code, modifiers } => match code {
+                KeyCode::Esc => {
+                    output.reset();
+                }
+                KeyCode::Enter => {
+                    output.add_op(Operation::Jump);
+                    output.set_mode(Mode::View);
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    output.add_op(Operation::MoveResult(ListMovement::Previous));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    output.add_op(Operation::MoveResult(ListMovement::Next));
+                }
+                KeyCode::Char('p') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    output.add_op(Operation::MoveResult(ListMovement::Previous));
+                }
+                KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    output.add_op(Operation::MoveResult(ListMovement::Next));
+                }
+                KeyCode::Backspace
+                | KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Home
+                | KeyCode::End
+                | KeyCode::PageUp
+                | KeyCode::PageDown
+                | KeyCode::Tab
+                | KeyCode::BackTab
+                | KeyCode::Delete
+                | KeyCode::Insert
+                | KeyCode::F(..)
+                | KeyCode::Null
+                | KeyCode::CapsLock
+                | KeyCode::ScrollLock
+                | KeyCode::NumLock
+                | KeyCode::PrintScreen
+                | KeyCode::Pause
+                | KeyCode::Menu
+                | KeyCode::KeypadBegin
+                | KeyCode::Media(..)
+                | KeyCode::Modifier(..)
+                | KeyCode::Char(..) => {}
+            },
+            UserAction::Resize { dimensions } => {
+                output.add_op(Operation::Resize { dimensions });
+            }
+            UserAction::Mouse { .. } | UserAction::Paste(..) | UserAction::Focus => {}
+        }
+
+        output
+    }
+}
+
+/// The [`ModeInterpreter`] for [`Mode::Search`].
+#[derive(Clone, Debug)]
+struct SearchInterpreter {}
+
+impl SearchInterpreter {
+    /// Creates a new `SearchInterpreter`.
+    const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ModeInterpreter for SearchInterpreter {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
+        let mut output = Output::new();
+
+        match input {
+            UserAction::Key {
+                code: KeyCode::Esc, ..
+            } => {
+                output.reset();
+            }
+            UserAction::Key {
+                code: KeyCode::Enter,
+                ..
+            } => {
+                output.add_op(Operation::EndSearch);
+                output.set_mode(Mode::View);
+            }
+            UserAction::Key { code, modifiers } => {
+                match (CollectInterpreter::line_edit(code, modifiers), code) {
+                    (Some(edit), _) => {
+                        output.add_op(Operation::EditSearch(edit));
+                    }
+                    (None, KeyCode::Char(c))
+                        if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                    {
+                        output.add_op(Operation::InputSearch(c.to_string()));
+                    }
+                    (None, _) => {}
+                }
+            }
+            UserAction::Paste(text) => {
+                output.add_op(Operation::InputSearch(text));
+            }
+            UserAction::Resize { dimensions } => {
+                output.add_op(Operation::Resize { dimensions });
+            }
+            UserAction::Mouse { .. } | UserAction::Focus => {}
+        }
+
+        output
+    }
+}
+
+/// The [`ModeInterpreter`] for [`Mode::Pane`].
+#[derive(Clone, Debug)]
+struct PaneInterpreter {}
+
+impl PaneInterpreter {
+    /// Creates a new `PaneInterpreter`.
+    const fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the [`Operation`] performed by `key`.
+    const fn operation(key: KeyCode) -> Option<Operation> {
+        match key {
+            KeyCode::Char('s') => Some(Operation::SplitPane(SplitDirection::Below)),
+            KeyCode::Char('v') => Some(Operation::SplitPane(SplitDirection::Right)),
+            KeyCode::Char('h') | KeyCode::Left => {
+                Some(Operation::FocusPane(orient::ScreenDirection::Left))
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                Some(Operation::FocusPane(orient::ScreenDirection::Down))
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                Some(Operation::FocusPane(orient::ScreenDirection::Up))
+            }
+            KeyCode::Char('l') | KeyCode::Right => {
+                Some(Operation::FocusPane(orient::ScreenDirection::Right))
+            }
+            KeyCode::Char('+') => Some(Operation::ResizePane(PaneResize::Taller)),
+            KeyCode::Char('-') => Some(Operation::ResizePane(PaneResize::Shorter)),
+            KeyCode::Char('>') => Some(Operation::ResizePane(PaneResize::Wider)),
+            KeyCode::Char('<') => Some(Operation::ResizePane(PaneResize::Narrower)),
+            KeyCode::Char('c') => Some(Operation::ClosePane),
+            KeyCode::Char('t') => Some(Operation::NewTab),
+            KeyCode::Char('n') => Some(Operation::SwitchTab(ListMovement::Next)),
+            KeyCode::Char('p') => Some(Operation::SwitchTab(ListMovement::Previous)),
+            KeyCode::Char('x') => Some(Operation::CloseTab),
+            KeyCode::Backspace
+            | KeyCode::Enter
+            | KeyCode::Home
+            | KeyCode::End
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::Tab
+            | KeyCode::BackTab
+            | KeyCode::Delete
+            | KeyCode::Insert
+            | KeyCode::F(..)
+            | KeyCode::Esc
+            | KeyCode::Null
+            | KeyCode::CapsLock
+            | KeyCode::ScrollLock
+            | KeyCode::NumLock
+            | KeyCode::PrintScreen
+            | KeyCode::Pause
+            | KeyCode::Menu
+            | KeyCode::KeypadBegin
+            | KeyCode::Media(..)
+            | KeyCode::Modifier(..)
+            | KeyCode::Char(..) => None,
+        }
+    }
+}
+
+impl ModeInterpreter for PaneInterpreter {
+    fn decode(&self, input: UserAction, _: &ScrollKeys) -> Output {
+        let mut output = Output::new();
+
+        match input {
+            UserAction::Key { code, .. } => match Self::operation(code) {
+                Some(operation) => {
+                    output.add_op(operation);
+                    output.set_mode(Mode::View);
+                }
+                None => {
+                    output.reset();
+                }
+            },
+            UserAction::Resize { dimensions } => {
+                output.add_op(Operation::Resize { dimensions });
+            }
+            UserAction::Mouse { .. } | UserAction::Paste(..) | UserAction::Focus => {
+                output.reset();
+            }
+        }
+
+        output
+    }
+}
+
+/// Testing of the translate module.
+#[cfg(test)]
+mod test {
+    use {
+        super::{
+            super::settings::{Setting, Settings},
+            *,
+        },
+        crossterm::event::KeyModifiers,
+    };
+
+    /// Tests decoding user input while the [`Interpreter`] is in [`Mode::View`].
+    mod view {
+        use super::*;
+
+        fn view_mode() -> Interpreter {
+            Interpreter::default()
+        }
+
+        /// The `Ctrl-w` key shall confirm the user wants to quit.
+        #[test]
+        fn quit() {
+            let mut int = view_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('w'),
+                    modifiers: KeyModifiers::CONTROL,
+                })),
+                Some(Operation::Confirm(ConfirmAction::Quit))
+            );
+            assert_eq!(int.mode, Mode::Confirm);
+        }
+
+        /// The page keys shall scroll by a page and the `Home` and `End` keys shall scroll to the top and bottom.
+        #[test]
+        fn scroll() {
+            let mut int = view_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::PageDown,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Scroll(
+                    orient::ScreenDirection::Down,
+                    ScrollAmount::Page
+                ))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Home,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Scroll(
+                    orient::ScreenDirection::Up,
+                    ScrollAmount::Edge
+                ))
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// A scroll bound to another key shall scroll by that key instead of the key it was bound to.
+        #[test]
+        fn rebind_scroll() {
+            let mut int = view_mode();
+            let mut settings = Settings::default();
+
+            if let Ok(setting) = Setting::parse("scrollkey", "down page J") {
+                settings.apply(setting);
+            }
+
+            int.set_scroll_keys(settings.scroll_keys);
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('J'),
+                    modifiers: KeyModifiers::SHIFT,
+                })),
+                Some(Operation::Scroll(
+                    orient::ScreenDirection::Down,
+                    ScrollAmount::Page
+                ))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::PageDown,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                None
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::PageUp,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Scroll(
+                    orient::ScreenDirection::Up,
+                    ScrollAmount::Page
+                ))
+            );
+        }
+
+        /// Pressing the left button shall click and the wheel shall scroll.
+        #[test]
+        fn mouse() {
+            let mut int = view_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Mouse {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    row: Some(Unit::from(3)),
+                    column: Unit::from(7),
+                })),
+                Some(Operation::Click {
+                    row: Unit::from(3),
+                    column: Unit::from(7),
+                })
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Mouse {
+                    kind: MouseEventKind::ScrollUp,
+                    row: Some(Unit::from(3)),
+                    column: Unit::from(7),
+                })),
+                Some(Operation::Wheel {
+                    direction: orient::ScreenDirection::Up,
+                    row: Unit::from(3),
+                    column: Unit::from(7),
+                })
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Mouse {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    row: None,
+                    column: Unit::from(7),
+                })),
+                None
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// Pasted text shall not be interpreted as commands.
+        #[test]
+        fn paste() {
+            let mut int = view_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Paste("w".to_string()))),
+                None
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// The `z` key shall undo and the `Z` key shall redo.
+        #[test]
+        fn undo_redo() {
+            let mut int = view_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('z'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Undo)
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('Z'),
+                    modifiers: KeyModifiers::SHIFT,
+                })),
+                Some(Operation::Redo)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+
+    /// Tests decoding user input while mode is [`Mode::Insert`].
+    mod insert {
+        use super::*;
+
+        fn insert_mode() -> Interpreter {
+            Interpreter {
+                mode: Mode::Insert,
+                ..Interpreter::default()
+            }
+        }
+
+        /// The `i` key in [`Mode::View`] shall start inserting before the selection.
+        #[test]
+        fn start() {
+            let mut int = Interpreter::default();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('i'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::StartInsert(InsertPlacement::Before))
+            );
+            assert_eq!(int.mode, Mode::Insert);
+        }
+
+        /// Pasted text shall be inserted as a single edit.
+        #[test]
+        fn paste() {
+            let mut int = insert_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Paste("a\nb".to_string()))),
+                Some(Operation::InsertText("a\nb".to_string()))
+            );
+            assert_eq!(int.mode, Mode::Insert);
+        }
+
+        /// Char keys shall be inserted as text.
+        #[test]
+        fn insert_char() {
+            let mut int = insert_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('a'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::InsertText("a".to_string()))
+            );
+            assert_eq!(int.mode, Mode::Insert);
+        }
+
+        /// The `Esc` key shall stop inserting and return to [`Mode::View`].
+        #[test]
+        fn end() {
+            let mut int = insert_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Esc,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::EndInsert)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+
+    /// Tests decoding user input while mode is [`Mode::Register`].
+    mod register {
+        use super::*;
+
+        /// The `"` key followed by a char shall select the register with that name.
+        #[test]
+        fn select() {
+            let mut int = Interpreter::default();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('"'),
+                    modifiers: KeyModifiers::SHIFT,
+                })),
+                None
+            );
+            assert_eq!(int.mode, Mode::Register);
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('a'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::SelectRegister('a'))
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// The `Esc` key shall cancel selecting a register.
+        #[test]
+        fn cancel() {
+            let mut int = Interpreter {
+                mode: Mode::Register,
+                ..Interpreter::default()
+            };
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Esc,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Reset)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+
+    /// Tests decoding user input while in the Confirm mode.
+    mod confirm {
+        use super::*;
+
+        fn confirm_mode() -> Interpreter {
+            Interpreter {
+                mode: Mode::Confirm,
+                ..Interpreter::default()
+            }
+        }
+
+        /// The `y` key shall accept the action.
+        #[test]
+        fn confirm() {
+            let mut int = confirm_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('y'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Accept)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// Any other key shall cancel the action, resetting the application to View mode.
+        #[test]
+        fn cancel() {
+            let mut int = confirm_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('n'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Reset)
+            );
+            assert_eq!(int.mode, Mode::View);
+
+            int = confirm_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('1'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Reset)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+
+    /// Tests decoding user input while mode is [`Mode::Replace`].
+    #[cfg(test)]
+    mod replace {
+        use super::*;
+
+        /// The `y` key shall accept the replacement, the `n` key shall skip it and any other key shall stop replacing.
+        #[test]
+        fn confirm() {
+            let mut int = Interpreter::default();
+
+            int.start_replace();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('y'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Accept)
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('n'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Skip)
+            );
+            assert_eq!(int.mode, Mode::Replace);
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Esc,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Reset)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+
+    /// Tests decoding user input while mode is [`Mode::Collect`].
+    #[cfg(test)]
+    mod collect {
+        use super::*;
+
+        fn collect_mode() -> Interpreter {
+            Interpreter {
+                mode: Mode::Collect,
+                ..Interpreter::default()
+            }
+        }
+
+        /// Pasted text shall be collected.
+        #[test]
+        fn paste() {
+            let mut int = collect_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Paste("open a".to_string()))),
+                Some(Operation::CollectText("open a".to_string()))
+            );
+            assert_eq!(int.mode, Mode::Collect);
+        }
+
+        /// The `Esc` key shall return to [`Mode::View`].
+        #[test]
+        fn reset() {
+            let mut int = collect_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Esc,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Reset)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// All char keys shall be collected.
+        #[test]
+        fn collect() {
+            let mut int = collect_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
                     code: KeyCode::Char('a'),
                     modifiers: KeyModifiers::empty(),
                 })),
@@ -517,6 +1745,79 @@ mod test {
             assert_eq!(int.mode, Mode::Collect);
         }
 
+        /// Editing keys and their Ctrl shortcuts shall edit the command.
+        #[test]
+        fn edit() {
+            let mut int = collect_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Backspace,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::EditCommand(LineEdit::DeleteBackward))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Left,
+                    modifiers: KeyModifiers::CONTROL,
+                })),
+                Some(Operation::EditCommand(LineEdit::MoveWordLeft))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('w'),
+                    modifiers: KeyModifiers::CONTROL,
+                })),
+                Some(Operation::EditCommand(LineEdit::DeleteWordBackward))
+            );
+            assert_eq!(int.mode, Mode::Collect);
+        }
+
+        /// The `Up` and `Down` keys shall recall commands and `Ctrl-r` shall search them.
+        #[test]
+        fn recall() {
+            let mut int = collect_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Up,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::RecallCommand(CommandRecall::Previous))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Down,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::RecallCommand(CommandRecall::Next))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('r'),
+                    modifiers: KeyModifiers::CONTROL,
+                })),
+                Some(Operation::RecallCommand(CommandRecall::SearchBackward))
+            );
+            assert_eq!(int.mode, Mode::Collect);
+        }
+
+        /// The `Tab` key shall complete the command.
+        #[test]
+        fn complete() {
+            let mut int = collect_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Tab,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Complete)
+            );
+            assert_eq!(int.mode, Mode::Collect);
+        }
+
         /// The `Enter` key shall execute the command and return to [`Mode::View`].
         #[test]
         fn execute() {
@@ -532,4 +1833,258 @@ mod test {
             assert_eq!(int.mode, Mode::View);
         }
     }
+
+    /// Tests decoding user input while mode is [`Mode::Pick`].
+    mod pick {
+        use super::*;
+
+        fn pick_mode() -> Interpreter {
+            Interpreter {
+                mode: Mode::Pick,
+                ..Interpreter::default()
+            }
+        }
+
+        /// The `f` key in [`Mode::View`] shall open the file finder.
+        #[test]
+        fn start() {
+            let mut int = Interpreter::default();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('f'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::StartPick)
+            );
+            assert_eq!(int.mode, Mode::Pick);
+        }
+
+        /// Char keys shall filter and arrow keys shall move the selection.
+        #[test]
+        fn filter() {
+            let mut int = pick_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('a'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::InputQuery("a".to_string()))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Down,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::MovePick(ListMovement::Next))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('p'),
+                    modifiers: KeyModifiers::CONTROL,
+                })),
+                Some(Operation::MovePick(ListMovement::Previous))
+            );
+            assert_eq!(int.mode, Mode::Pick);
+        }
+
+        /// The `Enter` key shall open the selected file and return to [`Mode::View`].
+        #[test]
+        fn pick() {
+            let mut int = pick_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Enter,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Pick)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+
+    /// Tests decoding user input while mode is [`Mode::List`].
+    mod list {
+        use super::*;
+
+        fn list_mode() -> Interpreter {
+            let mut int = Interpreter::default();
+            int.start_list();
+            int
+        }
+
+        /// The `j` and `k` keys shall move the selection.
+        #[test]
+        fn navigate() {
+            let mut int = list_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('j'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::MoveResult(ListMovement::Next))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('k'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::MoveResult(ListMovement::Previous))
+            );
+            assert_eq!(int.mode, Mode::List);
+        }
+
+        /// The `Enter` key shall jump to the selected result and return to [`Mode::View`].
+        #[test]
+        fn jump() {
+            let mut int = list_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Enter,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Jump)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+
+    /// Tests decoding user input while mode is [`Mode::Search`].
+    mod search {
+        use super::*;
+
+        fn search_mode() -> Interpreter {
+            Interpreter {
+                mode: Mode::Search,
+                ..Interpreter::default()
+            }
+        }
+
+        /// The `/` and `?` keys in [`Mode::View`] shall start a search and the `n` and `N` keys shall find matches.
+        #[test]
+        fn start() {
+            let mut int = Interpreter::default();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('N'),
+                    modifiers: KeyModifiers::SHIFT,
+                })),
+                Some(Operation::FindMatch(SearchDirection::Backward))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('?'),
+                    modifiers: KeyModifiers::SHIFT,
+                })),
+                Some(Operation::StartSearch(SearchDirection::Backward))
+            );
+            assert_eq!(int.mode, Mode::Search);
+        }
+
+        /// Char keys shall edit the pattern and the `Enter` key shall return to [`Mode::View`].
+        #[test]
+        fn edit() {
+            let mut int = search_mode();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('a'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::InputSearch("a".to_string()))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Backspace,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::EditSearch(LineEdit::DeleteBackward))
+            );
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Enter,
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::EndSearch)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
+    /// Tests decoding user input while mode is [`Mode::Pane`].
+    mod pane {
+        use super::*;
+
+        /// The `s` key in [`Mode::View`] followed by a pane key shall arrange the panes and return to [`Mode::View`].
+        #[test]
+        fn arrange() {
+            let mut int = Interpreter::default();
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                None
+            );
+            assert_eq!(int.mode, Mode::Pane);
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('v'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::SplitPane(SplitDirection::Right))
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// The `t`, `n` and `p` keys shall open and switch tabs.
+        #[test]
+        fn tabs() {
+            let mut int = Interpreter {
+                mode: Mode::Pane,
+                ..Interpreter::default()
+            };
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('t'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::NewTab)
+            );
+
+            int.mode = Mode::Pane;
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('p'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::SwitchTab(ListMovement::Previous))
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+
+        /// Other keys shall reset.
+        #[test]
+        fn reset() {
+            let mut int = Interpreter {
+                mode: Mode::Pane,
+                ..Interpreter::default()
+            };
+
+            assert_eq!(
+                int.translate(Input::User(UserAction::Key {
+                    code: KeyCode::Char('q'),
+                    modifiers: KeyModifiers::empty(),
+                })),
+                Some(Operation::Reset)
+            );
+            assert_eq!(int.mode, Mode::View);
+        }
+    }
 }
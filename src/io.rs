@@ -1,16 +1,24 @@
 //! Implements the interface for all input and output to the application.
 #![allow(clippy::pattern_type_mismatch)]
+mod clipboard;
 mod fs;
+mod grep;
+mod index;
+mod store;
 mod ui;
 
+pub use clipboard::ClipboardConfig;
+
 pub(crate) use {
-    fs::File,
-    ui::{Dimensions, RowText, Style, StyledText, Unit, UserAction},
+    fs::{File, PathCompletion, SavedFile},
+    grep::GrepMatch,
+    ui::{Dimensions, Region, RowText, Style, StyledText, Unit, UserAction},
 };
 
 use {
-    crate::app::{Document, ScopeFromRangeError},
+    crate::app::{Document, FileReplacement, History, ScopeFromRangeError},
     clap::ArgMatches,
+    clipboard::{create_clipboard, Clipboard, ClipboardCommand, ClipboardConsumer, ClipboardError},
     core::{
         convert::TryFrom,
         sync::atomic::{AtomicBool, Ordering},
@@ -19,26 +27,35 @@ use {
     fehler::{throw, throws},
     fs::{
         create_file_system, ConsumeFileError, FileCommand, FileCommandProducer, FileConsumer,
-        FileError, RootDirError,
+        FileError, PathCompletionConsumer, RootDirError, SavedFileConsumer,
+    },
+    grep::{create_grep, Grep, GrepCommand, GrepConsumer, GrepError},
+    index::{create_indexer, IndexCommand, IndexConsumer, IndexError, Indexer},
+    log::warn,
+    lsp_types::{
+        MessageType, ShowMessageParams, ShowMessageRequestParams, TextDocumentContentChangeEvent,
+        TextDocumentIdentifier, TextDocumentItem, VersionedTextDocumentIdentifier,
     },
-    log::error,
-    lsp_types::{ShowMessageRequestParams, TextDocumentIdentifier},
     market::{
         channel::{WithdrawnDemandFault, WithdrawnSupplyFault},
         vec::{Collector, Distributor},
         ConsumeFailure, ConsumeFault, Consumer, ProduceFailure, Producer,
     },
     parse_display::Display as ParseDisplay,
+    ropey::Rope,
     starship::{context::Context, print},
     std::{
         io::{self, ErrorKind},
         rc::Rc,
+        sync::Arc,
     },
+    store::{Store, StoreCommand, StoreError},
     toml::{value::Table, Value},
     ui::{
         CreateTerminalError, DisplayCmd, DisplayCmdFailure, Terminal, UserActionConsumer,
         UserActionFailure,
     },
+    url::Url,
 };
 
 /// An error creating an [`Interface`].
@@ -108,6 +125,21 @@ enum Glitch {
     /// Unable to convert config file to Config.
     #[error("config file invalid format: {0}")]
     ConfigFormat(#[from] toml::de::Error),
+    /// Unable to store data.
+    #[error("unable to store data: {0}")]
+    Store(#[from] StoreError),
+    /// Unable to access the system clipboard.
+    #[error("unable to access clipboard: {0}")]
+    Clipboard(#[from] ClipboardError),
+    /// Unable to write a file or list a directory.
+    #[error("unable to access file system: {0}")]
+    File(#[from] FileError),
+    /// Unable to index the root directory.
+    #[error("unable to index files: {0}")]
+    Index(#[from] IndexError),
+    /// Unable to search the root directory.
+    #[error("unable to search files: {0}")]
+    Grep(#[from] GrepError),
 }
 
 /// An event that prevents [`Interface`] from consuming.
@@ -130,6 +162,15 @@ pub enum ConsumeInputError {
     /// An error consuming a file.
     #[error("")]
     File(#[from] ConsumeFileError),
+    /// An error consuming the text of the clipboard.
+    #[error("")]
+    Clipboard(#[from] ClipboardError),
+    /// An error consuming indexed paths.
+    #[error("")]
+    Index(#[from] IndexError),
+    /// An error consuming the matches of a search.
+    #[error("")]
+    Grep(#[from] GrepError),
     /// An error in [`Tongue`].
     #[error(transparent)]
     Translation(#[from] TranslationError),
@@ -230,9 +271,18 @@ impl Producer for InternalFileProducer {
 
     #[throws(Self::Failure)]
     fn produce(&self, good: Self::Good) {
-        self.0
-            .produce(good)
-            .map_err(|error| ProduceFailure::Fault(error.into()))?
+        match good {
+            FileCommand::Read { .. } => self
+                .0
+                .produce(good)
+                .map_err(|error| ProduceFailure::Fault(error.into()))?,
+            FileCommand::Write { .. } | FileCommand::List { .. } | FileCommand::Replace { .. } => {
+                // Failing to write a file or list a directory does not prevent the application from running.
+                if let Err(error) = self.0.produce(good) {
+                    warn!("{}", Glitch::from(error));
+                }
+            }
+        }
     }
 }
 
@@ -249,6 +299,135 @@ impl Consumer for InternalFileConsumer {
     }
 }
 
+/// Implements [`PathCompletionConsumer`] that can be pushed to [`Interface.consumers`].
+struct InternalPathCompletionConsumer(PathCompletionConsumer);
+
+impl Consumer for InternalPathCompletionConsumer {
+    type Good = Input;
+    type Failure = ConsumeFailure<ConsumeInputError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        Input::Paths(self.0.consume().map_err(ConsumeFailure::map_fault)?)
+    }
+}
+
+/// Implements [`SavedFileConsumer`] that can be pushed to [`Interface.consumers`].
+struct InternalSavedFileConsumer(SavedFileConsumer);
+
+impl Consumer for InternalSavedFileConsumer {
+    type Good = Input;
+    type Failure = ConsumeFailure<ConsumeInputError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        Input::Saved(self.0.consume().map_err(ConsumeFailure::map_fault)?)
+    }
+}
+
+/// Implements [`Indexer`] that can be pushed to [`Interface.producers`].
+struct InternalIndexer(Indexer);
+
+impl Producer for InternalIndexer {
+    type Good = IndexCommand;
+    type Failure = ProduceFailure<ProduceOutputError>;
+
+    #[throws(Self::Failure)]
+    fn produce(&self, good: Self::Good) {
+        // Failing to index files does not prevent the application from running.
+        if let Err(error) = self.0.index(&good) {
+            warn!("{}", Glitch::from(error));
+        }
+    }
+}
+
+/// Implements [`IndexConsumer`] that can be pushed to [`Interface.consumers`].
+struct InternalIndexConsumer(IndexConsumer);
+
+impl Consumer for InternalIndexConsumer {
+    type Good = Input;
+    type Failure = ConsumeFailure<ConsumeInputError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        Input::Index(self.0.consume().map_err(ConsumeFailure::map_fault)?)
+    }
+}
+
+/// Implements [`Grep`] that can be pushed to [`Interface.producers`].
+struct InternalGrep(Grep);
+
+impl Producer for InternalGrep {
+    type Good = GrepCommand;
+    type Failure = ProduceFailure<ProduceOutputError>;
+
+    #[throws(Self::Failure)]
+    fn produce(&self, good: Self::Good) {
+        // Failing to search files does not prevent the application from running.
+        if let Err(error) = self.0.search(&good) {
+            warn!("{}", Glitch::from(error));
+        }
+    }
+}
+
+/// Implements [`GrepConsumer`] that can be pushed to [`Interface.consumers`].
+struct InternalGrepConsumer(GrepConsumer);
+
+impl Consumer for InternalGrepConsumer {
+    type Good = Input;
+    type Failure = ConsumeFailure<ConsumeInputError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        Input::Grep(self.0.consume().map_err(ConsumeFailure::map_fault)?)
+    }
+}
+
+/// Implements [`Store`] that can be pushed to [`Interface.producers`].
+struct InternalStoreProducer(Store);
+
+impl Producer for InternalStoreProducer {
+    type Good = StoreCommand;
+    type Failure = ProduceFailure<ProduceOutputError>;
+
+    #[throws(Self::Failure)]
+    fn produce(&self, good: Self::Good) {
+        // Failing to store data does not prevent the application from running.
+        if let Err(error) = self.0.store(good) {
+            warn!("{}", Glitch::from(error));
+        }
+    }
+}
+
+/// Implements [`Clipboard`] that can be pushed to [`Interface.producers`].
+struct InternalClipboardProducer(Clipboard);
+
+impl Producer for InternalClipboardProducer {
+    type Good = ClipboardCommand;
+    type Failure = ProduceFailure<ProduceOutputError>;
+
+    #[throws(Self::Failure)]
+    fn produce(&self, good: Self::Good) {
+        // Failing to access the clipboard does not prevent the application from running.
+        if let Err(error) = self.0.execute(good) {
+            warn!("{}", Glitch::from(error));
+        }
+    }
+}
+
+/// Implements [`ClipboardConsumer`] that can be pushed to [`Interface.consumers`].
+struct InternalClipboardConsumer(ClipboardConsumer);
+
+impl Consumer for InternalClipboardConsumer {
+    type Good = Input;
+    type Failure = ConsumeFailure<ConsumeInputError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        Input::Clipboard(self.0.consume().map_err(ConsumeFailure::map_fault)?)
+    }
+}
+
 /// The interface between the application and all external components.
 #[derive(Debug)]
 pub(crate) struct Interface {
@@ -260,17 +439,24 @@ pub(crate) struct Interface {
     tongue: Rc<Tongue>,
     /// The application has quit.
     has_quit: AtomicBool,
+    /// Stores application data.
+    store: Store,
 }
 
 impl Interface {
     /// Creates a new interface.
     #[throws(CreateInterfaceError)]
-    pub(crate) fn new(initial_file: Option<String>) -> Self {
+    pub(crate) fn new(initial_file: Option<String>, clipboard_config: ClipboardConfig) -> Self {
         let user_interface = InternalTerminal(Terminal::new()?);
         let mut consumers = Collector::new();
         let mut producers = Distributor::new();
-        let (file_command_producer, file_consumer) = create_file_system()?;
+        let store = Store::new();
+        let (file_command_producer, file_consumer, path_completion_consumer, saved_file_consumer) =
+            create_file_system(store.clone())?;
+        let (clipboard, clipboard_consumer) = create_clipboard(clipboard_config);
         let tongue = Rc::new(Tongue::new(file_command_producer.root_dir()));
+        let (indexer, index_consumer) = create_indexer(file_command_producer.root_dir().clone());
+        let (grep, grep_consumer) = create_grep(file_command_producer.root_dir().clone());
 
         if let Some(file) = initial_file {
             file_command_producer.produce(FileCommand::Read { path: file })?
@@ -279,20 +465,35 @@ impl Interface {
         consumers.push(InternalUserActionConsumer(UserActionConsumer));
         consumers.push(InternalLspConsumer(Rc::clone(&tongue)));
         consumers.push(InternalFileConsumer(file_consumer));
+        consumers.push(InternalPathCompletionConsumer(path_completion_consumer));
+        consumers.push(InternalSavedFileConsumer(saved_file_consumer));
+        consumers.push(InternalClipboardConsumer(clipboard_consumer));
+        consumers.push(InternalIndexConsumer(index_consumer));
+        consumers.push(InternalGrepConsumer(grep_consumer));
 
         producers.push(InternalLspProducer(Rc::clone(&tongue)));
         producers.push(user_interface);
         producers.push(InternalFileProducer(file_command_producer));
+        producers.push(InternalStoreProducer(store.clone()));
+        producers.push(InternalClipboardProducer(clipboard));
+        producers.push(InternalIndexer(indexer));
+        producers.push(InternalGrep(grep));
 
         let interface = Self {
             consumers,
             producers,
             tongue,
             has_quit: AtomicBool::new(false),
+            store,
         };
 
         interface
     }
+
+    /// Returns the previously executed commands, from oldest to newest.
+    pub(crate) fn command_history(&self) -> Vec<String> {
+        self.store.load_commands()
+    }
 }
 
 impl Consumer for Interface {
@@ -336,9 +537,19 @@ impl Producer for Interface {
             Output::OpenFile { .. }
             | Output::UpdateView { .. }
             | Output::EditDoc { .. }
-            | Output::UpdateHeader
+            | Output::UpdateHeader { .. }
             | Output::Question { .. }
             | Output::CloseDoc { .. }
+            | Output::StoreHistory { .. }
+            | Output::RecordCommand { .. }
+            | Output::Copy { .. }
+            | Output::Paste
+            | Output::Notify { .. }
+            | Output::SaveFile { .. }
+            | Output::ListPaths { .. }
+            | Output::IndexPaths { .. }
+            | Output::Grep { .. }
+            | Output::ReplaceInFile { .. }
             | Output::Command { .. } => {}
             Output::Quit => {
                 self.has_quit.store(true, Ordering::Relaxed);
@@ -361,6 +572,16 @@ pub(crate) enum Input {
     User(UserAction),
     /// A message from the language server.
     Lsp(Reception),
+    /// The text read from the system clipboard.
+    Clipboard(String),
+    /// The paths that complete a prefix.
+    Paths(PathCompletion),
+    /// A document that was written to its file.
+    Saved(SavedFile),
+    /// A batch of the paths of the files within the root directory.
+    Index(Vec<String>),
+    /// The matches of a search within a file.
+    Grep(Vec<GrepMatch>),
 }
 
 impl From<File> for Input {
@@ -403,30 +624,108 @@ pub(crate) enum Output {
     /// Edits a document.
     EditDoc {
         /// The file that is edited.
-        doc: Document,
+        doc: Box<Document>,
         /// The edit to be performed.
         edit: DocEdit,
     },
     /// Updates the area of the document that is shown.
-    #[display("Update view to:\n{rows:?}")]
+    #[display("Update view at {region} to:\n{rows:?}")]
     UpdateView {
+        /// The [`Region`] of the page in which the rows are shown.
+        region: Region,
         /// The rows of the document to be shown.
         rows: Vec<RowText>,
     },
     /// Sets the header of the application.
     #[display("Update header")]
-    UpdateHeader,
+    UpdateHeader {
+        /// The labels of the tab strip shown next to the prompt.
+        tabs: Vec<StyledText>,
+    },
     /// Asks the user a question.
     #[display("Ask `{request:?}`")]
     Question {
+        /// The [`Region`] of the page in which the question is shown.
+        region: Region,
         /// The request to be answered.
         request: ShowMessageRequestParams,
     },
+    /// Shows a message to the user.
+    #[display("Notify `{message:?}`")]
+    Notify {
+        /// The message.
+        message: ShowMessageParams,
+    },
+    /// Writes the text of a document to its file.
+    #[display("Save `{url}`")]
+    SaveFile {
+        /// The URL of the file.
+        url: Url,
+        /// The text to be written.
+        text: Rope,
+        /// The version of the document whose text is written.
+        version: i32,
+    },
     /// Adds an intake box.
     #[display("Command `{command}`")]
     Command {
         /// The prompt of the intake box.
         command: String,
+        /// The column of the cursor within the intake box.
+        ///
+        /// If `None`, the cursor is hidden.
+        cursor: Option<usize>,
+    },
+    /// Adds a command to the command history.
+    #[display("Record command `{command}`")]
+    RecordCommand {
+        /// The command.
+        command: String,
+    },
+    /// Stores the history of a document.
+    #[display("Store history of `{url}`")]
+    StoreHistory {
+        /// The URL of the document.
+        url: Url,
+        /// The current text of the document.
+        text: Rope,
+        /// The history of the document.
+        history: Arc<History>,
+    },
+    /// Copies text to the system clipboard.
+    #[display("Copy to clipboard")]
+    Copy {
+        /// The text to be copied.
+        text: String,
+    },
+    /// Reads the text of the system clipboard.
+    #[display("Paste from clipboard")]
+    Paste,
+    /// Lists the paths that start with `prefix`.
+    #[display("List paths `{prefix}`")]
+    ListPaths {
+        /// The prefix, relative to the root directory.
+        prefix: String,
+    },
+    /// Indexes the paths of all files within the root directory.
+    #[display("Index paths")]
+    IndexPaths {
+        /// If hidden files are indexed.
+        hidden: bool,
+    },
+    /// Searches all files within the root directory for `pattern`.
+    #[display("Grep `{pattern}`")]
+    Grep {
+        /// The regex that is searched for.
+        pattern: String,
+    },
+    /// Replaces text within a file that is not open.
+    #[display("Replace in `{url}`")]
+    ReplaceInFile {
+        /// The URL of the file.
+        url: Url,
+        /// The replacements, which do not overlap.
+        replacements: Vec<FileReplacement>,
     },
     /// Quit the application.
     #[display("Quit")]
@@ -441,12 +740,22 @@ impl TryFrom<Output> for FileCommand {
     fn try_from(value: Output) -> Self {
         match value {
             Output::OpenFile { path } => Self::Read { path },
+            Output::SaveFile { url, text, version } => Self::Write { url, text, version },
+            Output::ListPaths { prefix } => Self::List { prefix },
+            Output::ReplaceInFile { url, replacements } => Self::Replace { url, replacements },
             Output::Command { .. }
             | Output::EditDoc { .. }
-            | Output::UpdateHeader
+            | Output::UpdateHeader { .. }
             | Output::UpdateView { .. }
             | Output::Question { .. }
             | Output::CloseDoc { .. }
+            | Output::StoreHistory { .. }
+            | Output::RecordCommand { .. }
+            | Output::Copy { .. }
+            | Output::Paste
+            | Output::Notify { .. }
+            | Output::IndexPaths { .. }
+            | Output::Grep { .. }
             | Output::Quit => throw!(TryIntoFileCommandError::InvalidOutput),
         }
     }
@@ -461,14 +770,41 @@ impl TryFrom<Output> for Vec<Transmission> {
         match value {
             Output::CloseDoc { doc } => vec![Transmission::close_doc(doc)],
             Output::EditDoc { doc, edit } => match edit {
-                DocEdit::Open { .. } => vec![Transmission::OpenDoc { doc: doc.into() }],
+                DocEdit::Open => vec![Transmission::OpenDoc { doc: (*doc).into() }],
+                DocEdit::Change { changes } if changes.is_empty() => Vec::new(),
+                DocEdit::Change { changes } => vec![Transmission::ChangeDoc {
+                    doc: VersionedTextDocumentIdentifier::new(doc.url().clone(), doc.version()),
+                    changes,
+                }],
+                DocEdit::Resend => {
+                    let item = TextDocumentItem::from(*doc);
+
+                    vec![Transmission::ChangeDoc {
+                        doc: VersionedTextDocumentIdentifier::new(item.uri, item.version),
+                        changes: vec![TextDocumentContentChangeEvent {
+                            range: None,
+                            range_length: None,
+                            text: item.text,
+                        }],
+                    }]
+                }
                 DocEdit::Update => throw!(TryIntoProtocolError::InvalidOutput),
             },
             Output::OpenFile { .. }
             | Output::Command { .. }
-            | Output::UpdateHeader
+            | Output::UpdateHeader { .. }
             | Output::UpdateView { .. }
             | Output::Question { .. }
+            | Output::StoreHistory { .. }
+            | Output::RecordCommand { .. }
+            | Output::Copy { .. }
+            | Output::Paste
+            | Output::Notify { .. }
+            | Output::SaveFile { .. }
+            | Output::ListPaths { .. }
+            | Output::IndexPaths { .. }
+            | Output::Grep { .. }
+            | Output::ReplaceInFile { .. }
             | Output::Quit => throw!(TryIntoProtocolError::InvalidOutput),
         }
     }
@@ -482,17 +818,36 @@ impl TryFrom<Output> for DisplayCmd {
     fn try_from(value: Output) -> Self {
         match value {
             Output::EditDoc { doc, edit } => match edit {
-                DocEdit::Open { .. } | DocEdit::Update => Self::Rows { rows: doc.rows()? },
+                DocEdit::Open | DocEdit::Update | DocEdit::Change { .. } => Self::Rows {
+                    region: doc.region(),
+                    rows: doc.rows()?,
+                },
+                DocEdit::Resend => throw!(TryIntoDisplayCmdError::InvalidOutput),
+            },
+            Output::UpdateView { region, rows } => Self::Rows { region, rows },
+            Output::Question { region, request } => Self::Rows {
+                region,
+                rows: request
+                    .message
+                    .lines()
+                    .map(|line| {
+                        RowText::new(vec![StyledText::new(line.to_string(), Style::Default)])
+                    })
+                    .collect(),
             },
-            Output::UpdateView { rows } => Self::Rows { rows },
-            Output::Question { request } => Self::Rows {
-                rows: vec![RowText::new(vec![StyledText::new(
-                    request.message,
-                    Style::Default,
-                )])],
+            Output::Command { command, cursor } => Self::Command { command, cursor },
+            Output::Copy { text } => Self::Copy { text },
+            Output::Notify { message } => Self::Notice {
+                notice: StyledText::new(
+                    message.message,
+                    if message.typ == MessageType::Error {
+                        Style::Error
+                    } else {
+                        Style::Default
+                    },
+                ),
             },
-            Output::Command { command } => Self::Command { command },
-            Output::UpdateHeader => {
+            Output::UpdateHeader { tabs } => {
                 let mut context = Context::new(ArgMatches::new());
 
                 if let Some(mut config) = context.config.config.clone() {
@@ -513,9 +868,20 @@ impl TryFrom<Output> for DisplayCmd {
 
                 Self::Header {
                     header: print::get_prompt(context),
+                    tabs,
                 }
             }
-            Output::CloseDoc { .. } | Output::OpenFile { .. } | Output::Quit => {
+            Output::CloseDoc { .. }
+            | Output::OpenFile { .. }
+            | Output::StoreHistory { .. }
+            | Output::RecordCommand { .. }
+            | Output::Paste
+            | Output::SaveFile { .. }
+            | Output::ListPaths { .. }
+            | Output::IndexPaths { .. }
+            | Output::Grep { .. }
+            | Output::ReplaceInFile { .. }
+            | Output::Quit => {
                 throw!(TryIntoDisplayCmdError::InvalidOutput)
             }
         }
@@ -530,6 +896,154 @@ pub(crate) enum TryIntoFileCommandError {
     InvalidOutput,
 }
 
+impl TryFrom<Output> for StoreCommand {
+    type Error = TryIntoStoreCommandError;
+
+    #[inline]
+    #[throws(Self::Error)]
+    fn try_from(value: Output) -> Self {
+        match value {
+            Output::StoreHistory { url, text, history } => Self::History { url, text, history },
+            Output::RecordCommand { command } => Self::Command { command },
+            Output::OpenFile { .. }
+            | Output::Command { .. }
+            | Output::EditDoc { .. }
+            | Output::UpdateHeader { .. }
+            | Output::UpdateView { .. }
+            | Output::Question { .. }
+            | Output::CloseDoc { .. }
+            | Output::Copy { .. }
+            | Output::Paste
+            | Output::Notify { .. }
+            | Output::SaveFile { .. }
+            | Output::ListPaths { .. }
+            | Output::IndexPaths { .. }
+            | Output::Grep { .. }
+            | Output::ReplaceInFile { .. }
+            | Output::Quit => throw!(TryIntoStoreCommandError::InvalidOutput),
+        }
+    }
+}
+
+impl TryFrom<Output> for ClipboardCommand {
+    type Error = TryIntoClipboardCommandError;
+
+    #[inline]
+    #[throws(Self::Error)]
+    fn try_from(value: Output) -> Self {
+        match value {
+            Output::Copy { text } => Self::Copy { text },
+            Output::Paste => Self::Paste,
+            Output::OpenFile { .. }
+            | Output::Command { .. }
+            | Output::EditDoc { .. }
+            | Output::UpdateHeader { .. }
+            | Output::UpdateView { .. }
+            | Output::Question { .. }
+            | Output::CloseDoc { .. }
+            | Output::StoreHistory { .. }
+            | Output::RecordCommand { .. }
+            | Output::Notify { .. }
+            | Output::SaveFile { .. }
+            | Output::ListPaths { .. }
+            | Output::IndexPaths { .. }
+            | Output::Grep { .. }
+            | Output::ReplaceInFile { .. }
+            | Output::Quit => throw!(TryIntoClipboardCommandError::InvalidOutput),
+        }
+    }
+}
+
+impl TryFrom<Output> for IndexCommand {
+    type Error = TryIntoIndexCommandError;
+
+    #[inline]
+    #[throws(Self::Error)]
+    fn try_from(value: Output) -> Self {
+        match value {
+            Output::IndexPaths { hidden } => Self { hidden },
+            Output::OpenFile { .. }
+            | Output::Command { .. }
+            | Output::EditDoc { .. }
+            | Output::UpdateHeader { .. }
+            | Output::UpdateView { .. }
+            | Output::Question { .. }
+            | Output::CloseDoc { .. }
+            | Output::StoreHistory { .. }
+            | Output::RecordCommand { .. }
+            | Output::Copy { .. }
+            | Output::Paste
+            | Output::Notify { .. }
+            | Output::SaveFile { .. }
+            | Output::ListPaths { .. }
+            | Output::Grep { .. }
+            | Output::ReplaceInFile { .. }
+            | Output::Quit => throw!(TryIntoIndexCommandError::InvalidOutput),
+        }
+    }
+}
+
+impl TryFrom<Output> for GrepCommand {
+    type Error = TryIntoGrepCommandError;
+
+    #[inline]
+    #[throws(Self::Error)]
+    fn try_from(value: Output) -> Self {
+        match value {
+            Output::Grep { pattern } => Self { pattern },
+            Output::OpenFile { .. }
+            | Output::Command { .. }
+            | Output::EditDoc { .. }
+            | Output::UpdateHeader { .. }
+            | Output::UpdateView { .. }
+            | Output::Question { .. }
+            | Output::CloseDoc { .. }
+            | Output::StoreHistory { .. }
+            | Output::RecordCommand { .. }
+            | Output::Copy { .. }
+            | Output::Paste
+            | Output::Notify { .. }
+            | Output::SaveFile { .. }
+            | Output::ListPaths { .. }
+            | Output::IndexPaths { .. }
+            | Output::ReplaceInFile { .. }
+            | Output::Quit => throw!(TryIntoGrepCommandError::InvalidOutput),
+        }
+    }
+}
+
+/// An error converting [`Output`] into a [`GrepCommand`].
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub(crate) enum TryIntoGrepCommandError {
+    /// Invalid [`Output`].
+    #[error("")]
+    InvalidOutput,
+}
+
+/// An error converting [`Output`] into an [`IndexCommand`].
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub(crate) enum TryIntoIndexCommandError {
+    /// Invalid [`Output`].
+    #[error("")]
+    InvalidOutput,
+}
+
+/// An error converting [`Output`] into a [`ClipboardCommand`].
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub(crate) enum TryIntoClipboardCommandError {
+    /// Invalid [`Output`].
+    #[error("")]
+    InvalidOutput,
+}
+
+/// An error converting [`Output`] into a [`StoreCommand`].
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub(crate) enum TryIntoStoreCommandError {
+    /// Invalid [`Output`].
+    #[error("")]
+    InvalidOutput,
+}
+
 /// An error converting [`Output`] into a [`DisplayCmd`].
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum TryIntoDisplayCmdError {
@@ -550,15 +1064,19 @@ pub(crate) enum TryIntoProtocolError {
 }
 
 /// Edits a document.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub(crate) enum DocEdit {
-    /// Opens a document.
-    Open {
-        /// The version of the document.
-        version: i32,
-    },
+    /// Opens a document, which is sent to the language server at its version.
+    Open,
     /// Updates the display of the document.
     Update,
+    /// Changes the text of the document.
+    Change {
+        /// The changes made to the text.
+        changes: Vec<TextDocumentContentChangeEvent>,
+    },
+    /// Sends the whole text of the document to the language server without showing it.
+    Resend,
 }
 
 /// The changes to be made to a document.
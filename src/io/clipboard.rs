@@ -0,0 +1,174 @@
+//! Implements the interface with the system clipboard.
+//!
+//! Copied text is always sent to the terminal via the OSC 52 escape sequence, which reaches the clipboard of the local machine even over SSH. If configured, the text is also piped to an external copy command. Reading the clipboard requires a configured paste command.
+use {
+    fehler::{throw, throws},
+    log::trace,
+    market::{queue::Procurer, ConsumeFailure, ConsumeFault, Consumer, Producer},
+    parse_display::Display as ParseDisplay,
+    std::{
+        io::{self, Write},
+        process::{Command, ExitStatus, Stdio},
+        string::FromUtf8Error,
+    },
+    structopt::StructOpt,
+    thiserror::Error as ThisError,
+};
+
+/// The configuration of the system clipboard.
+#[derive(Clone, Debug, Default, StructOpt)]
+pub struct ClipboardConfig {
+    /// The command that copies its stdin to the system clipboard, such as `wl-copy` or `xclip -selection clipboard`.
+    #[structopt(long, value_name("COMMAND"))]
+    copy_command: Option<String>,
+    /// The command that writes the system clipboard to its stdout, such as `wl-paste` or `xclip -selection clipboard -o`.
+    #[structopt(long, value_name("COMMAND"))]
+    paste_command: Option<String>,
+}
+
+/// An error accessing the system clipboard.
+#[derive(Debug, ConsumeFault, ThisError)]
+pub enum ClipboardError {
+    /// An IO error.
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    /// A command is empty.
+    #[error("clipboard command is empty")]
+    EmptyCommand,
+    /// No paste command is configured.
+    #[error("no paste command is configured")]
+    NoPasteCommand,
+    /// A command failed.
+    #[error("`{command}` failed: {status}")]
+    Status {
+        /// The command.
+        command: String,
+        /// The exit status of the command.
+        status: ExitStatus,
+    },
+    /// The clipboard holds text that is not valid UTF-8.
+    #[error("{0}")]
+    Utf8(#[from] FromUtf8Error),
+}
+
+/// Specifies an access of the system clipboard.
+#[derive(Debug, ParseDisplay)]
+pub(crate) enum ClipboardCommand {
+    /// Copies text to the clipboard via the external copy command.
+    #[display("Copy to clipboard")]
+    Copy {
+        /// The text to be copied.
+        text: String,
+    },
+    /// Reads the text of the clipboard via the external paste command.
+    #[display("Paste from clipboard")]
+    Paste,
+}
+
+/// Creates the interface with the system clipboard.
+pub(crate) fn create_clipboard(config: ClipboardConfig) -> (Clipboard, ClipboardConsumer) {
+    let (text_producer, text_consumer) = market::queue::create_supply_chain();
+
+    (
+        Clipboard {
+            config,
+            text_producer,
+        },
+        ClipboardConsumer { text_consumer },
+    )
+}
+
+/// Returns the [`Command`] described by `command`, a program followed by its whitespace separated arguments.
+#[throws(ClipboardError)]
+fn parse_command(command: &str) -> Command {
+    let mut words = command.split_whitespace();
+    let mut process = Command::new(words.next().ok_or(ClipboardError::EmptyCommand)?);
+
+    let _ = process.args(words);
+    process
+}
+
+/// Executes [`ClipboardCommand`]s.
+pub(crate) struct Clipboard {
+    /// The configuration.
+    config: ClipboardConfig,
+    /// Sends the text read from the clipboard.
+    text_producer: market::queue::Supplier<String>,
+}
+
+impl Clipboard {
+    /// Executes `command`.
+    #[throws(ClipboardError)]
+    pub(crate) fn execute(&self, command: ClipboardCommand) {
+        match command {
+            ClipboardCommand::Copy { text } => {
+                if let Some(copy_command) = self.config.copy_command.as_ref() {
+                    trace!("copy via `{}`", copy_command);
+                    let mut child = parse_command(copy_command)?
+                        .stdin(Stdio::piped())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .spawn()?;
+
+                    if let Some(mut stdin) = child.stdin.take() {
+                        stdin.write_all(text.as_bytes())?;
+                    }
+
+                    let status = child.wait()?;
+
+                    if !status.success() {
+                        throw!(ClipboardError::Status {
+                            command: copy_command.clone(),
+                            status,
+                        });
+                    }
+                }
+            }
+            ClipboardCommand::Paste => {
+                let paste_command = self
+                    .config
+                    .paste_command
+                    .as_ref()
+                    .ok_or(ClipboardError::NoPasteCommand)?;
+
+                trace!("paste via `{}`", paste_command);
+                let output = parse_command(paste_command)?
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::null())
+                    .output()?;
+
+                if !output.status.success() {
+                    throw!(ClipboardError::Status {
+                        command: paste_command.clone(),
+                        status: output.status,
+                    });
+                }
+
+                #[allow(clippy::unwrap_used)] // Supplier::produce() cannot fail.
+                self.text_producer
+                    .produce(String::from_utf8(output.stdout)?)
+                    .unwrap();
+            }
+        }
+    }
+}
+
+/// Consumes the text read from the clipboard.
+pub(crate) struct ClipboardConsumer {
+    /// The [`Consumer`].
+    text_consumer: Procurer<String>,
+}
+
+impl Consumer for ClipboardConsumer {
+    type Good = String;
+    type Failure = ConsumeFailure<ClipboardError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        #[allow(clippy::map_err_ignore)]
+        // Currently unable to implement ConsumeFailure<T>: From<InsufficientStockFailure>.
+        self.text_consumer
+            .consume()
+            .map_err(|_| ConsumeFailure::EmptyStock)?
+    }
+}
@@ -1,13 +1,20 @@
 //! Handles filesystem operations.
 use {
+    super::store::Store,
+    crate::app::{FileReplacement, History, VcsChanges},
+    core::{cmp, convert::TryFrom, mem},
     docuglot::Language,
-    fehler::throws,
+    fehler::{throw, throws},
     log::trace,
+    lsp_types::Position,
     market::{queue::Procurer, ConsumeFailure, ConsumeFault, Consumer, Failure, Producer},
     parse_display::Display as ParseDisplay,
+    ropey::Rope,
     std::{
         env, fs,
-        io::{self, ErrorKind},
+        io::{self, BufReader, BufWriter, ErrorKind, Write},
+        path::Path,
+        process::Command,
     },
     thiserror::Error as ThisError,
     url::{ParseError, Url},
@@ -35,22 +42,226 @@ fn root_dir() -> Url {
 }
 
 /// Create the interface to the file system.
+///
+/// The history of each file that is read is restored from `store`.
 #[throws(RootDirError)]
-pub(crate) fn create_file_system() -> (FileCommandProducer, FileConsumer) {
+pub(crate) fn create_file_system(
+    store: Store,
+) -> (
+    FileCommandProducer,
+    FileConsumer,
+    PathCompletionConsumer,
+    SavedFileConsumer,
+) {
     let (url_producer, url_consumer) = market::queue::create_supply_chain();
+    let (completion_producer, completion_consumer) = market::queue::create_supply_chain();
+    let (saved_producer, saved_consumer) = market::queue::create_supply_chain();
     (
         FileCommandProducer {
             root_dir: root_dir()?,
             url_producer,
+            completion_producer,
+            saved_producer,
         },
-        FileConsumer { url_consumer },
+        FileConsumer {
+            url_consumer,
+            store,
+        },
+        PathCompletionConsumer {
+            completion_consumer,
+        },
+        SavedFileConsumer { saved_consumer },
+    )
+}
+
+/// Returns the paths that start with `prefix`, relative to `root_dir`.
+///
+/// Paths of directories end with `/`. Hidden entries are only listed when the name being completed starts with `.`.
+#[throws(FileError)]
+fn list_paths(root_dir: &Url, prefix: &str) -> Vec<String> {
+    let (dir, name) = prefix.split_at(prefix.rfind('/').map_or(0, |index| index.saturating_add(1)));
+    let dir_url = root_dir.join(dir)?;
+    #[allow(clippy::map_err_ignore)]
+    // Url::to_file_path() returns () as Err type so the error has no helpful information.
+    let dir_path = dir_url
+        .to_file_path()
+        .map_err(|_| FileError::NotFilePath(dir_url.clone()))?;
+    let mut paths = Vec::new();
+
+    for entry in fs::read_dir(dir_path)? {
+        let entry = entry?;
+
+        if let Some(file_name) = entry.file_name().to_str() {
+            if file_name.starts_with(name) && (name.starts_with('.') || !file_name.starts_with('.'))
+            {
+                paths.push(if entry.path().is_dir() {
+                    format!("{}{}/", dir, file_name)
+                } else {
+                    format!("{}{}", dir, file_name)
+                });
+            }
+        }
+    }
+
+    paths.sort();
+    paths
+}
+
+/// Writes `text` to the file at `url`.
+#[throws(FileError)]
+fn write(url: &Url, text: &Rope) {
+    trace!("write {}", url.path());
+    #[allow(clippy::map_err_ignore)]
+    // Url::to_file_path() returns () as Err type so the error has no helpful information.
+    let path = url
+        .to_file_path()
+        .map_err(|_| FileError::NotFilePath(url.clone()))?;
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+
+    text.write_to(&mut writer)?;
+    writer.flush()?;
+}
+
+/// Returns the lines of the file at `path` that differ from its last commit.
+///
+/// A file that git cannot diff, such as one outside of a repository, has no changes.
+fn vcs_changes(path: &Path) -> VcsChanges {
+    path.parent()
+        .zip(path.file_name())
+        .and_then(|(dir, name)| {
+            Command::new("git")
+                .current_dir(dir)
+                .args(["diff", "--no-color", "--no-ext-diff", "-U0", "HEAD", "--"])
+                .arg(name)
+                .output()
+                .ok()
+        })
+        .map(|output| VcsChanges::from_diff(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Applies `replacements` to the text of the file at `url`.
+///
+/// If the text within the range of any replacement is no longer the text it expects to replace, the file was changed since it was searched and nothing is written.
+#[throws(FileError)]
+fn replace(url: &Url, mut replacements: Vec<FileReplacement>) {
+    trace!("replace in {}", url.path());
+    #[allow(clippy::map_err_ignore)]
+    // Url::to_file_path() returns () as Err type so the error has no helpful information.
+    let path = url
+        .to_file_path()
+        .map_err(|_| FileError::NotFilePath(url.clone()))?;
+    let mut text = Rope::from_reader(BufReader::new(fs::File::open(path)?))?;
+
+    // Replacing from the end of the text keeps the ranges of the earlier replacements valid.
+    replacements
+        .sort_by_key(|file_replacement| cmp::Reverse(file_replacement.replacement.range.start));
+
+    for FileReplacement {
+        replacement,
+        old_text,
+    } in replacements
+    {
+        match (
+            char_index(&text, replacement.range.start),
+            char_index(&text, replacement.range.end),
+        ) {
+            (Some(start), Some(end)) if start <= end && text.slice(start..end) == old_text => {
+                text.remove(start..end);
+                text.insert(start, &replacement.text);
+            }
+            // Nothing has been written yet, so the file is left as it is.
+            _ => throw!(FileError::Changed(url.clone())),
+        }
+    }
+
+    write(url, &text)?;
+}
+
+/// Returns the index of the char of `text` at `position`, or `None` if its line is not within `text`.
+///
+/// A `position` beyond the end of its line is treated as the end of the line.
+fn char_index(text: &Rope, position: Position) -> Option<usize> {
+    let line = usize::try_from(position.line).ok()?;
+    // The line ending is not part of the line.
+    let length = text
+        .get_line(line)?
+        .chars()
+        .take_while(|ch| !matches!(ch, '\n' | '\r'))
+        .count();
+
+    Some(
+        text.line_to_char(line)
+            .saturating_add(cmp::min(usize::try_from(position.character).ok()?, length)),
     )
 }
 
+/// The paths that complete a prefix.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PathCompletion {
+    /// The prefix that was completed.
+    pub(crate) prefix: String,
+    /// The paths that start with `prefix`, sorted alphabetically.
+    pub(crate) paths: Vec<String>,
+}
+
+/// Consumes [`PathCompletion`]s.
+pub(crate) struct PathCompletionConsumer {
+    /// The [`Consumer`].
+    completion_consumer: Procurer<PathCompletion>,
+}
+
+impl Consumer for PathCompletionConsumer {
+    type Good = PathCompletion;
+    type Failure = ConsumeFailure<ConsumeFileError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        #[allow(clippy::map_err_ignore)]
+        // Currently unable to implement ConsumeFailure<T>: From<InsufficientStockFailure>.
+        self.completion_consumer
+            .consume()
+            .map_err(|_| ConsumeFailure::EmptyStock)?
+    }
+}
+
+/// A document whose text was written to its file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SavedFile {
+    /// The URL of the file.
+    pub(crate) url: Url,
+    /// The version of the document whose text was written.
+    pub(crate) version: i32,
+    /// The lines of the written text that differ from the last commit of the file.
+    pub(crate) changes: VcsChanges,
+}
+
+/// Consumes [`SavedFile`]s.
+pub(crate) struct SavedFileConsumer {
+    /// The [`Consumer`].
+    saved_consumer: Procurer<SavedFile>,
+}
+
+impl Consumer for SavedFileConsumer {
+    type Good = SavedFile;
+    type Failure = ConsumeFailure<ConsumeFileError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        #[allow(clippy::map_err_ignore)]
+        // Currently unable to implement ConsumeFailure<T>: From<InsufficientStockFailure>.
+        self.saved_consumer
+            .consume()
+            .map_err(|_| ConsumeFailure::EmptyStock)?
+    }
+}
+
 /// Consumes [`Url`]s.
 pub(crate) struct FileConsumer {
     /// The [`Consumer`].
     url_consumer: Procurer<Url>,
+    /// The [`Store`] from which histories are restored.
+    store: Store,
 }
 
 impl Consumer for FileConsumer {
@@ -67,7 +278,10 @@ impl Consumer for FileConsumer {
             // consume() can throw InsufficientStockFailure
             .map_err(|_| ConsumeFailure::EmptyStock)?;
 
-        File::read(path_url).map_err(ConsumeFileError::from)?
+        let mut file = File::read(path_url).map_err(ConsumeFileError::from)?;
+
+        file.history = self.store.load_history(&file.url, &file.text);
+        file
     }
 }
 
@@ -77,6 +291,10 @@ pub(crate) struct FileCommandProducer {
     root_dir: Url,
     /// Sends the [`Url`]s to the file system handler.
     url_producer: market::queue::Supplier<Url>,
+    /// Sends the [`PathCompletion`]s.
+    completion_producer: market::queue::Supplier<PathCompletion>,
+    /// Sends the [`SavedFile`]s.
+    saved_producer: market::queue::Supplier<SavedFile>,
 }
 
 impl FileCommandProducer {
@@ -99,6 +317,29 @@ impl Producer for FileCommandProducer {
                 .url_producer
                 .produce(self.root_dir.join(&path)?)
                 .unwrap(),
+            #[allow(clippy::unwrap_used)] // Supplier::produce() cannot fail.
+            Self::Good::Write { url, text, version } => {
+                write(&url, &text)?;
+                self.saved_producer
+                    .produce(SavedFile {
+                        changes: url
+                            .to_file_path()
+                            .map(|path| vcs_changes(&path))
+                            .unwrap_or_default(),
+                        url,
+                        version,
+                    })
+                    .unwrap();
+            }
+            Self::Good::Replace { url, replacements } => replace(&url, replacements)?,
+            #[allow(clippy::unwrap_used)] // Supplier::produce() cannot fail.
+            Self::Good::List { prefix } => self
+                .completion_producer
+                .produce(PathCompletion {
+                    paths: list_paths(&self.root_dir, &prefix)?,
+                    prefix,
+                })
+                .unwrap(),
         }
     }
 }
@@ -107,11 +348,17 @@ impl Producer for FileCommandProducer {
 #[derive(Debug, ThisError)]
 pub enum FileError {
     /// An IO error.
-    #[error("")]
+    #[error("{0}")]
     Io(#[from] io::Error),
     /// An error creating a [`Purl`]
-    #[error("")]
+    #[error("{0}")]
     Create(#[from] ParseError),
+    /// A URL that does not refer to a file.
+    #[error("`{0}` is not a file path")]
+    NotFilePath(Url),
+    /// A file whose text no longer matches the text to be replaced.
+    #[error("`{0}` changed since it was searched")]
+    Changed(Url),
 }
 
 impl Failure for FileError {
@@ -127,6 +374,30 @@ pub(crate) enum FileCommand {
         /// The relative path of the file.
         path: String,
     },
+    /// Writes `text` to the file at `url`.
+    #[display("Write `{url}`")]
+    Write {
+        /// The URL of the file.
+        url: Url,
+        /// The text to be written.
+        text: Rope,
+        /// The version of the document whose text is written.
+        version: i32,
+    },
+    /// Lists the paths that start with `prefix`.
+    #[display("List `{prefix}`")]
+    List {
+        /// The prefix, relative to the root directory.
+        prefix: String,
+    },
+    /// Applies `replacements` to the file at `url`.
+    #[display("Replace in `{url}`")]
+    Replace {
+        /// The URL of the file.
+        url: Url,
+        /// The replacements, which do not overlap.
+        replacements: Vec<FileReplacement>,
+    },
 }
 
 /// A struct that represents a file.
@@ -135,7 +406,11 @@ pub(crate) struct File {
     /// The URL of the file.
     url: Url,
     /// The text of a file.
-    text: String,
+    text: Rope,
+    /// The history restored for the file.
+    history: Option<History>,
+    /// The lines that differ from the last commit of the file.
+    changes: VcsChanges,
 }
 
 impl File {
@@ -145,24 +420,56 @@ impl File {
         trace!("read {}", url.path());
         #[allow(clippy::map_err_ignore)]
         // Url::to_file_path() returns () as Err type so the error has no helpful information.
+        let path = url.to_file_path().map_err(|_| ReadFileError {
+            file: url.to_string(),
+            error: ErrorKind::NotFound,
+        })?;
+
         Self {
-            text: fs::read_to_string(url.to_file_path().map_err(|_| ReadFileError {
-                file: url.to_string(),
-                error: ErrorKind::NotFound,
-            })?)
-            .map_err(|error| ReadFileError {
-                file: url.to_string(),
-                error: error.kind(),
-            })?,
+            // Reading into a Rope avoids holding the entire file in a single allocation.
+            text: fs::File::open(&path)
+                .and_then(|file| Rope::from_reader(BufReader::new(file)))
+                .map_err(|error| ReadFileError {
+                    file: url.to_string(),
+                    error: error.kind(),
+                })?,
+            changes: vcs_changes(&path),
             url,
+            history: None,
+        }
+    }
+
+    /// Creates a file at `url` with `text`, without reading it.
+    #[cfg(test)]
+    pub(crate) fn new(url: Url, text: &str) -> Self {
+        Self {
+            url,
+            text: Rope::from(text),
+            history: None,
+            changes: VcsChanges::default(),
         }
     }
 
     /// Returns a reference to the text of `self`.
-    pub(crate) const fn text(&self) -> &String {
+    pub(crate) const fn text(&self) -> &Rope {
         &self.text
     }
 
+    /// Returns a mutable reference to the text of `self`.
+    pub(crate) fn text_mut(&mut self) -> &mut Rope {
+        &mut self.text
+    }
+
+    /// Takes the restored history of `self`.
+    pub(crate) fn take_history(&mut self) -> Option<History> {
+        self.history.take()
+    }
+
+    /// Takes the lines of `self` that differ from the last commit of its file.
+    pub(crate) fn take_changes(&mut self) -> VcsChanges {
+        mem::take(&mut self.changes)
+    }
+
     /// Returns a reference to the URL of `self`.
     pub(crate) const fn url(&self) -> &Url {
         &self.url
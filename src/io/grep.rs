@@ -0,0 +1,214 @@
+//! Implements the searching of all files within the root directory for a pattern.
+//!
+//! Files are searched by a background thread so that a large tree does not block the application. The matches within each file are sent as they are found.
+use {
+    core::{cell::RefCell, convert::TryFrom, ops::Range},
+    fehler::throws,
+    ignore::WalkBuilder,
+    log::trace,
+    market::{ConsumeFailure, ConsumeFault, Consumer},
+    parse_display::Display as ParseDisplay,
+    regex::Regex,
+    std::{
+        fs, io,
+        path::Path,
+        rc::Rc,
+        sync::mpsc::{self, Receiver},
+        thread,
+    },
+    thiserror::Error as ThisError,
+    url::Url,
+};
+
+/// An error searching the root directory.
+#[derive(Debug, ConsumeFault, ThisError)]
+pub enum GrepError {
+    /// The pattern is not a valid regex.
+    #[error("{0}")]
+    Regex(#[from] regex::Error),
+    /// The root directory is not a file path.
+    #[error("`{0}` is not a file path")]
+    NotFilePath(Url),
+    /// The searching thread could not be spawned.
+    #[error("{0}")]
+    Spawn(#[from] io::Error),
+}
+
+/// Specifies a search of the root directory.
+#[derive(Debug, ParseDisplay)]
+#[display("Grep `{pattern}`")]
+pub(crate) struct GrepCommand {
+    /// The regex that is searched for.
+    pub(crate) pattern: String,
+}
+
+/// A line that matches the pattern of a search.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct GrepMatch {
+    /// The path of the file, relative to the root directory.
+    pub(crate) path: String,
+    /// The URL of the file, if its path can be represented as one.
+    pub(crate) url: Option<Url>,
+    /// The index of the line.
+    pub(crate) line: u32,
+    /// The text of the line, excluding its line ending.
+    pub(crate) text: String,
+    /// The char ranges of `text` that match the pattern.
+    pub(crate) ranges: Vec<Range<u32>>,
+}
+
+/// Creates the searcher of the files within `root_dir`.
+pub(crate) fn create_grep(root_dir: Url) -> (Grep, GrepConsumer) {
+    let receiver = Rc::new(RefCell::new(None));
+
+    (
+        Grep {
+            root_dir,
+            receiver: Rc::clone(&receiver),
+        },
+        GrepConsumer { receiver },
+    )
+}
+
+/// Returns the matches of `regex` within `text`, the text of the file at `path`.
+fn search(path: &str, url: Option<&Url>, text: &str, regex: &Regex) -> Vec<GrepMatch> {
+    let mut matches = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let ranges: Vec<Range<u32>> = regex
+            .find_iter(line)
+            .filter_map(|found| {
+                let start = line.get(..found.start())?.chars().count();
+                let end = start.saturating_add(found.as_str().chars().count());
+
+                Some(u32::try_from(start).ok()?..u32::try_from(end).ok()?)
+            })
+            .collect();
+
+        if !ranges.is_empty() {
+            if let Ok(line_index) = u32::try_from(index) {
+                matches.push(GrepMatch {
+                    path: path.to_string(),
+                    url: url.cloned(),
+                    line: line_index,
+                    text: line.to_string(),
+                    ranges,
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+/// Walks `dir` and sends the matches of `regex` within each file via `send`.
+///
+/// Files ignored by `.gitignore`, hidden files and files that are not valid UTF-8 are skipped. Walking stops once `send` fails. An empty batch is sent once every file has been searched.
+fn walk<F: Fn(Vec<GrepMatch>) -> bool>(dir: &Path, regex: &Regex, send: F) {
+    // Entries that cannot be read are skipped.
+    for entry in WalkBuilder::new(dir).build().flatten() {
+        if entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            if let (Some(path), Ok(text)) = (
+                entry
+                    .path()
+                    .strip_prefix(dir)
+                    .ok()
+                    .and_then(|path| path.to_str()),
+                fs::read_to_string(entry.path()),
+            ) {
+                let matches = search(
+                    &path.replace('\\', "/"),
+                    Url::from_file_path(entry.path()).ok().as_ref(),
+                    &text,
+                    regex,
+                );
+
+                if !matches.is_empty() && !send(matches) {
+                    return;
+                }
+            }
+        }
+    }
+
+    let _ = send(Vec::new());
+}
+
+/// Searches the files within the root directory.
+pub(crate) struct Grep {
+    /// The root directory.
+    root_dir: Url,
+    /// Receives the matches found by the current search.
+    receiver: Rc<RefCell<Option<Receiver<Vec<GrepMatch>>>>>,
+}
+
+impl Grep {
+    /// Starts the search specified by `command`.
+    ///
+    /// Any previous search is abandoned.
+    #[throws(GrepError)]
+    pub(crate) fn search(&self, command: &GrepCommand) {
+        let regex = Regex::new(&command.pattern)?;
+        #[allow(clippy::map_err_ignore)]
+        // Url::to_file_path() returns () as Err type so the error has no helpful information.
+        let dir = self
+            .root_dir
+            .to_file_path()
+            .map_err(|_| GrepError::NotFilePath(self.root_dir.clone()))?;
+        let (sender, receiver) = mpsc::channel();
+
+        trace!("grep `{}` in {}", command.pattern, dir.display());
+        // Dropping the previous receiver causes the previous thread to stop once it sends its next matches.
+        let _ = self.receiver.replace(Some(receiver));
+        let _ = thread::Builder::new()
+            .name("grep".to_string())
+            .spawn(move || walk(&dir, &regex, |matches| sender.send(matches).is_ok()))?;
+    }
+}
+
+/// Consumes the matches found by a search.
+pub(crate) struct GrepConsumer {
+    /// Receives the matches found by the current search.
+    receiver: Rc<RefCell<Option<Receiver<Vec<GrepMatch>>>>>,
+}
+
+impl Consumer for GrepConsumer {
+    type Good = Vec<GrepMatch>;
+    type Failure = ConsumeFailure<GrepError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        #[allow(clippy::map_err_ignore)]
+        // A disconnected receiver means the search is complete, which is equivalent to having no stock.
+        self.receiver
+            .borrow()
+            .as_ref()
+            .ok_or(ConsumeFailure::EmptyStock)?
+            .try_recv()
+            .map_err(|_| ConsumeFailure::EmptyStock)?
+    }
+}
+
+/// Testing of the `grep` module.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Matches shall be reported per line with char ranges.
+    #[test]
+    fn char_ranges() {
+        assert_eq!(
+            Regex::new("b+").map(|regex| search("a.txt", None, "\u{e9}bb b\r\nnone\n", &regex)),
+            Ok(vec![GrepMatch {
+                path: "a.txt".to_string(),
+                url: None,
+                line: 0,
+                text: "\u{e9}bb b".to_string(),
+                ranges: vec![1..3, 4..5],
+            }])
+        );
+    }
+}
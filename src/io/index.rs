@@ -0,0 +1,142 @@
+//! Implements the indexing of the paths of all files within the root directory.
+//!
+//! The directory tree is walked by a background thread so that a large tree does not block the application. Paths are sent in batches as they are found.
+use {
+    core::cell::RefCell,
+    fehler::throws,
+    ignore::WalkBuilder,
+    log::trace,
+    market::{ConsumeFailure, ConsumeFault, Consumer},
+    parse_display::Display as ParseDisplay,
+    std::{
+        io,
+        path::Path,
+        rc::Rc,
+        sync::mpsc::{self, Receiver},
+        thread,
+    },
+    thiserror::Error as ThisError,
+    url::Url,
+};
+
+/// The number of paths sent in a single batch.
+const BATCH_SIZE: usize = 512;
+
+/// An error indexing the root directory.
+#[derive(Debug, ConsumeFault, ThisError)]
+pub enum IndexError {
+    /// The root directory is not a file path.
+    #[error("`{0}` is not a file path")]
+    NotFilePath(Url),
+    /// The indexing thread could not be spawned.
+    #[error("{0}")]
+    Spawn(#[from] io::Error),
+}
+
+/// Specifies an indexing of the root directory.
+#[derive(Debug, ParseDisplay)]
+#[display("Index (hidden: {hidden})")]
+pub(crate) struct IndexCommand {
+    /// If hidden files are indexed.
+    pub(crate) hidden: bool,
+}
+
+/// Creates the indexer of the files within `root_dir`.
+pub(crate) fn create_indexer(root_dir: Url) -> (Indexer, IndexConsumer) {
+    let receiver = Rc::new(RefCell::new(None));
+
+    (
+        Indexer {
+            root_dir,
+            receiver: Rc::clone(&receiver),
+        },
+        IndexConsumer { receiver },
+    )
+}
+
+/// Walks `dir` and sends the paths of its files, relative to `dir`, in batches via `send`.
+///
+/// Files ignored by `.gitignore` are skipped, as are hidden files unless `hidden` is true. Walking stops once `send` fails.
+fn walk<F: Fn(Vec<String>) -> bool>(dir: &Path, hidden: bool, send: F) {
+    let mut batch = Vec::new();
+
+    // Entries that cannot be read are skipped.
+    for entry in WalkBuilder::new(dir).hidden(!hidden).build().flatten() {
+        if entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            if let Some(path) = entry
+                .path()
+                .strip_prefix(dir)
+                .ok()
+                .and_then(|path| path.to_str())
+            {
+                batch.push(path.replace('\\', "/"));
+            }
+
+            if batch.len() >= BATCH_SIZE && !send(batch.split_off(0)) {
+                return;
+            }
+        }
+    }
+
+    if !batch.is_empty() {
+        let _ = send(batch);
+    }
+}
+
+/// Indexes the files within the root directory.
+pub(crate) struct Indexer {
+    /// The root directory.
+    root_dir: Url,
+    /// Receives the paths found by the current indexing.
+    receiver: Rc<RefCell<Option<Receiver<Vec<String>>>>>,
+}
+
+impl Indexer {
+    /// Starts indexing as specified by `command`.
+    ///
+    /// Any previous indexing is abandoned.
+    #[throws(IndexError)]
+    pub(crate) fn index(&self, command: &IndexCommand) {
+        #[allow(clippy::map_err_ignore)]
+        // Url::to_file_path() returns () as Err type so the error has no helpful information.
+        let dir = self
+            .root_dir
+            .to_file_path()
+            .map_err(|_| IndexError::NotFilePath(self.root_dir.clone()))?;
+        let hidden = command.hidden;
+        let (sender, receiver) = mpsc::channel();
+
+        trace!("index {}", dir.display());
+        // Dropping the previous receiver causes the previous thread to stop once it sends its next batch.
+        let _ = self.receiver.replace(Some(receiver));
+        let _ = thread::Builder::new()
+            .name("index".to_string())
+            .spawn(move || walk(&dir, hidden, |batch| sender.send(batch).is_ok()))?;
+    }
+}
+
+/// Consumes the batches of indexed paths.
+pub(crate) struct IndexConsumer {
+    /// Receives the paths found by the current indexing.
+    receiver: Rc<RefCell<Option<Receiver<Vec<String>>>>>,
+}
+
+impl Consumer for IndexConsumer {
+    type Good = Vec<String>;
+    type Failure = ConsumeFailure<IndexError>;
+
+    #[throws(Self::Failure)]
+    fn consume(&self) -> Self::Good {
+        #[allow(clippy::map_err_ignore)]
+        // A disconnected receiver means the indexing is complete, which is equivalent to having no stock.
+        self.receiver
+            .borrow()
+            .as_ref()
+            .ok_or(ConsumeFailure::EmptyStock)?
+            .try_recv()
+            .map_err(|_| ConsumeFailure::EmptyStock)?
+    }
+}
@@ -0,0 +1,333 @@
+//! Implements the persistent storage of application data.
+//!
+//! Histories are written by a background thread so that storing a long history does not block the application.
+use {
+    crate::app::History,
+    fehler::throws,
+    log::{trace, warn},
+    parse_display::Display as ParseDisplay,
+    ropey::Rope,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        io::{self, BufRead, BufReader, BufWriter, Write},
+        path::PathBuf,
+        rc::Rc,
+        sync::{
+            mpsc::{self, Receiver, Sender},
+            Arc,
+        },
+        thread::{self, JoinHandle},
+    },
+    thiserror::Error as ThisError,
+    url::Url,
+};
+
+/// The offset basis of the 64-bit FNV-1a hash.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// The prime of the 64-bit FNV-1a hash.
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// The maximum number of commands kept in the command history.
+const MAX_COMMANDS: usize = 1000;
+
+/// Returns the 64-bit FNV-1a hash of `bytes`.
+///
+/// Unlike [`std::collections::hash_map::DefaultHasher`], the result is stable across releases of Rust, which is required for values that are stored.
+fn fnv_hash<I: IntoIterator<Item = u8>>(bytes: I) -> u64 {
+    bytes.into_iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// An error storing data.
+#[derive(Debug, ThisError)]
+pub(crate) enum StoreError {
+    /// An IO error.
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    /// An error serializing data.
+    #[error("{0}")]
+    Serialize(#[from] serde_json::Error),
+    /// The thread that writes histories has stopped.
+    #[error("history writer has stopped")]
+    WriterStopped,
+}
+
+/// Specifies data to be stored.
+#[derive(Debug, ParseDisplay)]
+pub(crate) enum StoreCommand {
+    /// Stores the history of a document.
+    #[display("Store history of `{url}`")]
+    History {
+        /// The URL of the document.
+        url: Url,
+        /// The text of the document when it was last written to its file.
+        text: Rope,
+        /// The history of the document.
+        history: Arc<History>,
+    },
+    /// Adds a command to the command history.
+    #[display("Record command `{command}`")]
+    Command {
+        /// The command.
+        command: String,
+    },
+}
+
+/// The stored history of a document.
+#[derive(Deserialize, Serialize)]
+struct HistoryRecord<H> {
+    /// The hash of the text of the document when it was last written to its file.
+    hash: u64,
+    /// The history.
+    history: H,
+}
+
+/// A history to be written to the file at `path`.
+struct HistoryWrite {
+    /// The path of the file.
+    path: PathBuf,
+    /// The text of the document when it was last written to its file.
+    text: Rope,
+    /// The history.
+    history: Arc<History>,
+}
+
+impl HistoryWrite {
+    /// Writes the history to its file.
+    #[throws(StoreError)]
+    fn write(&self) {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        serde_json::to_writer(
+            BufWriter::new(fs::File::create(&self.path)?),
+            &HistoryRecord {
+                hash: fnv_hash(self.text.bytes()),
+                history: &*self.history,
+            },
+        )?;
+    }
+}
+
+/// Writes each history received by `receiver` until every sender is dropped.
+///
+/// Of the pending histories of a file, only the latest is written.
+fn write_histories(receiver: &Receiver<HistoryWrite>) {
+    while let Ok(write) = receiver.recv() {
+        let mut pending = vec![write];
+
+        for write in receiver.try_iter() {
+            pending.retain(|pending_write| pending_write.path != write.path);
+            pending.push(write);
+        }
+
+        for write in pending {
+            if let Err(error) = write.write() {
+                warn!("unable to store `{}`: {}", write.path.display(), error);
+            }
+        }
+    }
+}
+
+/// Writes histories on a background thread.
+#[derive(Debug)]
+struct HistoryWriter {
+    /// Sends histories to the thread.
+    ///
+    /// Only `None` while `Self` is dropped.
+    sender: Option<Sender<HistoryWrite>>,
+    /// The thread.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HistoryWriter {
+    /// Creates a new [`HistoryWriter`], spawning its thread.
+    #[throws(io::Error)]
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            sender: Some(sender),
+            thread: Some(
+                thread::Builder::new()
+                    .name("history".to_string())
+                    .spawn(move || write_histories(&receiver))?,
+            ),
+        }
+    }
+
+    /// Sends `write` to the thread.
+    #[throws(StoreError)]
+    fn send(&self, write: HistoryWrite) {
+        #[allow(clippy::map_err_ignore)]
+        // The only cause of a send error is that the thread has stopped.
+        self.sender
+            .as_ref()
+            .ok_or(StoreError::WriterStopped)?
+            .send(write)
+            .map_err(|_| StoreError::WriterStopped)?;
+    }
+}
+
+impl Drop for HistoryWriter {
+    fn drop(&mut self) {
+        // Disconnecting the channel lets the thread write the pending histories and stop.
+        drop(self.sender.take());
+
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                warn!("history writer panicked");
+            }
+        }
+    }
+}
+
+/// Stores application data in the directories of the current user.
+#[derive(Clone, Debug)]
+pub(crate) struct Store {
+    /// The directory where the history of documents is stored.
+    ///
+    /// If `None`, history is not stored.
+    history_dir: Option<PathBuf>,
+    /// Writes histories in the background.
+    ///
+    /// If `None`, history is not stored.
+    history_writer: Option<Rc<HistoryWriter>>,
+    /// The file where the history of executed commands is stored, one command per line.
+    ///
+    /// If `None`, commands are not stored.
+    command_history_path: Option<PathBuf>,
+}
+
+impl Store {
+    /// Creates a new [`Store`].
+    pub(crate) fn new() -> Self {
+        let history_writer = match HistoryWriter::new() {
+            Ok(writer) => Some(Rc::new(writer)),
+            Err(error) => {
+                warn!("unable to start history writer: {}", error);
+                None
+            }
+        };
+
+        Self {
+            history_dir: dirs::cache_dir().map(|dir| dir.join("paper").join("history")),
+            history_writer,
+            command_history_path: dirs::data_dir()
+                .map(|dir| dir.join("paper").join("command_history")),
+        }
+    }
+
+    /// Returns the stored commands, from oldest to newest.
+    ///
+    /// If more than [`MAX_COMMANDS`] are stored, the oldest are discarded.
+    pub(crate) fn load_commands(&self) -> Vec<String> {
+        let mut commands = self
+            .command_history_path
+            .as_ref()
+            .and_then(|path| fs::File::open(path).ok())
+            .map(|file| {
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let excess = commands.len().saturating_sub(MAX_COMMANDS);
+
+        if excess > 0 {
+            let _ = commands.drain(..excess);
+
+            if let Err(error) = self.write_commands(&commands) {
+                warn!("unable to truncate command history: {}", error);
+            }
+        }
+
+        commands
+    }
+
+    /// Replaces the stored commands with `commands`.
+    #[throws(StoreError)]
+    fn write_commands(&self, commands: &[String]) {
+        if let Some(path) = self.command_history_path.as_ref() {
+            let mut writer = BufWriter::new(fs::File::create(path)?);
+
+            for command in commands {
+                writeln!(writer, "{}", command)?;
+            }
+
+            writer.flush()?;
+        }
+    }
+
+    /// Returns the path of the file that stores the history of the document at `url`.
+    fn history_path(&self, url: &Url) -> Option<PathBuf> {
+        self.history_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}.json", fnv_hash(url.as_str().bytes()))))
+    }
+
+    /// Returns the stored history of the document at `url`.
+    ///
+    /// The history is only returned if it was stored when the file last written matched `text`, continuing from the revision that was written; otherwise it is discarded.
+    pub(crate) fn load_history(&self, url: &Url, text: &Rope) -> Option<History> {
+        let path = self.history_path(url)?;
+        let record: HistoryRecord<History> = fs::File::open(&path)
+            .ok()
+            .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())?;
+
+        if let Some(history) = (record.hash == fnv_hash(text.bytes()))
+            .then(|| record.history.into_saved())
+            .flatten()
+        {
+            trace!("restored history of `{}`", url);
+            Some(history)
+        } else {
+            trace!("discarded outdated history of `{}`", url);
+
+            if let Err(error) = fs::remove_file(&path) {
+                warn!("unable to remove `{}`: {}", path.display(), error);
+            }
+
+            None
+        }
+    }
+
+    /// Stores the data specified by `command`.
+    #[throws(StoreError)]
+    pub(crate) fn store(&self, command: StoreCommand) {
+        match command {
+            StoreCommand::History { url, text, history } => {
+                if let (Some(path), Some(writer)) =
+                    (self.history_path(&url), self.history_writer.as_ref())
+                {
+                    writer.send(HistoryWrite {
+                        path,
+                        text,
+                        history,
+                    })?;
+                }
+            }
+            StoreCommand::Command { command } => {
+                if let Some(path) = self.command_history_path.as_ref() {
+                    if let Some(dir) = path.parent() {
+                        fs::create_dir_all(dir)?;
+                    }
+
+                    writeln!(
+                        fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(path)?,
+                        "{}",
+                        command
+                    )?;
+                }
+            }
+        }
+    }
+}
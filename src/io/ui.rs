@@ -1,25 +1,29 @@
 //! Implements the interface between the user and the application.
 //!
 //! Visual output is organized as follows:
-//! - A header is displayed on a single row at the top of the display. The header displays general information about the current state of the system.
+//! - A header is displayed on a single row at the top of the display. The header displays general information about the current state of the system, followed by the tab strip.
 //! - A command bar is display on the row under the header. The command bar displays the current command being built by the user.
-//! - A page is displayed in the remaining space of the display. The page displays the text of the currently viewed document.
+//! - A page is displayed in the remaining space of the display. The page is split into [`Region`]s, each of which displays the text of a document.
 mod error;
 
 pub(crate) use error::{CreateTerminalError, DisplayCmdFailure, UserActionFailure};
 
 use {
     core::{
-        cell::{RefCell, RefMut},
+        cell::{Cell, RefCell, RefMut},
         convert::TryFrom,
         ops::Deref,
         time::Duration,
     },
     crossterm::{
-        cursor::{Hide, MoveTo},
-        event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+        cursor::{Hide, MoveTo, Show},
+        event::{
+            self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+            EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent,
+            MouseEventKind,
+        },
         execute, queue,
-        style::{Color, Print, ResetColor, SetBackgroundColor},
+        style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
         terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
     },
     error::{DestroyError, InitError, PollFailure, ReachedEnd, ReadFailure, WriteFailure},
@@ -27,6 +31,8 @@ use {
     market::{Consumer, ProduceFailure, Producer},
     parse_display::Display as ParseDisplay,
     std::io::{self, Stdout},
+    unicode_segmentation::UnicodeSegmentation,
+    unicode_width::UnicodeWidthStr,
 };
 
 /// A instantaneous duration of time.
@@ -39,9 +45,14 @@ fn is_action_available() -> bool {
 }
 
 /// Reads a current [`UserAction`], blocking until one is received.
+///
+/// Returns `None` for a key event that is not a press, such as the release that some terminals report after each press.
 #[throws(ReadFailure)]
-fn read_action() -> UserAction {
-    event::read().map(UserAction::from)?
+fn read_action() -> Option<UserAction> {
+    match event::read()? {
+        Event::Key(KeyEvent { kind, .. }) if kind != KeyEventKind::Press => None,
+        event => Some(event.into()),
+    }
 }
 
 /// Produces all [`DisplayCmd`]s via the stdout of the application.
@@ -77,13 +88,15 @@ impl Producer for Terminal {
     #[throws(Self::Failure)]
     fn produce(&self, good: Self::Good) {
         match good {
-            DisplayCmd::Rows { rows } => {
+            DisplayCmd::Rows { region, rows } => {
                 // The top 2 rows are reserved for the header and command bar.
-                let mut row_id = Unit(2);
+                let mut row_id = Unit(2)
+                    .forward_checked(region.row.into())
+                    .ok_or_else(|| ProduceFailure::Fault(ReachedEnd.into()))?;
 
-                for row in rows {
+                for row in rows.into_iter().take(region.size.height.into()) {
                     self.presenter
-                        .single_line(row_id, row.texts)
+                        .region_line(row_id, region.column, row.fit(region.size.width.into()))
                         .map_err(|failure| market::ProduceFailure::Fault(failure.into()))?;
 
                     row_id = row_id
@@ -91,14 +104,33 @@ impl Producer for Terminal {
                         .ok_or_else(|| ProduceFailure::Fault(ReachedEnd.into()))?;
                 }
             }
-            DisplayCmd::Command { command } => {
+            DisplayCmd::Command { command, cursor } => {
+                self.presenter.set_cursor(
+                    cursor
+                        .and_then(|column| u16::try_from(column).ok())
+                        .map(|column| (column, 1)),
+                );
                 self.presenter
                     .single_line(Unit(1), vec![StyledText::new(command, Style::Default)])
                     .map_err(|failure| market::ProduceFailure::Fault(failure.into()))?;
             }
-            DisplayCmd::Header { header } => {
+            DisplayCmd::Header { header, tabs } => {
+                let mut texts = vec![StyledText::new(header, Style::Default)];
+
+                texts.extend(tabs);
+                self.presenter
+                    .single_line(Unit(0), texts)
+                    .map_err(|failure| market::ProduceFailure::Fault(failure.into()))?;
+            }
+            DisplayCmd::Notice { notice } => {
+                self.presenter.set_cursor(None);
+                self.presenter
+                    .single_line(Unit(1), vec![notice])
+                    .map_err(|failure| market::ProduceFailure::Fault(failure.into()))?;
+            }
+            DisplayCmd::Copy { text } => {
                 self.presenter
-                    .single_line(Unit(0), vec![StyledText::new(header, Style::Default)])
+                    .copy(&text)
                     .map_err(|failure| market::ProduceFailure::Fault(failure.into()))?;
             }
         }
@@ -115,7 +147,9 @@ impl Consumer for UserActionConsumer {
     #[throws(Self::Failure)]
     fn consume(&self) -> Self::Good {
         if is_action_available().map_err(|error| market::ConsumeFailure::Fault(error.into()))? {
-            read_action().map_err(|error| market::ConsumeFailure::Fault(error.into()))?
+            read_action()
+                .map_err(|error| market::ConsumeFailure::Fault(error.into()))?
+                .ok_or(market::ConsumeFailure::EmptyStock)?
         } else {
             throw!(market::ConsumeFailure::EmptyStock);
         }
@@ -127,6 +161,10 @@ impl Consumer for UserActionConsumer {
 struct Presenter {
     /// The stdout of the application.
     out: RefCell<Stdout>,
+    /// The column and row of the visible cursor.
+    ///
+    /// If `None`, the cursor is hidden.
+    cursor: Cell<Option<(u16, u16)>>,
 }
 
 impl Presenter {
@@ -136,11 +174,19 @@ impl Presenter {
     }
 
     /// Initializes the interface, saving the current display and hiding the cursor.
+    ///
+    /// Bracketed paste is enabled so that pasted text is received as a single [`UserAction::Paste`] rather than as keys. Mouse capture is enabled so that mouse events are received as [`UserAction::Mouse`].
     #[throws(InitError)]
     fn init(&self) {
         // Required to store out due to macro calling out_mut() multiple times.
         let mut out = self.out_mut();
-        execute!(out, EnterAlternateScreen, Hide)?;
+        execute!(
+            out,
+            EnterAlternateScreen,
+            EnableBracketedPaste,
+            EnableMouseCapture,
+            Hide
+        )?;
     }
 
     /// Closes out the interface display, returning to the display prior to initialization.
@@ -148,25 +194,72 @@ impl Presenter {
     fn destroy(&self) {
         // Required to store out due to macro calling out_mut() multiple times.
         let mut out = self.out_mut();
-        execute!(out, LeaveAlternateScreen)?;
+        execute!(
+            out,
+            DisableMouseCapture,
+            DisableBracketedPaste,
+            LeaveAlternateScreen
+        )?;
     }
 
-    /// Writes `text` at `row`.
+    /// Sets the position of the visible cursor, hiding it if `cursor` is `None`.
+    ///
+    /// The cursor is placed by the next write.
+    fn set_cursor(&self, cursor: Option<(u16, u16)>) {
+        self.cursor.set(cursor);
+    }
+
+    /// Writes `text` at `row`, clearing the rest of the row.
     #[throws(WriteFailure)]
     fn single_line(&self, row: Unit, styled_texts: Vec<StyledText>) {
+        self.write(row, Unit(0), styled_texts, true)?;
+    }
+
+    /// Writes `text` at `column` of `row`, leaving the rest of the row untouched.
+    #[throws(WriteFailure)]
+    fn region_line(&self, row: Unit, column: Unit, styled_texts: Vec<StyledText>) {
+        self.write(row, column, styled_texts, false)?;
+    }
+
+    /// Writes `text` at `column` of `row`, clearing the rest of the row if `clears_rest`.
+    #[throws(WriteFailure)]
+    fn write(&self, row: Unit, column: Unit, styled_texts: Vec<StyledText>, clears_rest: bool) {
         // Required to store out due to macro calling out_mut() multiple times.
         let mut out = self.out_mut();
-        queue!(out, MoveTo(0, *row),)?;
+        queue!(out, MoveTo(*column, *row),)?;
 
         for styled_text in styled_texts {
             queue!(
                 out,
                 SetBackgroundColor(styled_text.background()),
+                SetForegroundColor(styled_text.foreground()),
                 Print(styled_text.text),
             )?;
         }
 
-        execute!(out, ResetColor, Clear(ClearType::UntilNewLine))?;
+        queue!(out, ResetColor)?;
+
+        if clears_rest {
+            queue!(out, Clear(ClearType::UntilNewLine))?;
+        }
+
+        // Writing moves the cursor so it must be placed after every write.
+        if let Some((column, cursor_row)) = self.cursor.get() {
+            execute!(out, MoveTo(column, cursor_row), Show)?;
+        } else {
+            execute!(out, Hide)?;
+        }
+    }
+
+    /// Copies `text` to the clipboard of the terminal via the OSC 52 escape sequence.
+    #[throws(WriteFailure)]
+    fn copy(&self, text: &str) {
+        // Required to store out due to macro calling out_mut() multiple times.
+        let mut out = self.out_mut();
+        execute!(
+            out,
+            Print(format!("\u{1b}]52;c;{}\u{7}", base64::encode(text)))
+        )?;
     }
 }
 
@@ -174,12 +267,13 @@ impl Default for Presenter {
     fn default() -> Self {
         Self {
             out: RefCell::new(io::stdout()),
+            cursor: Cell::new(None),
         }
     }
 }
 
 /// Input generated by the user.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub(crate) enum UserAction {
     /// The dimensions of the page have been updated.
     Resize {
@@ -187,7 +281,14 @@ pub(crate) enum UserAction {
         dimensions: Dimensions,
     },
     /// A mouse event has occurred.
-    Mouse,
+    Mouse {
+        /// What the mouse did, including the button that was used.
+        kind: MouseEventKind,
+        /// The row of the page at which the event occurred, or `None` if it occurred on the header or command bar.
+        row: Option<Unit>,
+        /// The column at which the event occurred.
+        column: Unit,
+    },
     /// A key has been pressed.
     Key {
         /// The key.
@@ -195,6 +296,10 @@ pub(crate) enum UserAction {
         /// The modifiers held when the key was pressed.
         modifiers: KeyModifiers,
     },
+    /// Text has been pasted.
+    Paste(String),
+    /// The terminal has gained or lost focus.
+    Focus,
 }
 
 impl From<Event> for UserAction {
@@ -208,8 +313,22 @@ impl From<Event> for UserAction {
                     width: columns.into(),
                 },
             },
-            Event::Mouse(..) => Self::Mouse,
+            Event::Mouse(mouse) => mouse.into(),
             Event::Key(key) => key.into(),
+            Event::Paste(text) => Self::Paste(text),
+            Event::FocusGained | Event::FocusLost => Self::Focus,
+        }
+    }
+}
+
+impl From<MouseEvent> for UserAction {
+    #[inline]
+    fn from(value: MouseEvent) -> Self {
+        Self::Mouse {
+            kind: value.kind,
+            // The top 2 rows are reserved for the header and command bar.
+            row: value.row.checked_sub(2).map(Unit::from),
+            column: value.column.into(),
         }
     }
 }
@@ -228,8 +347,10 @@ impl From<KeyEvent> for UserAction {
 #[derive(Debug, ParseDisplay)]
 #[display("DisplayCmd")]
 pub(crate) enum DisplayCmd {
-    /// Display rows of text.
+    /// Display rows of text within a region of the page.
     Rows {
+        /// The [`Region`] in which the rows are displayed.
+        region: Region,
         /// The rows to be displayed.
         rows: Vec<RowText>,
     },
@@ -237,25 +358,49 @@ pub(crate) enum DisplayCmd {
     Command {
         /// The text of the command bar.
         command: String,
+        /// The column of the cursor within the command bar.
+        ///
+        /// If `None`, the cursor is hidden.
+        cursor: Option<usize>,
     },
     /// Displays the header.
     Header {
         /// The header text.
         header: String,
+        /// The labels of the tab strip that follows the header text.
+        tabs: Vec<StyledText>,
+    },
+    /// Displays a notice in the command bar.
+    Notice {
+        /// The notice.
+        notice: StyledText,
+    },
+    /// Copies text to the clipboard of the terminal.
+    Copy {
+        /// The text to be copied.
+        text: String,
     },
 }
 
 /// Describes the style of a text.
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum Style {
     /// Text is default.
     Default,
     /// Text is selected by the user.
     Selection,
+    /// Text describes an error.
+    Error,
+    /// Text is highlighted.
+    Highlight,
+    /// Text matches a search.
+    Match,
+    /// Text is in the gutter beside a document.
+    Gutter,
 }
 
 /// Describes a text with a given [`Style`].
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct StyledText {
     /// The text.
     text: String,
@@ -272,14 +417,25 @@ impl StyledText {
     /// Returns the background color of `self`.
     const fn background(&self) -> Color {
         match self.style {
-            Style::Default => Color::Reset,
+            Style::Default | Style::Highlight | Style::Gutter => Color::Reset,
             Style::Selection => Color::DarkGrey,
+            Style::Error => Color::DarkRed,
+            Style::Match => Color::DarkYellow,
+        }
+    }
+
+    /// Returns the foreground color of `self`.
+    const fn foreground(&self) -> Color {
+        match self.style {
+            Style::Default | Style::Selection | Style::Error | Style::Match => Color::Reset,
+            Style::Highlight => Color::Yellow,
+            Style::Gutter => Color::DarkGrey,
         }
     }
 }
 
 /// Describes the texts that make up a row.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct RowText {
     /// The [`StyledText`]s that make up a row.
     texts: Vec<StyledText>,
@@ -290,6 +446,63 @@ impl RowText {
     pub(crate) fn new(texts: Vec<StyledText>) -> Self {
         Self { texts }
     }
+
+    /// Returns the [`StyledText`]s of `self` cut or padded to exactly `width` columns.
+    ///
+    /// Each grapheme cluster takes up its display width; one that would cross the last column is replaced by padding.
+    fn fit(self, width: usize) -> Vec<StyledText> {
+        let mut remaining = width;
+        let mut texts = Vec::new();
+        let mut is_cut = false;
+
+        for styled_text in self.texts {
+            if is_cut {
+                break;
+            }
+
+            let mut text = String::new();
+
+            for grapheme in styled_text.text.graphemes(true) {
+                let grapheme_width = grapheme.width();
+
+                if grapheme_width > remaining {
+                    is_cut = true;
+                    break;
+                }
+
+                remaining = remaining.saturating_sub(grapheme_width);
+                text.push_str(grapheme);
+            }
+
+            texts.push(StyledText::new(text, styled_text.style));
+        }
+
+        if remaining > 0 {
+            texts.push(StyledText::new(" ".repeat(remaining), Style::Default));
+        }
+
+        texts
+    }
+}
+
+/// A rectangular area of the page.
+#[derive(Clone, Copy, Debug, Default, Eq, ParseDisplay, PartialEq)]
+#[display("{size} at {row}r {column}c")]
+pub(crate) struct Region {
+    /// The row of the page at which the region starts.
+    pub(crate) row: Unit,
+    /// The column at which the region starts.
+    pub(crate) column: Unit,
+    /// The [`Dimensions`] of the region.
+    pub(crate) size: Dimensions,
+}
+
+impl Region {
+    /// Returns if the cell at `row` and `column` of the page is within `self`.
+    pub(crate) fn contains(self, row: Unit, column: Unit) -> bool {
+        (*self.row..self.row.saturating_add(*self.size.height)).contains(&*row)
+            && (*self.column..self.column.saturating_add(*self.size.width)).contains(&*column)
+    }
 }
 
 /// The dimensions of a grid.
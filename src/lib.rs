@@ -16,7 +16,7 @@ mod logging;
 mod orient;
 
 // Export so that other crates can build Arguments.
-pub use logging::LogConfig;
+pub use {io::ClipboardConfig, logging::LogConfig};
 
 use {
     app::{Processor, ScopeFromRangeError},
@@ -43,6 +43,9 @@ pub struct Arguments {
     #[allow(clippy::missing_docs_in_private_items)] // Flattened structs do not allow doc comments.
     #[structopt(flatten)]
     log_config: LogConfig,
+    #[allow(clippy::missing_docs_in_private_items)] // Flattened structs do not allow doc comments.
+    #[structopt(flatten)]
+    clipboard_config: ClipboardConfig,
 }
 
 /// An instance of the `paper` application.
@@ -77,9 +80,11 @@ impl Paper {
         // Logger is created first so all other parts can use it.
         logging::init(arguments.log_config)?;
 
+        let io = Interface::new(arguments.file, arguments.clipboard_config)?;
+
         Self {
-            io: Interface::new(arguments.file)?,
-            processor: Processor::new(),
+            processor: Processor::new(io.command_history()),
+            io,
         }
     }
 
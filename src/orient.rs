@@ -1,4 +1,5 @@
 //! Describes orientations of a direction.
+use enum_map::Enum;
 
 /// A direction along an axis.
 #[derive(Clone, Copy, Debug)]
@@ -28,7 +29,7 @@ struct PlanarDirection {
 }
 
 /// Describes a direction on a planar surface that is facing the user.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Enum, PartialEq)]
 pub(crate) enum ScreenDirection {
     /// Towards the top of the screen.
     Up,